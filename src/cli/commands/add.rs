@@ -1,7 +1,209 @@
-use std::path::PathBuf;
-use crate::{lib::errors::Errors, core::index::Index, cli::configure_input_paths};
+use std::{env, fs, collections::BTreeMap, io::{self, BufRead, Write}, path::{Path, PathBuf}, process};
+use crate::{lib::{errors::{Errors, Context}, diff::{Hunk, hunks, patch}, config::Config, layout::Layout, locale, read_object_bytes, file_mode}, core::{index::{Index, Stage}, head::Head, tree::Tree, blob::Blob}, cli::configure_input_paths};
 
-pub fn add(paths: Vec<PathBuf>) -> Result<(), Errors> {
+const CONTEXT_LINES: usize = 3;
+const EDITOR_KEY: &str = "core.editor";
+const DEFAULT_EDITOR: &str = "vi";
+const NO_NEWLINE_MARKER: &str = "\\ No newline at end of file";
+const PATCH_HELP: &str = "\
+y - stage this hunk
+n - do not stage this hunk
+q - quit; do not stage this hunk or any of the remaining ones
+a - stage this hunk and all later hunks in the file
+d - do not stage this hunk or any of the later hunks in the file
+s - split the current hunk into smaller hunks
+e - manually edit the current hunk
+? - print help
+";
+const EDIT_INSTRUCTIONS: &str = "\
+# ---
+# To remove '-' lines, make them ' ' lines (context).
+# To remove '+' lines, delete them.
+# Lines starting with # will be removed.
+";
+
+enum Outcome {
+  Next,
+  Quit,
+}
+
+pub fn add(paths: Vec<PathBuf>, patch: bool) -> Result<(), Errors> {
   let file_paths = configure_input_paths(&paths)?;
-  Index::add(&file_paths)
+
+  match patch {
+    true => add_patch(&file_paths),
+    false => Index::add(&file_paths),
+  }
+}
+
+// Offers the changes to tracked text files hunk by hunk, staging the picked ones on top of what is
+// already staged.
+fn add_patch(paths: &[PathBuf]) -> Result<(), Errors> {
+  let locale = locale();
+  let tree = match Head::get()?.commit()? {
+    Some(commit) => commit.tree()?,
+    None => Tree::new(),
+  };
+  let mut staged_ids = tree.blobs()
+    .into_iter()
+    .map(|(path, blob)| (path, String::from(blob.id())))
+    .collect::<BTreeMap<PathBuf, String>>();
+
+  for (path, stage) in Index::get()?.staged_paths() {
+    match stage {
+      Stage::Blob(blob) => staged_ids.insert(path.clone(), String::from(blob.id())),
+      Stage::Deleted => staged_ids.remove(path),
+    };
+  }
+
+  let stdin = io::stdin();
+  let mut input = stdin.lock().lines();
+
+  for (path, id) in staged_ids {
+    let location = locale.as_std_path().join(&path);
+
+    if !paths.is_empty() && !paths.iter().any(|prefix| location.starts_with(prefix)) {
+      continue;
+    }
+
+    let metadata = match fs::symlink_metadata(&location) {
+      Ok(metadata) if metadata.is_file() => metadata,
+      _ => continue,
+    };
+
+    let (old, new) = match (String::from_utf8(read_object_bytes(&id)?), fs::read_to_string(&location)) {
+      (Ok(old), Ok(new)) if old != new => (old, new),
+      _ => continue,
+    };
+
+    let old_lines = old.split_inclusive('\n').collect::<Vec<&str>>();
+    let new_lines = new.split_inclusive('\n').collect::<Vec<&str>>();
+    let (picked, outcome) = pick_hunks(&path, hunks(&old_lines, &new_lines, CONTEXT_LINES), &mut input)?;
+
+    if !picked.is_empty() {
+      let blob = Blob::new(patch(&old_lines, &picked).concat())?.with_mode(file_mode(&metadata));
+      Index::add_blob(&location, blob)?;
+    }
+
+    if let Outcome::Quit = outcome {
+      break;
+    }
+  }
+
+  Ok(())
+}
+
+fn pick_hunks<I: Iterator<Item = io::Result<String>>>(path: &Path, mut hunks: Vec<Hunk>, input: &mut I) -> Result<(Vec<Hunk>, Outcome), Errors> {
+  let mut picked = Vec::new();
+  let mut index = 0;
+
+  let path = path.display();
+  println!("diff --rgit a/{} b/{}\n--- a/{}\n+++ b/{}", path, path, path, path);
+
+  while index < hunks.len() {
+    let hunk = &hunks[index];
+    print!("{}\n{}({}/{}) Stage this hunk [y,n,q,a,d,s,e,?]? ", hunk.header(), format_lines(hunk), index + 1, hunks.len());
+    io::stdout().flush()?;
+
+    let answer = match input.next() {
+      Some(answer) => answer?,
+      None => return Ok((picked, Outcome::Quit)),
+    };
+
+    match answer.trim() {
+      "y" => {
+        picked.push(hunk.clone());
+        index += 1;
+      },
+      "n" => index += 1,
+      "q" => return Ok((picked, Outcome::Quit)),
+      "a" => {
+        picked.extend_from_slice(&hunks[index..]);
+        break;
+      },
+      "d" => break,
+      "s" => {
+        let split = hunk.split();
+
+        match split.len() {
+          1 => println!("Sorry, cannot split this hunk"),
+          count => {
+            println!("Split into {} hunks.", count);
+            hunks.splice(index..index + 1, split);
+          },
+        }
+      },
+      "e" => match edit_hunk(hunk)? {
+        Some(edited) => {
+          picked.push(edited);
+          index += 1;
+        },
+        None => println!("Your edited hunk does not apply"),
+      },
+      _ => print!("{}", PATCH_HELP),
+    }
+  }
+
+  Ok((picked, Outcome::Next))
+}
+
+fn edit_hunk(hunk: &Hunk) -> Result<Option<Hunk>, Errors> {
+  let location = locale().join(Layout::get().hunk_edit_path());
+  let context = || format!("unable to write '{}'", location);
+
+  fs::write(&location, format!("# Manual hunk edit mode\n{}\n{}{}", hunk.header(), format_lines(hunk), EDIT_INSTRUCTIONS)).context(context)?;
+
+  let editor = Config::load()?.get(EDITOR_KEY).cloned()
+    .or_else(|| env::var("VISUAL").ok())
+    .or_else(|| env::var("EDITOR").ok())
+    .unwrap_or_else(|| String::from(DEFAULT_EDITOR));
+  let status = process::Command::new("sh")
+    .arg("-c")
+    .arg(format!("{} \"$@\"", editor))
+    .arg(&editor)
+    .arg(&location)
+    .status()
+    .context(|| format!("unable to run editor '{}'", editor))?;
+
+  if !status.success() {
+    return Err(Errors::BadArguments(format!("the editor '{}' failed", editor)));
+  }
+
+  let text = fs::read_to_string(&location).context(|| format!("unable to read '{}'", location))?;
+  fs::remove_file(&location).ok();
+
+  Ok(hunk.with_lines(parse_lines(&text)))
+}
+
+// Lines keep their line breaks, so a missing one at the end of the file is marked as in Git.
+fn format_lines(hunk: &Hunk) -> String {
+  hunk.lines()
+    .iter()
+    .map(|line| match line.ends_with('\n') {
+      true => line.clone(),
+      false => format!("{}\n{}\n", line, NO_NEWLINE_MARKER),
+    })
+    .collect()
+}
+
+fn parse_lines(text: &str) -> Vec<String> {
+  let mut lines: Vec<String> = Vec::new();
+
+  for line in text.split_inclusive('\n') {
+    if line.starts_with('#') || line.starts_with("@@") {
+      continue;
+    }
+
+    if line.trim_end_matches('\n') == NO_NEWLINE_MARKER {
+      if let Some(last) = lines.last_mut() {
+        last.pop();
+      }
+    } else if line == "\n" {
+      lines.push(String::from(" \n"));
+    } else {
+      lines.push(String::from(line));
+    }
+  }
+
+  lines
 }
@@ -0,0 +1,201 @@
+use std::{process, path::PathBuf};
+use clap::{Subcommand, AppSettings};
+use crate::{lib::{errors::{Errors, Context}, locale}, core::{bisect::{Bisect, Term, Step, describe}, head::Reference, revision::Revision, commit::Commit}};
+use super::{switch::switch_keeping, status::{is_clean, untracked_files}};
+
+const SKIP_EXIT_CODE: i32 = 125;
+const ABORT_EXIT_CODE: i32 = 128;
+
+#[derive(Subcommand)]
+pub enum BisectAction {
+  #[clap(about = "Starts bisecting, optionally marking a bad and some good commits")]
+  Start {
+    bad: Option<String>,
+    good: Vec<String>,
+  },
+
+  #[clap(about = "Marks commits as good (defaults to HEAD)")]
+  Good {
+    revisions: Vec<String>,
+  },
+
+  #[clap(about = "Marks a commit as bad (defaults to HEAD)")]
+  Bad {
+    revision: Option<String>,
+  },
+
+  #[clap(about = "Marks commits as untestable (defaults to HEAD)")]
+  Skip {
+    revisions: Vec<String>,
+  },
+
+  #[clap(about = "Ends bisecting and switches back to where it started")]
+  Reset,
+
+  #[clap(about = "Prints the commits marked so far")]
+  Log,
+
+  #[clap(about = "Marks each commit by the exit code of a command: 0 for good, 125 to skip, 1 to 127 for bad")]
+  #[clap(setting = AppSettings::TrailingVarArg)]
+  Run {
+    #[clap(required = true, allow_hyphen_values = true)]
+    command: Vec<String>,
+  },
+}
+
+pub fn bisect(action: BisectAction) -> Result<(), Errors> {
+  match action {
+    BisectAction::Start { bad, good } => {
+      ensure_clean()?;
+      let mut bisect = Bisect::start()?;
+
+      if let Some(bad) = bad {
+        bisect.mark(Term::Bad, &Revision::resolve(&bad)?)?;
+      }
+
+      for revision in good {
+        bisect.mark(Term::Good, &Revision::resolve(&revision)?)?;
+      }
+
+      advance(&bisect).map(|_| ())
+    },
+    BisectAction::Good { revisions } => mark(Term::Good, revisions),
+    BisectAction::Bad { revision } => mark(Term::Bad, revision.into_iter().collect()),
+    BisectAction::Skip { revisions } => mark(Term::Skip, revisions),
+    BisectAction::Reset => {
+      ensure_clean()?;
+      let bisect = Bisect::get()?;
+      let kept_files = match bisect.start_reference()? {
+        Reference::Branch(branch) => kept_files(branch.commit()?.as_ref())?,
+        Reference::Commit(commit) => kept_files(Some(&commit))?,
+      };
+
+      match bisect.reset()? {
+        Reference::Branch(branch) => switch_keeping(false, false, branch.name().clone(), &kept_files),
+        Reference::Commit(commit) => switch_keeping(false, true, commit.id().clone(), &kept_files),
+      }
+    },
+    BisectAction::Log => {
+      print!("{}", Bisect::log()?);
+      Ok(())
+    },
+    BisectAction::Run { command } => run(&command),
+  }
+}
+
+fn mark(term: Term, revisions: Vec<String>) -> Result<(), Errors> {
+  ensure_clean()?;
+  let mut bisect = Bisect::get()?;
+  let revisions = match revisions.is_empty() {
+    true => vec![String::from("HEAD")],
+    false => revisions,
+  };
+
+  for revision in revisions {
+    bisect.mark(term, &Revision::resolve(&revision)?)?;
+  }
+
+  advance(&bisect).map(|_| ())
+}
+
+// Checks out the next commit to test, returning whether the search is over.
+fn advance(bisect: &Bisect) -> Result<bool, Errors> {
+  match bisect.next()? {
+    Step::Waiting { bad, good } => {
+      match (bad, good) {
+        (false, false) => println!("status: waiting for both good and bad commits"),
+        (true, _) => println!("status: waiting for good commit(s), bad commit known"),
+        (_, true) => println!("status: waiting for bad commit, good commit(s) known"),
+      }
+
+      Ok(false)
+    },
+    Step::Test { commit, remaining } => {
+      println!("Bisecting: {} revisions left to test after this (roughly {} steps)", remaining, usize::BITS - remaining.leading_zeros());
+      println!("{}", describe(&commit));
+
+      switch_keeping(false, true, commit.id().clone(), &kept_files(Some(&commit))?).map(|_| false)
+    },
+    Step::Found(commit) => {
+      let mut output = format!("{} is the first bad commit\ncommit {}\nAuthor: {} <{}>\nDate:   {}\n\n", commit.id(), commit.id(), commit.author().username(), commit.author().email(), commit.author().date());
+
+      for line in commit.message().lines() {
+        output += &format!("    {}\n", line);
+      }
+
+      print!("{}", output);
+      bisect.finish(&commit).map(|_| true)
+    },
+    Step::OnlySkipped(commits) => {
+      let ids = commits.iter().map(|commit| format!("{}\n", commit.id())).collect::<String>();
+      print!("There are only 'skip'ped commits left to test.\nThe first bad commit could be any of:\n{}We cannot bisect more!\n", ids);
+
+      Ok(true)
+    },
+  }
+}
+
+// Switching clears the working tree, so any change to tracked files there would be lost. The check
+// comes before anything is marked, which leaves the session as it was.
+fn ensure_clean() -> Result<(), Errors> {
+  match is_clean()? {
+    true => Ok(()),
+    false => Err(Errors::LocalChanges),
+  }
+}
+
+// Untracked files stay through a switch, unless the commit switched to holds them, or a file in
+// place of a folder along them, or a folder in their place.
+fn kept_files(commit: Option<&Commit>) -> Result<Vec<PathBuf>, Errors> {
+  let untracked_files = untracked_files()?;
+  let tree = match commit {
+    Some(commit) => commit.tree()?,
+    None => return Ok(untracked_files),
+  };
+
+  for (path, _) in tree.blob_iter() {
+    if let Some(untracked_file) = untracked_files.iter().find(|file| file.starts_with(&path) || path.starts_with(file)) {
+      return Err(Errors::UntrackedFile(untracked_file.display().to_string()));
+    }
+  }
+
+  Ok(untracked_files)
+}
+
+// Runs the command from the top of the repository on each commit to test, the way aliases run
+// shell commands.
+fn run(command: &[String]) -> Result<(), Errors> {
+  let mut bisect = Bisect::get()?;
+
+  let mut is_done = match bisect.next()? {
+    Step::Waiting { .. } => return Err(Errors::BadArguments(String::from("bisect run needs a bad and a good commit"))),
+    Step::Test { .. } => false,
+    Step::Found(_) | Step::OnlySkipped(_) => advance(&bisect)?,
+  };
+
+  while !is_done {
+    println!("running {}", command.join(" "));
+
+    let status = process::Command::new("sh")
+      .arg("-c")
+      .arg(format!("{} \"$@\"", command[0]))
+      .arg(&command[0])
+      .args(&command[1..])
+      .current_dir(locale())
+      .status()
+      .context(|| format!("unable to run '{}'", command[0]))?;
+
+    let term = match status.code() {
+      Some(0) => Term::Good,
+      Some(SKIP_EXIT_CODE) => Term::Skip,
+      Some(code) if (1..ABORT_EXIT_CODE).contains(&code) => Term::Bad,
+      code => return Err(Errors::BadArguments(format!("bisect run failed: exit code {} from '{}' is < 0 or >= {}", code.unwrap_or(-1), command.join(" "), ABORT_EXIT_CODE))),
+    };
+
+    ensure_clean()?;
+    bisect.mark(term, &Revision::resolve("HEAD")?)?;
+    is_done = advance(&bisect)?;
+  }
+
+  Ok(())
+}
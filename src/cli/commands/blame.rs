@@ -0,0 +1,140 @@
+use std::{path::{Path, PathBuf}, collections::{HashMap, HashSet}};
+use crate::{lib::{errors::{Errors, Context}, diff::{diff, Edit}, object::FromId, constants::SHORT_ID_LENGTH, read_object_bytes, relative}, core::{commit::Commit, revision::Revision, tree::Node}, cli::configure_input_paths};
+
+pub fn blame(path: PathBuf, revision: Option<String>, lines: Option<String>, porcelain: bool) -> Result<(), Errors> {
+  let path = relative(&configure_input_paths(&[path])?[0]);
+  let commit = Revision::resolve(revision.as_deref().unwrap_or("HEAD"))?;
+  let text = file_lines(&commit, &path)?.ok_or_else(|| Errors::UnrecognisedPath(path.clone()))?;
+
+  let range = match lines {
+    Some(lines) => parse_range(&lines, text.len())?,
+    None => (1, text.len()),
+  };
+
+  let owners = attribute(commit, &path, &text)?;
+  let mut described = HashSet::new();
+  let mut output = String::new();
+
+  for number in range.0..=range.1 {
+    let (commit, original_number) = &owners[number - 1];
+    let line = &text[number - 1];
+
+    if porcelain {
+      output += &format!("{} {} {} 1\n", commit.id(), original_number, number);
+
+      if described.insert(commit.id().clone()) {
+        output += &format!("author {}\n", commit.author().username());
+        output += &format!("author-mail <{}>\n", commit.author().email());
+        output += &format!("author-time {}\n", commit.author().date().timestamp());
+        output += &format!("author-tz {}\n", commit.author().date().format("%z"));
+        output += &format!("committer {}\n", commit.committer().username());
+        output += &format!("committer-mail <{}>\n", commit.committer().email());
+        output += &format!("committer-time {}\n", commit.committer().date().timestamp());
+        output += &format!("committer-tz {}\n", commit.committer().date().format("%z"));
+        output += &format!("summary {}\n", commit.message().lines().next().unwrap_or(""));
+        output += &format!("filename {}\n", path.display());
+      }
+
+      output += &format!("\t{}\n", line);
+    } else {
+      output += &format!("{} ({} {} {:>width$}) {}\n",
+        &commit.id()[..SHORT_ID_LENGTH],
+        commit.author().username(),
+        commit.author().date().format("%Y-%m-%d %H:%M:%S %z"),
+        number,
+        line,
+        width = text.len().to_string().len(),
+      );
+    }
+  }
+
+  print!("{}", output);
+
+  Ok(())
+}
+
+// Walks the history from `commit`, handing every line that is unchanged in the parent down to it.
+// A line belongs to the first commit whose parent does not hold it. Returns the commit and the
+// line number it had there for every line.
+fn attribute(commit: Commit, path: &Path, text: &[String]) -> Result<Vec<(Commit, usize)>, Errors> {
+  let mut owners = vec![None; text.len()];
+  let mut pending = (0..text.len()).map(|index| (index, index)).collect::<Vec<(usize, usize)>>();
+  let mut current_commit = commit;
+  let mut current_lines = Vec::from(text);
+
+  while !pending.is_empty() {
+    let parent = current_commit.parent_id().as_deref()
+      .map(Commit::from_id)
+      .transpose()
+      .context(|| format!("unable to read parent of commit '{}'", current_commit.id()))?;
+    let parent_lines = match &parent {
+      Some(parent) => file_lines(parent, path)?,
+      None => None,
+    };
+
+    let (parent, parent_lines) = match (parent, parent_lines) {
+      (Some(parent), Some(parent_lines)) => (parent, parent_lines),
+      _ => {
+        for (index, line) in pending.drain(..) {
+          owners[line] = Some((current_commit.clone(), index + 1));
+        }
+
+        break;
+      },
+    };
+
+    let unchanged = diff(&parent_lines, &current_lines).into_iter()
+      .filter_map(|edit| match edit {
+        Edit::Equal(parent_index, index) => Some((index, parent_index)),
+        _ => None,
+      })
+      .collect::<HashMap<usize, usize>>();
+
+    let mut parent_pending = Vec::new();
+
+    for (index, line) in pending {
+      match unchanged.get(&index) {
+        Some(parent_index) => parent_pending.push((*parent_index, line)),
+        None => owners[line] = Some((current_commit.clone(), index + 1)),
+      }
+    }
+
+    pending = parent_pending;
+    current_commit = parent;
+    current_lines = parent_lines;
+  }
+
+  Ok(owners.into_iter().map(|owner| owner.unwrap()).collect())
+}
+
+fn file_lines(commit: &Commit, path: &Path) -> Result<Option<Vec<String>>, Errors> {
+  match commit.tree()?.get(path) {
+    Some(Node::Blob(blob)) => {
+      let bytes = read_object_bytes(blob.id())?;
+      Ok(Some(String::from_utf8_lossy(&bytes).lines().map(String::from).collect()))
+    },
+    _ => Ok(None),
+  }
+}
+
+// Ranges read `<start>,<end>` or `<start>,+<count>`, with lines numbered from 1.
+fn parse_range(range: &str, length: usize) -> Result<(usize, usize), Errors> {
+  let error = || Errors::BadArguments(format!("invalid line range '{}'", range));
+  let (start, end) = range.split_once(',').unwrap_or((range, ""));
+  let start = start.parse::<usize>().map_err(|_| error())?;
+
+  let end = if end.is_empty() {
+    length
+  } else if let Some(count) = end.strip_prefix('+') {
+    let count = count.parse::<usize>().map_err(|_| error())?;
+    start.checked_add(count).and_then(|end| end.checked_sub(1)).ok_or_else(error)?
+  } else {
+    end.parse::<usize>().map_err(|_| error())?
+  };
+
+  if start == 0 || start > end || end > length {
+    return Err(error());
+  }
+
+  Ok((start, end))
+}
@@ -1,32 +1,119 @@
-use crate::{lib::{errors::Errors, constants::BRANCHES_PATH, locale}, core::{branch::Branch, head::{Head, Reference}}};
-
-pub fn branch(delete: Option<String>) -> Result<(), Errors> {
-  if let Some(name) = delete {
-    Branch::remove(&name)?;
-  } else {
-    let head = Head::get()?;
-    let branch_name = match head.reference() {
-      Reference::Branch(branch) => branch.name(),
-      Reference::Commit(_) => "",
-    };
+use std::collections::HashSet;
+use clap::{Args, ArgGroup};
+use crate::{lib::{errors::Errors, constants::SHORT_ID_LENGTH}, core::{branch::Branch, head::{Head, Reference}, revision::Revision}};
+
+#[derive(Args)]
+#[clap(group(ArgGroup::new("action").args(&["delete", "force-delete", "rename", "copy"])))]
+pub struct BranchOptions {
+  #[clap(short, long, help = "Deletes branches whose commits are all in the current branch")]
+  delete: bool,
+
+  #[clap(short = 'D', help = "Deletes branches even when they have unmerged commits")]
+  force_delete: bool,
+
+  #[clap(short = 'm', long = "move", help = "Renames a branch, the current one unless two names are given")]
+  rename: bool,
+
+  #[clap(short, long, help = "Copies a branch, the current one unless two names are given")]
+  copy: bool,
 
-    let location = locale().join(BRANCHES_PATH);
-    let mut branch_list = String::new();
+  #[clap(short, long, help = "Shows the tip commit of each branch")]
+  verbose: bool,
 
-    for entry in location.read_dir()? {
-      let entry = entry?;
-      let file_name = entry.file_name();
-      let file_name = file_name.to_str().unwrap();
+  #[clap(help = "The branch to create and its start point, or the branches to act on")]
+  names: Vec<String>,
+}
+
+pub fn branch(options: BranchOptions) -> Result<(), Errors> {
+  let head = Head::get()?;
+
+  if options.delete || options.force_delete {
+    if options.names.is_empty() {
+      return Err(Errors::BadArguments(String::from("branch name required")));
+    }
 
-      if file_name == branch_name {
-        branch_list += &format!("-> {}\n", file_name);
-      } else {
-        branch_list += &format!("   {}\n", file_name);
+    let merged_ids = head_history(&head)?;
+
+    for name in &options.names {
+      let branch = Branch::get(name)?;
+      let is_merged = branch.commit_id().as_ref().is_none_or(|id| merged_ids.contains(id));
+
+      if !is_merged && !options.force_delete {
+        return Err(Errors::UnmergedBranch(name.clone()));
       }
+
+      Branch::remove(name)?;
+      println!("Deleted branch {}", name);
     }
 
-    print!("{}", branch_list);
+    return Ok(());
+  }
+
+  if options.rename || options.copy {
+    let (name, new_name) = match (&options.names[..], head.reference()) {
+      ([name, new_name], _) => (name.as_str(), new_name.as_str()),
+      ([new_name], Reference::Branch(branch)) => (branch.name().as_str(), new_name.as_str()),
+      ([_], Reference::Commit(_)) => return Err(Errors::BadArguments(String::from("HEAD is detached, so the branch to use must be named"))),
+      _ => return Err(Errors::BadArguments(String::from("expected [<branch>] <new branch>"))),
+    };
+
+    return match options.rename {
+      true => Branch::rename(name, new_name).map(|_| ()),
+      false => Branch::copy(name, new_name).map(|_| ()),
+    };
   }
 
+  match &options.names[..] {
+    [] => list(&head, options.verbose),
+    [name] => Branch::new(name, head.commit()?.as_ref().map(|commit| commit.id().as_str())).map(|_| ()),
+    [name, start_point] => Branch::new(name, Some(Revision::resolve(start_point)?.id())).map(|_| ()),
+    _ => Err(Errors::BadArguments(String::from("expected <branch> [<start point>]"))),
+  }
+}
+
+fn list(head: &Head, verbose: bool) -> Result<(), Errors> {
+  let current_name = match head.reference() {
+    Reference::Branch(branch) => branch.name().as_str(),
+    Reference::Commit(_) => "",
+  };
+  let branches = Branch::list()?;
+  let width = branches.iter().map(|branch| branch.name().len()).max().unwrap_or(0);
+  let mut branch_list = String::new();
+
+  for branch in &branches {
+    let marker = if branch.name() == current_name { "->" } else { "  " };
+
+    let commit = match verbose {
+      true => branch.commit()?,
+      false => None,
+    };
+
+    branch_list += &match commit {
+      Some(commit) => format!("{} {:width$} {} {}\n",
+        marker,
+        branch.name(),
+        &commit.id()[..SHORT_ID_LENGTH],
+        commit.message().lines().next().unwrap_or(""),
+        width = width,
+      ),
+      None => format!("{} {}\n", marker, branch.name()),
+    };
+  }
+
+  print!("{}", branch_list);
+
   Ok(())
 }
+
+// Commits are merged once the current branch contains them.
+fn head_history(head: &Head) -> Result<HashSet<String>, Errors> {
+  let mut ids = HashSet::new();
+  let mut current_commit = head.commit()?;
+
+  while let Some(commit) = current_commit {
+    current_commit = commit.parent();
+    ids.insert(commit.id().clone());
+  }
+
+  Ok(ids)
+}
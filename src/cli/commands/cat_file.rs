@@ -0,0 +1,22 @@
+use std::io::{self, Write};
+use crate::{lib::{errors::Errors, object::{Object, FromId}, read_object}, core::{revision::Revision, tree::Tree}};
+use super::ls_tree::tree_entries;
+
+pub fn cat_file(object: String, object_type: bool, size: bool) -> Result<(), Errors> {
+  let id = Revision::resolve_object(&object)?;
+  let (object, bytes) = read_object(&id)?;
+
+  let output = if object_type {
+    format!("{}\n", object.as_str()).into_bytes()
+  } else if size {
+    format!("{}\n", bytes.len()).into_bytes()
+  } else if object == Object::Tree {
+    tree_entries(&Tree::from_id(&id)?, false).into_bytes()
+  } else {
+    bytes
+  };
+
+  io::stdout().write_all(&output)?;
+
+  Ok(())
+}
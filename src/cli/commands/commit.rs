@@ -1,37 +1,144 @@
-use crate::{lib::{errors::Errors, constants::DELETED_INDEX_STAGE, object::FromId}, core::{head::{Head, Reference}, tree::{Tree, Node}, index::Index, blob::Blob, commit::Commit, branch::Branch}};
+use std::{fs, path::PathBuf};
+use clap::Args;
+use crate::{cli::configure_input_paths, lib::{errors::{Errors, Context}, hook::Hook, layout::Layout, signature::SigningKey, identity::{Identity, parse_user, parse_date}, locale}, core::{head::{Head, Reference}, tree::{Tree, Node}, index::{Index, Stage}, commit::Commit, branch::Branch}};
 use super::status::status;
 
-pub fn commit(message: String) -> Result<(), Errors> {
-  let index = Index::get()?;
+#[derive(Args)]
+pub struct CommitOptions {
+  #[clap(short, long, required_unless_present = "amend")]
+  message: Option<String>,
 
-  if index.staged_paths().is_empty() {
+  #[clap(long, help = "Replaces the last commit, keeping its message and author unless given")]
+  amend: bool,
+
+  #[clap(short, long, conflicts_with = "paths", help = "Stages changes to tracked files first")]
+  all: bool,
+
+  #[clap(parse(from_os_str), help = "Commits only these paths, leaving other staged changes in the index")]
+  paths: Vec<PathBuf>,
+
+  #[clap(long, help = "Overrides the author (\"Name <email>\")")]
+  author: Option<String>,
+
+  #[clap(long, help = "Overrides the author date")]
+  date: Option<String>,
+
+  #[clap(short, long, help = "Skips the pre-commit and commit-msg hooks")]
+  no_verify: bool,
+
+  #[clap(short = 'S', long, help = "Signs the commit with the key in user.signingkey")]
+  sign: bool,
+}
+
+pub fn commit(options: CommitOptions) -> Result<(), Errors> {
+  let CommitOptions { message, amend, all, paths, author: author_text, date, no_verify, sign } = options;
+  let file_paths = configure_input_paths(&paths)?;
+  let staged = || match file_paths.is_empty() {
+    true => Index::get(),
+    false => Index::from_paths(&file_paths),
+  };
+
+  if all {
+    Index::add_tracked()?;
+  }
+
+  if !amend && staged()?.staged_paths().is_empty() {
     return status();
   }
 
   let head = Head::get()?;
-  let parent_commit = head.commit();
-  let mut tree = match &parent_commit {
+
+  // Amending builds on the last commit's tree but keeps its parent.
+  let (parent_commit_id, base_commit) = match (amend, head.commit()?) {
+    (true, Some(commit)) => (commit.parent_id().clone(), Some(commit)),
+    (true, None) => return Err(Errors::BadArguments(String::from("there is no commit to amend"))),
+    (false, commit) => (commit.as_ref().map(|commit| String::from(commit.id())), commit),
+  };
+  let message = match (message, &base_commit) {
+    (Some(message), _) => message,
+    (None, Some(commit)) => commit.message().clone(),
+    (None, None) => return Err(Errors::BadArguments(String::from("a commit message is required"))),
+  };
+
+  // As in Git, amending keeps the original author.
+  let author = match (&base_commit, amend) {
+    (Some(commit), true) => commit.author().clone(),
+    _ => Identity::author()?,
+  };
+  let author = match author_text {
+    Some(text) => author.with_user(&parse_user(&text)?),
+    None => author,
+  };
+  let author = match date {
+    Some(date) => author.with_date(parse_date(&date)?),
+    None => author,
+  };
+  let committer = Identity::committer()?;
+
+  let key = match sign {
+    true => Some(SigningKey::load()?),
+    false => None,
+  };
+
+  let message = match no_verify {
+    true => message,
+    false => {
+      Hook::PreCommit.run(&[])?;
+      run_commit_msg_hook(message)?
+    },
+  };
+
+  // The pre-commit hook may have staged more changes.
+  let index = staged()?;
+
+  let mut tree = match &base_commit {
     Some(commit) => commit.tree()?,
     None => Tree::new()
   };
 
   for (path, stage) in index.staged_paths() {
-    if stage == DELETED_INDEX_STAGE {
-      tree.remove(path);
-    } else {
-      tree.insert(path, Node::Blob(Blob::from_id(stage)?));
+    match stage {
+      Stage::Blob(blob) => tree.insert(path, Node::Blob(blob.clone()))?,
+      Stage::Deleted => tree.remove(path),
     }
   }
 
-  let parent_commit_id = parent_commit.map(|commit| String::from(commit.id()));
-  let commit = Commit::new(parent_commit_id.as_deref(), tree.pack()?, &message)?;
+  let commit = match &key {
+    Some(key) => Commit::signed(parent_commit_id.as_deref(), tree.pack()?, &message, author, committer, key)?,
+    None => Commit::with_identities(parent_commit_id.as_deref(), tree.pack()?, &message, author, committer)?,
+  };
 
   match head.reference() {
     Reference::Branch(branch) => Branch::set(branch.name(), commit.id()),
     Reference::Commit(_) => Head::set(&Reference::Commit(commit)),
   }?;
 
-  Index::clear()?;
+  match file_paths.is_empty() {
+    true => Index::clear(),
+    false => Index::remove(&file_paths),
+  }?;
+
+  // As in Git, a failing post-commit hook cannot undo the commit.
+  Hook::PostCommit.run(&[]).ok();
 
   Ok(())
 }
+
+// Hands the message to the commit-msg hook in a file it may rewrite.
+fn run_commit_msg_hook(message: String) -> Result<String, Errors> {
+  let path = Layout::get().commit_message_path();
+  let location = locale().join(path);
+  let context = || format!("unable to write '{}'", location);
+
+  fs::write(&location, format!("{}\n", message)).context(context)?;
+  Hook::CommitMsg.run(&[path])?;
+
+  let message = fs::read_to_string(&location).context(|| format!("unable to read '{}'", location))?;
+  let message = message.trim_end();
+
+  if message.is_empty() {
+    return Err(Errors::BadArguments(String::from("aborting commit due to empty commit message")));
+  }
+
+  Ok(String::from(message))
+}
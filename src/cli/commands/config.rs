@@ -1,12 +1,72 @@
-use crate::lib::{errors::Errors, user::User};
+use clap::Subcommand;
+use crate::lib::{errors::Errors, user::{USERNAME_KEY, EMAIL_KEY}, config::{Config, Scope, normalize_key}};
+
+#[derive(Subcommand)]
+pub enum ConfigAction {
+  #[clap(about = "Prints the value of a key")]
+  Get {
+    key: String,
+  },
+
+  #[clap(about = "Sets the value of a key")]
+  Set {
+    key: String,
+    value: String,
+  },
+
+  #[clap(about = "Removes a key")]
+  Unset {
+    key: String,
+  },
+}
+
+// Reads from every scope unless one is given, and writes to the repository config by default.
+pub fn config(action: Option<ConfigAction>, global: bool, system: bool, list: bool, username: Option<String>, email: Option<String>) -> Result<(), Errors> {
+  if action.is_none() && !list && username.is_none() && email.is_none() {
+    return Err(Errors::BadArguments(String::from("expected get, set, unset, --list, --username or --email")));
+  }
+
+  if action.is_some() && list {
+    return Err(Errors::BadArguments(String::from("--list cannot be used with get, set or unset")));
+  }
+
+  let scope = match (global, system) {
+    (true, _) => Some(Scope::Global),
+    (_, true) => Some(Scope::System),
+    _ => None,
+  };
+  let read = || match scope {
+    Some(scope) => Config::read(scope),
+    None => Config::load(),
+  };
+  let write_scope = scope.unwrap_or(Scope::Repository);
 
-pub fn config(username: Option<String>, email: Option<String>) -> Result<(), Errors> {
   if let Some(username) = username {
-    User::set_username(&username)?;
+    Config::set(write_scope, USERNAME_KEY, &username)?;
   }
 
   if let Some(email) = email {
-    User::set_email(&email)?;
+    Config::set(write_scope, EMAIL_KEY, &email)?;
+  }
+
+  if list {
+    let mut output = String::new();
+
+    for (key, value) in read()?.entries() {
+      output += &format!("{}={}\n", key, value);
+    }
+
+    print!("{}", output);
+  }
+
+  match action {
+    Some(ConfigAction::Get { key }) => {
+      let key = normalize_key(&key)?;
+      println!("{}", read()?.get(&key).ok_or(Errors::MissingConfigKey(key))?);
+    },
+    Some(ConfigAction::Set { key, value }) => Config::set(write_scope, &key, &value)?,
+    Some(ConfigAction::Unset { key }) => Config::unset(write_scope, &key)?,
+    None => (),
   }
 
   Ok(())
@@ -0,0 +1,271 @@
+use std::{collections::{HashMap, HashSet}, fs, path::{Path, PathBuf}};
+use camino::Utf8PathBuf;
+use walkdir::WalkDir;
+use crate::{lib::{errors::{Errors, Context}, format::ObjectFormat, layout::Layout, lock::Lock, locale, read_object, read_object_bytes, object::{FromId, Object}, constants::{GIT_REFERENCES_PATH, GIT_PACKED_REFS_PATH, LOCK_EXTENSION}}, core::{head::{Head, Reference}, commit::Commit, tree::{Tree, Node}, blob::Blob, index::Index}};
+
+pub fn convert_objects(object_format: String) -> Result<(), Errors> {
+  let target = ObjectFormat::parse(&object_format)?;
+  let source = ObjectFormat::get()?;
+
+  if source == target {
+    return Ok(());
+  }
+
+  // Trees in the Git layout embed raw ids of the old length, so the whole history is
+  // loaded before anything is written.
+  let references = references()?;
+  let packed_references = packed_references()?;
+  let head = Head::get()?;
+  let mut roots = Vec::new();
+
+  for (location, _, id) in &references {
+    roots.push(reference_commit(location.strip_prefix(locale()).unwrap_or(location).as_str(), id)?);
+  }
+
+  if let Some(contents) = &packed_references {
+    for (name, id) in packed_ids(contents)? {
+      roots.push(reference_commit(name, id)?);
+    }
+  }
+
+  if let Reference::Commit(commit) = head.reference() {
+    roots.push(commit.clone());
+  }
+
+  let mut histories = Vec::new();
+  let mut loaded = HashSet::new();
+  let mut trees = HashMap::new();
+
+  for root in roots {
+    let mut history = Vec::new();
+    let mut current_commit = Some(root);
+
+    while let Some(commit) = current_commit {
+      if !loaded.insert(commit.id().clone()) {
+        break;
+      }
+
+      // A signature covers the ids the commit names, so it cannot carry over to the new ones.
+      if commit.signature().is_some() {
+        return Err(Errors::BadArguments(format!("cannot convert signed commit '{}', whose signature would no longer verify", commit.id())));
+      }
+
+      match commit.dropped_headers()?.first().map(String::as_str) {
+        Some("parent") => return Err(Errors::BadArguments(format!("cannot convert merge commit '{}', as rgit keeps a single parent", commit.id()))),
+        Some(header) => return Err(Errors::BadArguments(format!("cannot convert commit '{}', whose '{}' header rgit does not keep", commit.id(), header))),
+        None => (),
+      }
+
+      if !trees.contains_key(commit.tree_id()) {
+        let tree = commit.tree()?;
+
+        if let Some(path) = gitlink_path(&tree) {
+          return Err(Errors::BadArguments(format!("cannot convert '{}', which names a submodule commit in its own object format", path.display())));
+        }
+
+        trees.insert(commit.tree_id().clone(), tree);
+      }
+
+      current_commit = commit.parent_id().as_deref().map(Commit::from_id).transpose()?;
+      history.push(commit);
+    }
+
+    history.reverse();
+    histories.push(history);
+  }
+
+  // Every new object and reference is written before the repository switches to the new format,
+  // and the old objects go only once it has.
+  ObjectFormat::write_as(target, || {
+    let mut converted = HashMap::new();
+
+    for history in &histories {
+      for commit in history {
+        if converted.contains_key(commit.id()) {
+          continue;
+        }
+
+        let mut tree = convert_tree(&trees[commit.tree_id()], &mut converted)?;
+        let parent_id = commit.parent_id().as_ref().and_then(|id| converted.get(id)).cloned();
+        let new_commit = Commit::with_identities(parent_id.as_deref(), tree.pack()?, commit.message(), commit.author().clone(), commit.committer().clone())?;
+
+        converted.insert(commit.id().clone(), new_commit.id().clone());
+      }
+    }
+
+    let mut updates = references.iter()
+      .map(|(location, contents, id)| (location.clone(), contents.clone(), contents.replacen(id.as_str(), &converted[id], 1)))
+      .collect::<Vec<_>>();
+
+    if let Some(contents) = &packed_references {
+      let new_contents = contents.lines()
+        .map(|line| match line.split_once(' ') {
+          Some((id, reference)) if !line.starts_with('#') => format!("{} {}\n", converted[id], reference),
+          _ => format!("{}\n", line),
+        })
+        .collect::<String>();
+
+      updates.push((locale().join(GIT_PACKED_REFS_PATH), contents.clone(), new_contents));
+    }
+
+    let new_head = match head.reference() {
+      Reference::Commit(commit) => Some(Reference::Commit(Commit::from_id(&converted[commit.id()])?)),
+      Reference::Branch(_) => None,
+    };
+
+    Index::rewrite(|blob| convert_blob(blob, &mut converted), || {
+      update_references(&updates, new_head.as_ref())?;
+      ObjectFormat::set(target)
+    })
+  })?;
+
+  remove_objects(source)
+}
+
+// References are rewritten together, and written back when any of them or HEAD cannot be.
+fn update_references(updates: &[(Utf8PathBuf, String, String)], head: Option<&Reference>) -> Result<(), Errors> {
+  let mut updated = Vec::new();
+
+  let result = (|| {
+    for (location, contents, new_contents) in updates {
+      Lock::acquire(location)?.commit(new_contents)?;
+      updated.push((location, contents));
+    }
+
+    match head {
+      Some(head) => Head::set(head),
+      None => Ok(()),
+    }
+  })();
+
+  if result.is_err() {
+    for (location, contents) in updated {
+      if let Ok(lock) = Lock::acquire(location) {
+        lock.commit(contents).ok();
+      }
+    }
+  }
+
+  result
+}
+
+// Loose references hold an id, or `ref: <reference>` for a symbolic one, which needs no rewrite.
+// In the Git layout they include tags and remote branches as well as branches.
+fn references() -> Result<Vec<(Utf8PathBuf, String, String)>, Errors> {
+  let layout = Layout::get();
+  let root = locale().join(match layout {
+    Layout::Rgit => layout.branches_path(),
+    Layout::Git => GIT_REFERENCES_PATH,
+  });
+  let mut references = Vec::new();
+
+  for entry in WalkDir::new(&root) {
+    let entry = entry?;
+
+    if !entry.file_type().is_file() || entry.path().extension().is_some_and(|extension| extension == LOCK_EXTENSION) {
+      continue;
+    }
+
+    let location = Utf8PathBuf::from_path_buf(entry.into_path()).map_err(|_| Errors::BadPathEncoding)?;
+    let contents = fs::read_to_string(&location).context(|| format!("unable to read '{}'", location))?;
+    let id = contents.trim();
+
+    if !id.is_empty() && !id.starts_with("ref: ") {
+      references.push((location.clone(), contents.clone(), String::from(id)));
+    }
+  }
+
+  Ok(references)
+}
+
+// `packed-refs` holds `<id> <reference>` lines, each optionally followed by a `^<id>` line peeling
+// an annotated tag, which names a tag object rather than a commit.
+fn packed_references() -> Result<Option<String>, Errors> {
+  let location = locale().join(GIT_PACKED_REFS_PATH);
+
+  if Layout::get() != Layout::Git || !location.exists() {
+    return Ok(None);
+  }
+
+  fs::read_to_string(&location).context(|| format!("unable to read '{}'", location)).map(Some)
+}
+
+fn packed_ids(contents: &str) -> Result<Vec<(&str, &str)>, Errors> {
+  let mut ids = Vec::new();
+
+  for line in contents.lines().filter(|line| !line.starts_with('#')) {
+    match line.split_once(' ') {
+      Some((id, reference)) => ids.push((reference, id)),
+      None => return Err(Errors::BadArguments(format!("cannot convert the annotated tag peeled to '{}'", line.trim_start_matches('^')))),
+    }
+  }
+
+  Ok(ids)
+}
+
+fn reference_commit(name: &str, id: &str) -> Result<Commit, Errors> {
+  match read_object(id) {
+    Ok((Object::Commit, _)) => Commit::from_id(id),
+    _ => Err(Errors::BadArguments(format!("cannot convert '{}', which does not name a commit", name))),
+  }
+}
+
+fn convert_tree(tree: &Tree, converted: &mut HashMap<String, String>) -> Result<Tree, Errors> {
+  let mut new_tree = Tree::new();
+
+  for (name, node) in tree.children() {
+    let node = match node {
+      Node::Blob(blob) => Node::Blob(convert_blob(blob, converted)?),
+      Node::Tree(tree) => Node::Tree(convert_tree(tree, converted)?),
+      Node::Gitlink(_) => return Err(Errors::BadArguments(format!("cannot convert '{}', which names a submodule commit", name.to_string_lossy()))),
+    };
+
+    new_tree.insert(name, node)?;
+  }
+
+  Ok(new_tree)
+}
+
+fn gitlink_path(tree: &Tree) -> Option<PathBuf> {
+  tree.children().iter().find_map(|(name, node)| match node {
+    Node::Tree(tree) => gitlink_path(tree).map(|path| Path::new(name).join(path)),
+    Node::Gitlink(_) => Some(PathBuf::from(name)),
+    Node::Blob(_) => None,
+  })
+}
+
+fn convert_blob(blob: &Blob, converted: &mut HashMap<String, String>) -> Result<Blob, Errors> {
+  if let Some(id) = converted.get(blob.id()) {
+    return Ok(Blob::from_id(id)?.with_mode(*blob.mode()));
+  }
+
+  let new_blob = Blob::new(read_object_bytes(blob.id())?)?.with_mode(*blob.mode());
+  converted.insert(blob.id().clone(), new_blob.id().clone());
+
+  Ok(new_blob)
+}
+
+fn remove_objects(format: ObjectFormat) -> Result<(), Errors> {
+  for folder in locale().join(Layout::get().objects_path()).read_dir()? {
+    let folder = folder?.path();
+    let prefix = folder.file_name().and_then(|name| name.to_str()).unwrap_or("");
+
+    if !folder.is_dir() || prefix.len() != 2 {
+      continue;
+    }
+
+    for object in folder.read_dir()? {
+      let object = object?;
+
+      if prefix.len() + object.file_name().len() == format.id_length() {
+        fs::remove_file(object.path())?;
+      }
+    }
+
+    if folder.read_dir()?.next().is_none() {
+      fs::remove_dir(&folder)?;
+    }
+  }
+
+  Ok(())
+}
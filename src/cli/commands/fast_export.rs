@@ -0,0 +1,123 @@
+use std::{collections::HashMap, io::{self, Write}, path::{Path, PathBuf}};
+use crate::{lib::{errors::Errors, read_object_bytes, name_bytes, layout::Layout, constants::GITLINK_MODE}, core::{branch::Branch, tree::{Tree, Node}}};
+
+pub fn fast_export() -> Result<(), Errors> {
+  let mut stream = Vec::new();
+  let mut marks = HashMap::new();
+  let mut next_mark = 1;
+  let branches = Branch::list()?;
+
+  for branch in &branches {
+    let reference = format!("refs/heads/{}", branch.name());
+    let mut history = Vec::new();
+    let mut current_commit = branch.commit()?;
+
+    while let Some(commit) = current_commit {
+      if marks.contains_key(commit.id()) {
+        break;
+      }
+
+      current_commit = commit.parent();
+      history.push(commit);
+    }
+
+    for commit in history.iter().rev() {
+      let tree = commit.tree()?;
+      let mut paths = Vec::new();
+
+      for (path, blob) in tree.blob_iter() {
+        if !marks.contains_key(blob.id()) {
+          let bytes = read_object_bytes(blob.id())?;
+
+          stream.extend_from_slice(format!("blob\nmark :{}\ndata {}\n", next_mark, bytes.len()).as_bytes());
+          stream.extend_from_slice(&bytes);
+          stream.push(b'\n');
+
+          marks.insert(String::from(blob.id()), next_mark);
+          next_mark += 1;
+        }
+
+        let mut line = format!("M {} :{} ", blob.mode().as_str(), marks[blob.id()]).into_bytes();
+        line.extend(quote_path(&name_bytes(path)));
+        line.push(b'\n');
+        paths.push(line);
+      }
+
+      // Submodule commits live in another repository, so they are named by id rather than mark.
+      for (path, id) in gitlinks(&tree, Path::new("")) {
+        let mut line = format!("M {} {} ", GITLINK_MODE, id).into_bytes();
+        line.extend(quote_path(&name_bytes(path)));
+        line.push(b'\n');
+        paths.push(line);
+      }
+
+      paths.sort();
+
+      let parent_mark = commit.parent_id().as_ref().and_then(|id| marks.get(id));
+
+      if parent_mark.is_none() {
+        stream.extend_from_slice(format!("reset {}\n", reference).as_bytes());
+      }
+
+      stream.extend_from_slice(format!("commit {}\nmark :{}\n", reference, next_mark).as_bytes());
+      stream.extend_from_slice(format!("author {}\ncommitter {}\n", commit.author().format(Layout::Git), commit.committer().format(Layout::Git)).as_bytes());
+      stream.extend_from_slice(format!("data {}\n{}\n", commit.message().len(), commit.message()).as_bytes());
+
+      if let Some(parent_mark) = parent_mark {
+        stream.extend_from_slice(format!("from :{}\n", parent_mark).as_bytes());
+      }
+
+      stream.extend_from_slice(b"deleteall\n");
+
+      for path in paths {
+        stream.extend(path);
+      }
+
+      stream.push(b'\n');
+
+      marks.insert(String::from(commit.id()), next_mark);
+      next_mark += 1;
+    }
+  }
+
+  for branch in &branches {
+    if let Some(commit_id) = branch.commit_id() {
+      stream.extend_from_slice(format!("reset refs/heads/{}\nfrom :{}\n\n", branch.name(), marks[commit_id]).as_bytes());
+    }
+  }
+
+  stream.extend_from_slice(b"done\n");
+  io::stdout().write_all(&stream)?;
+
+  Ok(())
+}
+
+fn gitlinks<'a>(tree: &'a Tree, prefix: &Path) -> Vec<(PathBuf, &'a String)> {
+  tree.children().iter()
+    .flat_map(|(name, node)| match node {
+      Node::Tree(tree) => gitlinks(tree, &prefix.join(name)),
+      Node::Gitlink(id) => vec![(prefix.join(name), id)],
+      Node::Blob(_) => Vec::new(),
+    })
+    .collect()
+}
+
+fn quote_path(path: &[u8]) -> Vec<u8> {
+  if !path.starts_with(b"\"") && !path.iter().any(|byte| matches!(byte, b'\n' | b'\\')) {
+    return Vec::from(path);
+  }
+
+  let mut quoted = vec![b'"'];
+
+  for byte in path {
+    match byte {
+      b'"' => quoted.extend_from_slice(b"\\\""),
+      b'\\' => quoted.extend_from_slice(b"\\\\"),
+      b'\n' => quoted.extend_from_slice(b"\\n"),
+      _ => quoted.push(*byte),
+    }
+  }
+
+  quoted.push(b'"');
+  quoted
+}
@@ -0,0 +1,369 @@
+use std::{collections::HashMap, ffi::OsString, io::{self, Read}};
+use crate::{lib::{errors::{Errors, Context}, identity::Identity, object::{FromId, Mode}, constants::{TREE_MODE, GITLINK_MODE}, format::ObjectFormat, name_from_bytes}, core::{blob::Blob, tree::{Tree, Node, is_valid_name}, commit::Commit, branch::Branch}};
+
+const BRANCH_REFERENCE_PREFIX: &str = "refs/heads/";
+
+struct Stream {
+  bytes: Vec<u8>,
+  position: usize,
+}
+
+impl Stream {
+  // Lines are bytes, as Git writes paths that are not UTF-8 unquoted.
+  fn peek_line(&self) -> Option<&[u8]> {
+    if self.position >= self.bytes.len() {
+      return None;
+    }
+
+    let rest = &self.bytes[self.position..];
+    let end = rest.iter().position(|byte| *byte == b'\n').unwrap_or(rest.len());
+
+    Some(&rest[..end])
+  }
+
+  fn next_line(&mut self) -> Option<Vec<u8>> {
+    let line = Vec::from(self.peek_line()?);
+
+    self.position += line.len() + 1;
+    Some(line)
+  }
+
+  // Reads the `data <count>` or `data <<<delimiter>` command that follows.
+  fn data(&mut self) -> Result<Vec<u8>, Errors> {
+    let line = self.next_line().ok_or(Errors::BadObjectStructure)?;
+    let argument = text(line.strip_prefix(b"data ").ok_or(Errors::BadObjectStructure)?)?;
+
+    let data = if let Some(delimiter) = argument.strip_prefix("<<") {
+      let mut data = Vec::new();
+
+      loop {
+        let line = self.next_line().ok_or(Errors::BadObjectStructure)?;
+
+        if line == delimiter.as_bytes() {
+          break;
+        }
+
+        data.extend_from_slice(&line);
+        data.push(b'\n');
+      }
+
+      data
+    } else {
+      let length = argument.parse::<usize>().map_err(|_| Errors::BadObjectStructure)?;
+      let end = self.position + length;
+
+      if end > self.bytes.len() {
+        return Err(Errors::BadObjectStructure);
+      }
+
+      let data = Vec::from(&self.bytes[self.position..end]);
+      self.position = end;
+      data
+    };
+
+    if self.bytes.get(self.position) == Some(&b'\n') {
+      self.position += 1;
+    }
+
+    Ok(data)
+  }
+}
+
+#[derive(Default)]
+struct Import {
+  marks: HashMap<String, String>,
+  references: HashMap<String, Option<String>>,
+}
+
+impl Import {
+  fn resolve(&self, name: &str) -> Result<String, Errors> {
+    if let Some(id) = self.marks.get(name) {
+      return Ok(id.clone());
+    }
+
+    if let Some(Some(id)) = self.references.get(name) {
+      return Ok(id.clone());
+    }
+
+    if let Some(branch_name) = name.strip_prefix(BRANCH_REFERENCE_PREFIX) {
+      if let Some(id) = Branch::get(branch_name)?.commit_id() {
+        return Ok(id.clone());
+      }
+    }
+
+    Commit::from_id(name).map(|commit| String::from(commit.id()))
+  }
+
+  fn resolve_object(&self, name: &str) -> String {
+    self.marks.get(name).cloned().unwrap_or_else(|| String::from(name))
+  }
+
+  fn tip(&self, reference: &str) -> Result<Option<String>, Errors> {
+    if let Some(id) = self.references.get(reference) {
+      return Ok(id.clone());
+    }
+
+    match reference.strip_prefix(BRANCH_REFERENCE_PREFIX) {
+      Some(branch_name) => match Branch::get(branch_name) {
+        Ok(branch) => Ok(branch.commit_id().clone()),
+        Err(Errors::UnrecognisedBranch(_)) => Ok(None),
+        Err(error) => Err(error),
+      },
+      None => Ok(None),
+    }
+  }
+}
+
+pub fn fast_import() -> Result<(), Errors> {
+  let mut bytes = Vec::new();
+  io::stdin().read_to_end(&mut bytes)?;
+
+  let mut stream = Stream { bytes, position: 0 };
+  let mut import = Import::default();
+
+  while let Some(line) = stream.next_line() {
+    let line = text(&line)?;
+    let (command, argument) = line.split_once(' ').unwrap_or((line, ""));
+
+    match command {
+      "blob" => import_blob(&mut stream, &mut import)?,
+      "commit" => import_commit(&mut stream, &mut import, argument)?,
+      "reset" => {
+        let from = match stream.peek_line() {
+          Some(line) if line.starts_with(b"from ") => {
+            let line = stream.next_line().unwrap();
+            Some(import.resolve(text(&line[5..])?)?)
+          },
+          _ => None,
+        };
+
+        import.references.insert(String::from(argument), from);
+      },
+      "tag" => return Err(Errors::BadArguments(format!("cannot import tag '{}', as rgit has no tags", argument))),
+      "done" => break,
+      "" | "feature" | "option" | "progress" | "checkpoint" => continue,
+      _ if command.starts_with('#') => continue,
+      _ => return Err(Errors::BadObjectStructure),
+    }
+  }
+
+  if let Some(reference) = import.references.keys().find(|reference| !reference.starts_with(BRANCH_REFERENCE_PREFIX)) {
+    return Err(Errors::BadArguments(format!("cannot import '{}', as rgit only keeps branches", reference)));
+  }
+
+  for (reference, commit_id) in &import.references {
+    if let (Some(name), Some(commit_id)) = (reference.strip_prefix(BRANCH_REFERENCE_PREFIX), commit_id) {
+      match Branch::get(name) {
+        Ok(_) => Branch::set(name, commit_id)?,
+        Err(Errors::UnrecognisedBranch(_)) => { Branch::new(name, Some(commit_id))?; },
+        Err(error) => return Err(error),
+      }
+    }
+  }
+
+  Ok(())
+}
+
+fn import_blob(stream: &mut Stream, import: &mut Import) -> Result<(), Errors> {
+  let mark = match stream.peek_line() {
+    Some(line) if line.starts_with(b"mark ") => Some(String::from(text(&stream.next_line().unwrap()[5..])?)),
+    _ => None,
+  };
+
+  if let Some(line) = stream.peek_line() {
+    if line.starts_with(b"original-oid ") {
+      stream.next_line();
+    }
+  }
+
+  let blob = Blob::new(stream.data()?)?;
+
+  if let Some(mark) = mark {
+    import.marks.insert(mark, String::from(blob.id()));
+  }
+
+  Ok(())
+}
+
+fn import_commit(stream: &mut Stream, import: &mut Import, reference: &str) -> Result<(), Errors> {
+  let mut mark = None;
+  let mut author = None;
+  let mut committer = None;
+
+  while let Some(line) = stream.peek_line() {
+    if let Some(value) = line.strip_prefix(b"mark ") {
+      mark = Some(String::from(text(value)?));
+    } else if let Some(value) = line.strip_prefix(b"author ") {
+      author = Some(Identity::parse(text(value)?)?);
+    } else if let Some(value) = line.strip_prefix(b"committer ") {
+      committer = Some(Identity::parse(text(value)?)?);
+    } else if !line.starts_with(b"original-oid ") && !line.starts_with(b"encoding ") {
+      break;
+    }
+
+    stream.next_line();
+  }
+
+  let message = String::from_utf8(stream.data()?)?;
+  let message = message.strip_suffix('\n').unwrap_or(&message);
+  let mut parent_id = import.tip(reference)?;
+
+  if let Some(line) = stream.peek_line() {
+    if let Some(from) = line.strip_prefix(b"from ") {
+      let from = String::from(text(from)?);
+      stream.next_line();
+      parent_id = Some(import.resolve(&from)?);
+    }
+  }
+
+  let mut tree = match &parent_id {
+    Some(parent_id) => Commit::from_id(parent_id)?.tree()?,
+    None => Tree::new(),
+  };
+
+  while let Some(line) = stream.peek_line() {
+    let line = Vec::from(line);
+    let (command, argument) = match line.iter().position(|byte| *byte == b' ') {
+      Some(position) => (&line[..position], &line[position + 1..]),
+      None => (&line[..], &[][..]),
+    };
+
+    if ![&b"M"[..], b"D", b"R", b"C", b"deleteall", b"merge", b"N", b""].contains(&command) {
+      break;
+    }
+
+    stream.next_line();
+
+    match command {
+      b"" => break,
+      b"M" => {
+        let mut parts = argument.splitn(3, |byte| *byte == b' ');
+        let (mode, data_reference, path) = match (parts.next(), parts.next(), parts.next()) {
+          (Some(mode), Some(data_reference), Some(path)) => (text(mode)?, text(data_reference)?, parse_path(path)?),
+          _ => return Err(Errors::BadObjectStructure),
+        };
+
+        let mode = match mode {
+          "644" => "100644",
+          "755" => "100755",
+          mode => mode,
+        };
+
+        let node = if mode == TREE_MODE || mode == "40000" {
+          Node::Tree(Tree::from_id(&import.resolve_object(data_reference))?)
+        } else if mode == GITLINK_MODE {
+          match ObjectFormat::is_id(data_reference) {
+            true => Node::Gitlink(String::from(data_reference)),
+            false => return Err(Errors::BadObjectStructure).context(|| format!("invalid submodule commit '{}'", data_reference)),
+          }
+        } else {
+          let blob = match data_reference {
+            "inline" => Blob::new(stream.data()?)?,
+            data_reference => Blob::from_id(&import.resolve_object(data_reference))?,
+          };
+
+          Node::Blob(blob.with_mode(Mode::parse(mode)?))
+        };
+
+        tree.insert(path, node)?;
+      },
+      b"D" => tree.remove(parse_path(argument)?),
+      b"R" | b"C" => {
+        let (source, destination) = split_paths(argument)?;
+        let node = tree.get(&source).cloned().ok_or_else(|| Errors::UnrecognisedPath(source.clone().into()))?;
+
+        if command == b"R" {
+          tree.remove(&source);
+        }
+
+        tree.insert(destination, node)?;
+      },
+      b"deleteall" => tree = Tree::new(),
+      b"merge" => return Err(Errors::BadArguments(format!("cannot import a merge into '{}', as rgit keeps a single parent", reference))),
+      _ => return Err(Errors::BadArguments(format!("cannot import notes into '{}', as rgit has no notes", reference))),
+    }
+  }
+
+  let committer = committer.or_else(|| author.clone()).ok_or(Errors::BadObjectStructure)?;
+  let author = author.unwrap_or_else(|| committer.clone());
+  let commit = Commit::with_identities(parent_id.as_deref(), tree.pack()?, message, author, committer)?;
+
+  if let Some(mark) = mark {
+    import.marks.insert(mark, String::from(commit.id()));
+  }
+
+  import.references.insert(String::from(reference), Some(String::from(commit.id())));
+
+  Ok(())
+}
+
+fn split_paths(argument: &[u8]) -> Result<(OsString, OsString), Errors> {
+  if argument.starts_with(b"\"") {
+    let mut escaped = false;
+
+    for (position, byte) in argument.iter().enumerate().skip(1) {
+      match byte {
+        b'\\' if !escaped => escaped = true,
+        b'"' if !escaped => {
+          let source = parse_path(&argument[..=position])?;
+          let destination = parse_path(argument[position + 1..].trim_ascii_start())?;
+          return Ok((source, destination));
+        },
+        _ => escaped = false,
+      }
+    }
+
+    Err(Errors::BadObjectStructure)
+  } else {
+    let position = argument.iter().position(|byte| *byte == b' ').ok_or(Errors::BadObjectStructure)?;
+    Ok((parse_path(&argument[..position])?, parse_path(&argument[position + 1..])?))
+  }
+}
+
+// Refuses paths that would leave the work tree or reach into the repository once checked out.
+fn parse_path(argument: &[u8]) -> Result<OsString, Errors> {
+  let path = unquote_path(argument)?;
+
+  if !path.split(|byte| *byte == b'/').all(is_valid_name) {
+    return Err(Errors::BadObjectStructure).context(|| format!("invalid path '{}'", String::from_utf8_lossy(&path)));
+  }
+
+  Ok(name_from_bytes(&path))
+}
+
+// Git quotes paths holding special characters and may quote bytes that are not UTF-8 as octal
+// escapes, or leave them raw.
+fn unquote_path(path: &[u8]) -> Result<Vec<u8>, Errors> {
+  let inner = match path.strip_prefix(b"\"").and_then(|path| path.strip_suffix(b"\"")) {
+    Some(inner) => inner,
+    None => return Ok(Vec::from(path)),
+  };
+
+  let mut bytes = Vec::new();
+  let mut input = inner.iter().copied();
+
+  while let Some(byte) = input.next() {
+    if byte != b'\\' {
+      bytes.push(byte);
+      continue;
+    }
+
+    match input.next() {
+      Some(b'n') => bytes.push(b'\n'),
+      Some(b't') => bytes.push(b'\t'),
+      Some(b'"') => bytes.push(b'"'),
+      Some(b'\\') => bytes.push(b'\\'),
+      Some(digit @ b'0'..=b'7') => {
+        let octal = [Some(digit), input.next(), input.next()].iter().flatten().map(|byte| char::from(*byte)).collect::<String>();
+        bytes.push(u8::from_str_radix(&octal, 8).map_err(|_| Errors::BadObjectStructure)?);
+      },
+      _ => return Err(Errors::BadObjectStructure),
+    }
+  }
+
+  Ok(bytes)
+}
+
+fn text(bytes: &[u8]) -> Result<&str, Errors> {
+  std::str::from_utf8(bytes).map_err(|_| Errors::BadObjectStructure)
+}
@@ -0,0 +1,131 @@
+use std::{fs, collections::{BTreeMap, BTreeSet}, path::PathBuf};
+use clap::Args;
+use regex::{Regex, RegexBuilder};
+use crate::{lib::{errors::Errors, locale, relative, read_object_bytes}, core::{index::{Index, Stage}, head::Head, revision::Revision, tree::Tree}, cli::configure_input_paths};
+
+#[derive(Args)]
+pub struct GrepOptions {
+  #[clap(short, long, help = "Matches regardless of case")]
+  ignore_case: bool,
+
+  #[clap(short = 'n', long, help = "Prefixes each matching line with its line number")]
+  line_number: bool,
+
+  #[clap(short = 'l', long, conflicts_with = "count", help = "Prints only the names of files with matches")]
+  files_with_matches: bool,
+
+  #[clap(short, long, help = "Prints the number of matching lines in each file")]
+  count: bool,
+
+  #[clap(help = "Regular expression to search for")]
+  pattern: String,
+
+  #[clap(help = "Commit to search instead of the working tree")]
+  revision: Option<String>,
+
+  #[clap(last = true, parse(from_os_str), help = "Searches only the paths")]
+  paths: Vec<PathBuf>,
+}
+
+// Searches the tracked files, read from the working tree or from the blobs of a revision, which
+// prefixes the file names as `<revision>:<path>`. Exits with 1 when nothing matches, as in Git.
+pub fn grep(options: GrepOptions) -> Result<(), Errors> {
+  let pattern = RegexBuilder::new(&options.pattern)
+    .case_insensitive(options.ignore_case)
+    .build()
+    .map_err(|_| Errors::UnrecognisedPattern(options.pattern.clone()))?;
+  let paths = configure_input_paths(&options.paths)?.iter().map(relative).collect::<Vec<PathBuf>>();
+  let is_selected = |path: &PathBuf| paths.is_empty() || paths.iter().any(|prefix| path.starts_with(prefix));
+
+  let files = match &options.revision {
+    Some(revision) => {
+      let tree = Revision::resolve(revision)?.tree()?;
+      let mut files = BTreeMap::new();
+
+      for (path, blob) in tree.blobs().into_iter().filter(|(path, _)| is_selected(path)) {
+        files.insert(format!("{}:{}", revision, path.display()), read_object_bytes(blob.id())?);
+      }
+
+      files
+    },
+    None => {
+      let mut files = BTreeMap::new();
+
+      for path in tracked_paths()?.into_iter().filter(is_selected) {
+        if let Ok(bytes) = fs::read(locale().as_std_path().join(&path)) {
+          files.insert(path.display().to_string(), bytes);
+        }
+      }
+
+      files
+    },
+  };
+
+  let mut output = String::new();
+
+  for (name, bytes) in files {
+    output += &search(&pattern, &name, bytes, &options);
+  }
+
+  print!("{}", output);
+
+  match output.is_empty() {
+    true => Err(Errors::NoMatch),
+    false => Ok(()),
+  }
+}
+
+// The files of the HEAD tree with the staged changes on top.
+fn tracked_paths() -> Result<BTreeSet<PathBuf>, Errors> {
+  let tree = match Head::get()?.commit()? {
+    Some(commit) => commit.tree()?,
+    None => Tree::new(),
+  };
+  let mut paths = tree.blobs().into_keys().collect::<BTreeSet<PathBuf>>();
+
+  for (path, stage) in Index::get()?.staged_paths() {
+    match stage {
+      Stage::Blob(_) => paths.insert(path.clone()),
+      Stage::Deleted => paths.remove(path),
+    };
+  }
+
+  Ok(paths)
+}
+
+fn search(pattern: &Regex, name: &str, bytes: Vec<u8>, options: &GrepOptions) -> String {
+  let (text, is_binary) = match String::from_utf8(bytes) {
+    Ok(text) => {
+      let is_binary = text.contains('\0');
+      (text, is_binary)
+    },
+    Err(error) => (String::from_utf8_lossy(error.as_bytes()).into_owned(), true),
+  };
+  let matches = text.lines()
+    .enumerate()
+    .filter(|(_, line)| pattern.is_match(line))
+    .collect::<Vec<(usize, &str)>>();
+
+  if matches.is_empty() {
+    return String::new();
+  }
+
+  if options.files_with_matches {
+    return format!("{}\n", name);
+  }
+
+  if options.count {
+    return format!("{}:{}\n", name, matches.len());
+  }
+
+  if is_binary {
+    return format!("Binary file {} matches\n", name);
+  }
+
+  matches.iter()
+    .map(|(index, line)| match options.line_number {
+      true => format!("{}:{}:{}\n", name, index + 1, line),
+      false => format!("{}:{}\n", name, line),
+    })
+    .collect()
+}
@@ -0,0 +1,27 @@
+use std::{path::PathBuf, fs, io::{self, Read}};
+use crate::lib::{errors::{Errors, Context}, object::Object, write_object_bytes, hash_object_bytes};
+
+pub fn hash_object(paths: Vec<PathBuf>, write: bool, stdin: bool) -> Result<(), Errors> {
+  let mut contents = Vec::new();
+
+  if stdin {
+    let mut bytes = Vec::new();
+    io::stdin().read_to_end(&mut bytes).context(|| String::from("unable to read standard input"))?;
+    contents.push(bytes);
+  }
+
+  for path in &paths {
+    contents.push(fs::read(path).context(|| format!("unable to read '{}'", path.display()))?);
+  }
+
+  for bytes in contents {
+    let id = match write {
+      true => write_object_bytes(Object::Blob, bytes)?,
+      false => hash_object_bytes(Object::Blob, bytes)?,
+    };
+
+    println!("{}", id);
+  }
+
+  Ok(())
+}
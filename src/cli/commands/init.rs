@@ -1,15 +1,26 @@
 use std::env;
-use crate::lib::{errors::Errors, initialize, constants::{PROJECT_ENV, REPOSITORY_PATH}};
+use crate::lib::{errors::Errors, initialize, initialize_git, format::ObjectFormat, constants::{PROJECT_ENV, REPOSITORY_PATH, GIT_REPOSITORY_PATH}};
 
-pub fn init() -> Result<(), Errors> {
+pub fn init(git: bool, object_format: String) -> Result<(), Errors> {
   let current_dir = env::current_dir()?;
+  let repository_path = if git { GIT_REPOSITORY_PATH } else { REPOSITORY_PATH };
+  let object_format = ObjectFormat::parse(&object_format)?;
 
-  if current_dir.join(REPOSITORY_PATH).exists() {
+  if current_dir.join(repository_path).exists() {
     return Err(Errors::ExistingRepository);
   }
 
   env::set_var(PROJECT_ENV, current_dir);
-  initialize()?;
+
+  if git {
+    initialize_git()?;
+  } else {
+    initialize()?;
+  }
+
+  if object_format != ObjectFormat::Sha1 {
+    ObjectFormat::set(object_format)?;
+  }
 
   Ok(())
 }
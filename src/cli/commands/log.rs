@@ -1,23 +1,497 @@
-use std::{io::Write, process::Command};
+use std::{io::{self, ErrorKind, IsTerminal, Write}, process::Command, collections::{BinaryHeap, HashMap}, path::{Path, PathBuf}, vec};
+use chrono::{DateTime, Utc};
+use clap::Args;
+use regex::Regex;
 use tempfile::NamedTempFile;
-use crate::{lib::errors::Errors, core::head::Head};
+use crate::{lib::{errors::{Errors, Context}, identity::parse_date, object::FromId, signature::SignatureStatus, constants::{GITLINK_MODE, TREE_MODE, SHORT_ID_LENGTH}, relative}, core::{head::{Head, Reference}, commit::Commit, branch::Branch, tree::Tree}, cli::configure_input_paths};
+
+const ONELINE_FORMAT: &str = "%h%d %s";
+
+#[derive(Args)]
+pub struct LogOptions {
+  #[clap(long, help = "Shows each commit on a single line")]
+  oneline: bool,
+
+  #[clap(short = 'n', long = "max-count", help = "Limits the number of commits shown")]
+  max_count: Option<usize>,
+
+  #[clap(long, help = "Shows commits whose author matches the pattern")]
+  author: Option<String>,
+
+  #[clap(long, help = "Shows commits made after a date (YYYY-MM-DD, RFC 3339, RFC 2822 or seconds since epoch)")]
+  since: Option<String>,
+
+  #[clap(long, help = "Shows commits made before a date (YYYY-MM-DD, RFC 3339, RFC 2822 or seconds since epoch)")]
+  until: Option<String>,
+
+  #[clap(long, help = "Shows commits whose message matches the pattern")]
+  grep: Option<String>,
+
+  #[clap(long, help = "Shows the oldest commits first")]
+  reverse: bool,
+
+  #[clap(long, conflicts_with = "oneline", help = "Formats each commit with %H, %h, %an, %ae, %ad, %cn, %ce, %cd, %s, %d and %n placeholders")]
+  format: Option<String>,
+
+  #[clap(long, conflicts_with_all = &["author", "since", "until", "grep", "reverse", "paths"], help = "Draws the history as a graph")]
+  graph: bool,
+
+  #[clap(long, help = "Shows the commits of every branch")]
+  all: bool,
+
+  #[clap(long, requires = "paths", help = "Follows a single file across renames")]
+  follow: bool,
+
+  #[clap(long, help = "Checks the signature of each signed commit")]
+  show_signature: bool,
+
+  #[clap(last = true, parse(from_os_str), help = "Shows only commits changing the paths")]
+  paths: Vec<PathBuf>,
+}
+
+pub fn log(options: LogOptions) -> Result<(), Errors> {
+  let author = options.author.as_deref().map(parse_pattern).transpose()?;
+  let grep = options.grep.as_deref().map(parse_pattern).transpose()?;
+  let since = options.since.as_deref().map(parse_date).transpose()?;
+  let until = options.until.as_deref().map(parse_date).transpose()?;
+  let paths = configure_input_paths(&options.paths)?.iter().map(relative).collect::<Vec<PathBuf>>();
+
+  if options.follow && paths.len() != 1 {
+    return Err(Errors::BadArguments(String::from("--follow requires exactly one path")));
+  }
 
-pub fn log() -> Result<(), Errors> {
   let head = Head::get()?;
-  let mut current_commit = head.commit();
-  let mut log = String::new();
+  let decorations = if options.graph || options.all {
+    decorations(&head)?
+  } else {
+    HashMap::new()
+  };
+
+  let mut commits = Vec::new();
+  let mut followed_paths = HashMap::new();
+
+  for commit in history(&head, options.all)? {
+    if options.max_count.is_some_and(|max_count| commits.len() >= max_count) {
+      break;
+    }
+
+    let commit = commit?;
+
+    let is_changed = if paths.is_empty() {
+      true
+    } else if options.follow {
+      let path = followed_paths.remove(commit.id()).unwrap_or_else(|| paths[0].clone());
+      let (is_changed, parent_path) = follow(&commit, &path)?;
+
+      if let Some(parent_id) = commit.parent_id() {
+        followed_paths.insert(parent_id.clone(), parent_path);
+      }
+
+      is_changed
+    } else {
+      let parent_tree_id = parent_tree_id(&commit)?;
+      let mut is_changed = false;
+
+      for path in &paths {
+        if node_id(Some(commit.tree_id()), path)? != node_id(parent_tree_id.as_ref(), path)? {
+          is_changed = true;
+          break;
+        }
+      }
+
+      is_changed
+    };
+
+    if !is_changed {
+      continue;
+    }
+
+    let signature = format!("{} <{}>", commit.author().username(), commit.author().email());
+    let is_shown = author.as_ref().is_none_or(|author| author.is_match(&signature))
+      && grep.as_ref().is_none_or(|grep| grep.is_match(commit.message()))
+      && since.is_none_or(|since| commit.committer().date() >= &since)
+      && until.is_none_or(|until| commit.committer().date() <= &until);
+
+    if is_shown {
+      commits.push(commit);
+    }
+  }
+
+  if options.reverse {
+    commits.reverse();
+  }
+
+  let format = if options.oneline {
+    Some(ONELINE_FORMAT)
+  } else {
+    options.format.as_deref()
+  };
+
+  let mut signatures = HashMap::new();
+
+  if options.show_signature {
+    for commit in &commits {
+      signatures.insert(commit.id().clone(), describe_signature(&commit.verify()?));
+    }
+  }
+
+  let describe = |commit: &Commit| {
+    let decoration = decorations.get(commit.id()).map(String::as_str).unwrap_or("");
+    let signature = signatures.get(commit.id()).map(String::as_str).unwrap_or("");
+
+    match format {
+      Some(format) => format!("{}{}\n", signature, format_commit(commit, format, decoration)),
+      None => format!("commit {}{}\n{}Author: {} <{}>\nDate:   {}\n\n{}\n",
+        commit.id(),
+        decoration,
+        signature,
+        commit.author().username(),
+        commit.author().email(),
+        commit.author().date(),
+        commit.message().lines().map(|line| format!("    {}\n", line)).collect::<String>(),
+      ),
+    }
+  };
+
+  let log = if options.graph {
+    graph(&commits, describe)
+  } else {
+    commits.iter().map(describe).collect()
+  };
+
+  page(log.trim_end())
+}
+
+// HEAD's history is a single line of first parents, read one commit at a time so a limited log
+// stops reading where it stops showing.
+enum History {
+  FirstParents {
+    tip: Option<Box<Commit>>,
+    next: Option<(String, String)>,
+  },
+  Sorted(vec::IntoIter<Commit>),
+}
+
+impl Iterator for History {
+  type Item = Result<Commit, Errors>;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    match self {
+      History::FirstParents { tip, next } => {
+        let commit = match tip.take() {
+          Some(commit) => *commit,
+          None => {
+            let (id, child_id) = next.take()?;
+
+            match Commit::from_id(&id).context(|| format!("unable to read parent of commit '{}'", child_id)) {
+              Ok(commit) => commit,
+              Err(error) => return Some(Err(error)),
+            }
+          },
+        };
+
+        *next = commit.parent_id().clone().map(|parent_id| (parent_id, commit.id().clone()));
+        Some(Ok(commit))
+      },
+      History::Sorted(commits) => commits.next().map(Ok),
+    }
+  }
+}
+
+// Commits are listed newest first but never before one of their children, so the histories of
+// several branches interleave by date.
+fn history(head: &Head, all: bool) -> Result<History, Errors> {
+  if !all {
+    return Ok(History::FirstParents { tip: head.commit()?.map(Box::new), next: None });
+  }
+
+  let mut tips = Vec::new();
+
+  if let Some(commit) = head.commit()? {
+    tips.push(commit);
+  }
+
+  for branch in Branch::list()? {
+    tips.extend(branch.commit()?);
+  }
+
+  let mut commits = HashMap::new();
+  let mut children = HashMap::new();
+
+  for tip in tips {
+    let mut current_commit = Some(tip);
+
+    while let Some(commit) = current_commit {
+      if commits.contains_key(commit.id()) {
+        break;
+      }
+
+      current_commit = match commit.parent_id() {
+        Some(parent_id) => {
+          let parent = Commit::from_id(parent_id).context(|| format!("unable to read parent of commit '{}'", commit.id()))?;
+          *children.entry(parent_id.clone()).or_insert(0) += 1;
+          Some(parent)
+        },
+        None => None,
+      };
+      commits.insert(commit.id().clone(), commit);
+    }
+  }
+
+  let mut ready = commits.values()
+    .filter(|commit| !children.contains_key(commit.id()))
+    .map(|commit| (commit.committer().date().with_timezone(&Utc), commit.id().clone()))
+    .collect::<BinaryHeap<(DateTime<Utc>, String)>>();
+  let mut history = Vec::new();
+
+  while let Some((_, id)) = ready.pop() {
+    let commit = commits.remove(&id).unwrap();
+
+    if let Some(parent_id) = commit.parent_id() {
+      let count = children.get_mut(parent_id).unwrap();
+      *count -= 1;
+
+      if *count == 0 {
+        let parent = &commits[parent_id];
+        ready.push((parent.committer().date().with_timezone(&Utc), parent.id().clone()));
+      }
+    }
+
+    history.push(commit);
+  }
+
+  Ok(History::Sorted(history.into_iter()))
+}
+
+fn parent_tree_id(commit: &Commit) -> Result<Option<String>, Errors> {
+  let parent = commit.parent_id().as_deref().map(Commit::from_id).transpose()?;
+  Ok(parent.map(|parent| parent.tree_id().clone()))
+}
+
+// Identifies what a path holds in a tree, reading only the trees along the path, so unchanged
+// folders compare by their tree id alone.
+fn node_id(tree_id: Option<&String>, path: &Path) -> Result<Option<(String, String)>, Errors> {
+  let tree_id = match tree_id {
+    Some(tree_id) => tree_id,
+    None => return Ok(None),
+  };
+
+  if path.as_os_str().is_empty() {
+    return Ok(Some((tree_id.clone(), String::new())));
+  }
+
+  Ok(Tree::entry(tree_id, path)?.map(|entry| match entry.mode.as_str() {
+    TREE_MODE => (entry.id, String::new()),
+    _ => (entry.id, entry.mode),
+  }))
+}
+
+// Returns whether the commit changed the file and the path it had in the parent commit. A file
+// missing from the parent was renamed when the parent holds the same content at a path the
+// commit no longer has, which only then needs both trees read whole.
+fn follow(commit: &Commit, path: &Path) -> Result<(bool, PathBuf), Errors> {
+  let parent_tree_id = parent_tree_id(commit)?;
+  let current = node_id(Some(commit.tree_id()), path)?;
+  let parent = node_id(parent_tree_id.as_ref(), path)?;
+
+  if current == parent {
+    return Ok((false, PathBuf::from(path)));
+  }
+
+  if let (Some((id, mode)), None, Some(parent_tree_id)) = (&current, &parent, &parent_tree_id) {
+    if !mode.is_empty() && mode != GITLINK_MODE {
+      let tree = commit.tree()?;
+      let renamed_path = Tree::from_id(parent_tree_id)?.blob_iter()
+        .filter(|(parent_path, parent_blob)| parent_blob.id() == id && tree.get(parent_path).is_none())
+        .map(|(parent_path, _)| parent_path)
+        .min();
+
+      if let Some(renamed_path) = renamed_path {
+        return Ok((true, renamed_path));
+      }
+    }
+  }
+
+  Ok((true, PathBuf::from(path)))
+}
+
+fn decorations(head: &Head) -> Result<HashMap<String, String>, Errors> {
+  let mut names: HashMap<String, Vec<String>> = HashMap::new();
+  let head_branch = match head.reference() {
+    Reference::Branch(branch) => Some(branch.name().clone()),
+    Reference::Commit(commit) => {
+      names.entry(commit.id().clone()).or_default().push(String::from("HEAD"));
+      None
+    },
+  };
+
+  for branch in Branch::list()? {
+    if let Some(commit_id) = branch.commit_id() {
+      let commit_names = names.entry(commit_id.clone()).or_default();
+
+      if head_branch.as_ref() == Some(branch.name()) {
+        commit_names.insert(0, format!("HEAD -> {}", branch.name()));
+      } else {
+        commit_names.push(branch.name().clone());
+      }
+    }
+  }
+
+  Ok(names.into_iter().map(|(id, names)| (id, format!(" ({})", names.join(", ")))).collect())
+}
+
+// Each column of the graph holds the id of the commit expected next in that line of history. A
+// commit is drawn in the first column expecting it, the column then continues with its parent,
+// and columns that reach the same parent are joined.
+fn graph<F: Fn(&Commit) -> String>(commits: &[Commit], describe: F) -> String {
+  let mut columns: Vec<String> = Vec::new();
+  let mut graph = String::new();
+
+  for commit in commits {
+    let column = match columns.iter().position(|id| id == commit.id()) {
+      Some(column) => column,
+      None => {
+        columns.push(commit.id().clone());
+        columns.len() - 1
+      },
+    };
+
+    let row = (0..columns.len())
+      .map(|index| if index == column { "*" } else { "|" })
+      .collect::<Vec<&str>>()
+      .join(" ");
+    let continuation = (0..columns.len())
+      .map(|index| if index == column && commit.parent_id().is_none() { " " } else { "|" })
+      .collect::<Vec<&str>>()
+      .join(" ");
+    let description = describe(commit);
+    let mut lines = description.lines();
+
+    graph += &format!("{} {}\n", row, lines.next().unwrap_or(""));
+
+    for line in lines {
+      graph += format!("{} {}", continuation, line).trim_end();
+      graph += "\n";
+    }
+
+    match commit.parent_id() {
+      Some(parent_id) => {
+        columns[column] = parent_id.clone();
+
+        while let Some((first, second)) = duplicate_columns(&columns) {
+          graph += &join_line(columns.len(), first, second);
+          columns.remove(second);
+        }
+      },
+      None => {
+        if column + 1 < columns.len() {
+          graph += &join_line(columns.len(), column, column);
+        }
+
+        columns.remove(column);
+      },
+    }
+  }
+
+  graph
+}
+
+fn duplicate_columns(columns: &[String]) -> Option<(usize, usize)> {
+  for second in 0..columns.len() {
+    if let Some(first) = columns[..second].iter().position(|id| id == &columns[second]) {
+      return Some((first, second));
+    }
+  }
+
+  None
+}
+
+// Draws column `second` moving into column `first`, e.g. `|/` or `|_|/`, with the columns after
+// it shifting one to the left. When both are the same, the column ends instead.
+fn join_line(width: usize, first: usize, second: usize) -> String {
+  let mut line = vec![' '; width * 2];
+
+  for column in 0..width {
+    if column < second {
+      line[column * 2] = '|';
+    } else if column > second {
+      line[column * 2 - 1] = '/';
+    }
+  }
+
+  if first != second {
+    for gap in first..second - 1 {
+      line[gap * 2 + 1] = '_';
+    }
+
+    line[second * 2 - 1] = '/';
+  }
+
+  String::from(line.iter().collect::<String>().trim_end()) + "\n"
+}
+
+fn format_commit(commit: &Commit, format: &str, decoration: &str) -> String {
+  let placeholders: [(&str, String); 12] = [
+    ("H", commit.id().clone()),
+    ("h", commit.id().chars().take(SHORT_ID_LENGTH).collect()),
+    ("an", commit.author().username().clone()),
+    ("ae", commit.author().email().clone()),
+    ("ad", commit.author().date().to_string()),
+    ("cn", commit.committer().username().clone()),
+    ("ce", commit.committer().email().clone()),
+    ("cd", commit.committer().date().to_string()),
+    ("s", String::from(commit.message().lines().next().unwrap_or(""))),
+    ("d", String::from(decoration)),
+    ("n", String::from("\n")),
+    ("%", String::from("%")),
+  ];
+
+  let mut formatted = String::new();
+  let mut rest = format;
+
+  while let Some(position) = rest.find('%') {
+    formatted += &rest[..position];
+    rest = &rest[position + 1..];
+
+    match placeholders.iter().find(|(placeholder, _)| rest.starts_with(placeholder)) {
+      Some((placeholder, value)) => {
+        formatted += value;
+        rest = &rest[placeholder.len()..];
+      },
+      None => formatted.push('%'),
+    }
+  }
+
+  formatted + rest
+}
+
+fn describe_signature(status: &SignatureStatus) -> String {
+  match status {
+    SignatureStatus::Unsigned => String::new(),
+    SignatureStatus::Good(email) => format!("Good signature from {}\n", email),
+    SignatureStatus::Bad(email) => format!("BAD signature from {}\n", email),
+  }
+}
+
+fn parse_pattern(pattern: &str) -> Result<Regex, Errors> {
+  Regex::new(pattern).map_err(|_| Errors::UnrecognisedPattern(String::from(pattern)))
+}
 
-  while let Some(commit) = current_commit {
-    log += &format!("commit {}\n", commit.id());
-    log += &format!("Author: {} <{}>\n", commit.author().username(), commit.author().email());
-    log += &format!("Date:   {}\n", commit.date());
-    log += &format!("\n    {}\n\n", commit.message());
+// The log is paged through `less` only when a person is reading it.
+fn page(log: &str) -> Result<(), Errors> {
+  if !io::stdout().is_terminal() {
+    if log.is_empty() {
+      return Ok(());
+    }
 
-    current_commit = commit.parent();
+    return match writeln!(io::stdout(), "{}", log) {
+      Err(error) if error.kind() == ErrorKind::BrokenPipe => Ok(()),
+      result => Ok(result?),
+    };
   }
 
   let mut temp_file = NamedTempFile::new()?;
-  temp_file.write_all(log.trim_end().as_bytes())?;
+  temp_file.write_all(log.as_bytes())?;
 
   Command::new("less")
     .arg(temp_file.path())
@@ -0,0 +1,38 @@
+use crate::{lib::{errors::Errors, object::{Object, FromId}, constants::{TREE_MODE, GITLINK_MODE}, read_object, name_bytes}, core::{revision::Revision, commit::Commit, tree::{Tree, Node}}};
+
+pub fn ls_tree(tree_ish: String, recursive: bool) -> Result<(), Errors> {
+  let id = Revision::resolve_object(&tree_ish)?;
+
+  let tree = match read_object(&id)?.0 {
+    Object::Commit => Commit::from_id(&id)?.tree()?,
+    Object::Tree => Tree::from_id(&id)?,
+    Object::Blob => return Err(Errors::BadArguments(format!("'{}' is not a tree", tree_ish))),
+  };
+
+  print!("{}", tree_entries(&tree, recursive));
+
+  Ok(())
+}
+
+// Lists entries as `<mode> <type> <id>\t<path>` sorted by path, descending into folders when `recursive`.
+pub fn tree_entries(tree: &Tree, recursive: bool) -> String {
+  let mut entries = if recursive {
+    tree.blob_iter()
+      .map(|(path, blob)| (name_bytes(path), blob.mode().as_str(), Object::Blob, blob.id().clone()))
+      .collect::<Vec<_>>()
+  } else {
+    tree.children().iter()
+      .map(|(name, node)| match node {
+        Node::Tree(tree) => (name_bytes(name), TREE_MODE, Object::Tree, tree.id().clone()),
+        Node::Blob(blob) => (name_bytes(name), blob.mode().as_str(), Object::Blob, blob.id().clone()),
+        Node::Gitlink(id) => (name_bytes(name), GITLINK_MODE, Object::Commit, id.clone()),
+      })
+      .collect::<Vec<_>>()
+  };
+
+  entries.sort_by(|a, b| a.0.cmp(&b.0));
+
+  entries.into_iter()
+    .map(|(path, mode, object, id)| format!("{} {} {}\t{}\n", mode, object.as_str(), id, String::from_utf8_lossy(&path)))
+    .collect()
+}
@@ -7,31 +7,60 @@ pub mod config;
 pub mod status;
 pub mod branch;
 pub mod log;
+pub mod fast_export;
+pub mod fast_import;
+pub mod convert_objects;
+pub mod blame;
+pub mod show;
+pub mod cat_file;
+pub mod hash_object;
+pub mod ls_tree;
+pub mod rev_parse;
+pub mod update_ref;
+pub mod verify_commit;
+pub mod signing_key;
+pub mod bisect;
+pub mod grep;
 
 use std::path::PathBuf;
-use clap::Subcommand;
+use clap::{Subcommand, ArgGroup};
+use log::LogOptions;
+use commit::CommitOptions;
+use branch::BranchOptions;
+use config::ConfigAction;
+use bisect::BisectAction;
+use grep::GrepOptions;
 
 #[derive(Subcommand)]
 pub enum Commands {
   #[clap(about = "Creates an empty repository")]
-  Init,
+  Init {
+    #[clap(long, help = "Creates a Git compatible repository in .git")]
+    git: bool,
+
+    #[clap(long, default_value = "sha1", possible_values = ["sha1", "sha256"], help = "Hash algorithm naming the objects")]
+    object_format: String,
+  },
 
   #[clap(about = "Stages files for commit")]
   Add {
-    #[clap(required = true)]
+    #[clap(required_unless_present = "patch", parse(from_os_str))]
     paths: Vec<PathBuf>,
+
+    #[clap(short, long, help = "Picks the changes to stage hunk by hunk")]
+    patch: bool,
   },
 
   #[clap(about = "Unstages staged files")]
   Restore {
-    #[clap(required = true)]
+    #[clap(required = true, parse(from_os_str))]
     paths: Vec<PathBuf>,
   },
 
   #[clap(about = "Creates a snapshot of the changes")]
   Commit {
-    #[clap(short, long)]
-    message: String,
+    #[clap(flatten)]
+    options: CommitOptions,
   },
 
   #[clap(about = "Switches to a different snapshot")]
@@ -46,24 +75,150 @@ pub enum Commands {
     target: String,
   },
 
-  #[clap(about = "Sets the user info")]
+  #[clap(about = "Gets and sets configuration values")]
   Config {
-    #[clap(long, required_unless_present = "email")]
+    #[clap(subcommand)]
+    action: Option<ConfigAction>,
+
+    #[clap(long, global = true, conflicts_with = "system", help = "Uses the user config in ~/.rgitconfig")]
+    global: bool,
+
+    #[clap(long, global = true, help = "Uses the system config")]
+    system: bool,
+
+    #[clap(short, long, help = "Lists every setting")]
+    list: bool,
+
+    #[clap(long, help = "Sets user.name")]
     username: Option<String>,
 
-    #[clap(long, required_unless_present = "username")]
+    #[clap(long, help = "Sets user.email")]
     email: Option<String>,
   },
 
   #[clap(about = "Show the working tree status")]
   Status,
 
-  #[clap(about = "List or delete branches")]
+  #[clap(about = "Lists, creates, renames, copies or deletes branches")]
   Branch {
-    #[clap(short)]
-    delete: Option<String>,
+    #[clap(flatten)]
+    options: BranchOptions,
   },
 
   #[clap(about = "Show commit logs")]
-  Log,
+  Log {
+    #[clap(flatten)]
+    options: LogOptions,
+  },
+
+  #[clap(name = "fast-export", about = "Writes the history as a fast-import stream")]
+  FastExport,
+
+  #[clap(name = "fast-import", about = "Reads a fast-import stream into the repository")]
+  FastImport,
+
+  #[clap(name = "convert-objects", about = "Rewrites every object with a different hash algorithm")]
+  ConvertObjects {
+    #[clap(possible_values = ["sha1", "sha256"], help = "Target object format")]
+    object_format: String,
+  },
+
+  #[clap(about = "Shows the commit that last changed each line of a file")]
+  Blame {
+    #[clap(parse(from_os_str))]
+    path: PathBuf,
+
+    #[clap(help = "Commit to annotate the file at (defaults to HEAD)")]
+    revision: Option<String>,
+
+    #[clap(short = 'L', help = "Annotates only the lines <start>,<end> or <start>,+<count>")]
+    lines: Option<String>,
+
+    #[clap(long, help = "Prints a machine readable format")]
+    porcelain: bool,
+  },
+
+  #[clap(about = "Shows a commit with its changes, or the file or folder at <revision>:<path>")]
+  Show {
+    #[clap(help = "Revision, object id or <revision>:<path> (defaults to HEAD)")]
+    object: Option<String>,
+  },
+
+  #[clap(name = "cat-file", about = "Prints the type, size or content of an object")]
+  #[clap(group(ArgGroup::new("query").required(true).args(&["object-type", "size", "pretty"])))]
+  CatFile {
+    #[clap(short = 't', help = "Prints the object type")]
+    object_type: bool,
+
+    #[clap(short, help = "Prints the object size in bytes")]
+    size: bool,
+
+    #[clap(short, help = "Prints the object content")]
+    pretty: bool,
+
+    #[clap(help = "Revision, object id or <revision>:<path>")]
+    object: String,
+  },
+
+  #[clap(name = "hash-object", about = "Computes the id of files as blobs")]
+  HashObject {
+    #[clap(short, help = "Writes the blobs into the repository")]
+    write: bool,
+
+    #[clap(long, help = "Reads the content from standard input")]
+    stdin: bool,
+
+    #[clap(required_unless_present = "stdin", parse(from_os_str))]
+    paths: Vec<PathBuf>,
+  },
+
+  #[clap(name = "ls-tree", about = "Lists the content of a folder")]
+  LsTree {
+    #[clap(short, help = "Lists the files of every subfolder")]
+    recursive: bool,
+
+    #[clap(help = "Revision, tree id or <revision>:<path>")]
+    tree_ish: String,
+  },
+
+  #[clap(name = "rev-parse", about = "Prints the object ids of revisions")]
+  RevParse {
+    #[clap(required = true)]
+    names: Vec<String>,
+  },
+
+  #[clap(name = "update-ref", about = "Points a branch or HEAD at a commit")]
+  UpdateRef {
+    #[clap(help = "HEAD, a branch name or refs/heads/<name>")]
+    reference: String,
+
+    new_value: String,
+
+    #[clap(help = "Updates only while the reference still points at this commit")]
+    old_value: Option<String>,
+  },
+
+  #[clap(name = "verify-commit", about = "Checks commit signatures against the trusted keys")]
+  VerifyCommit {
+    #[clap(required = true)]
+    revisions: Vec<String>,
+  },
+
+  #[clap(name = "signing-key", about = "Prints the trusted keys line for the key in user.signingkey")]
+  SigningKey {
+    #[clap(long, help = "Creates the key file first")]
+    generate: bool,
+  },
+
+  #[clap(about = "Finds the commit that introduced a bug by binary search")]
+  Bisect {
+    #[clap(subcommand)]
+    action: BisectAction,
+  },
+
+  #[clap(about = "Searches tracked files, in the working tree or at a revision, for lines matching a pattern")]
+  Grep {
+    #[clap(flatten)]
+    options: GrepOptions,
+  },
 }
@@ -0,0 +1,13 @@
+use crate::{lib::errors::Errors, core::revision::Revision};
+
+pub fn rev_parse(names: Vec<String>) -> Result<(), Errors> {
+  let mut output = String::new();
+
+  for name in names {
+    output += &format!("{}\n", Revision::resolve_object(&name)?);
+  }
+
+  print!("{}", output);
+
+  Ok(())
+}
@@ -0,0 +1,113 @@
+use std::{io::{self, Write}, collections::BTreeSet, path::PathBuf};
+use crate::{lib::{errors::Errors, object::{Object, FromId}, diff::unified, read_object, read_object_bytes, name_bytes}, core::{revision::Revision, commit::Commit, tree::{Tree, Node}, blob::Blob}};
+
+const CONTEXT_LINES: usize = 3;
+const EMPTY_PATH: &str = "/dev/null";
+
+pub fn show(object: Option<String>) -> Result<(), Errors> {
+  let name = object.unwrap_or_else(|| String::from("HEAD"));
+  let id = Revision::resolve_object(&name)?;
+  let (object_type, bytes) = read_object(&id)?;
+
+  let output = match object_type {
+    Object::Commit => {
+      let commit = Commit::from_id(&id)?;
+      let parent_tree = match commit.parent() {
+        Some(parent) => parent.tree()?,
+        None => Tree::new(),
+      };
+
+      let mut output = format!("commit {}\nAuthor: {} <{}>\nDate:   {}\n\n", commit.id(), commit.author().username(), commit.author().email(), commit.author().date());
+
+      for line in commit.message().lines() {
+        output += &format!("    {}\n", line);
+      }
+
+      let changes = diff_trees(&parent_tree, &commit.tree()?)?;
+
+      if !changes.is_empty() {
+        output += "\n";
+        output += &changes;
+      }
+
+      output.into_bytes()
+    },
+    Object::Tree => {
+      let tree = Tree::from_id(&id)?;
+      let mut output = format!("tree {}\n\n", name);
+      let names = tree.children().iter()
+        .map(|(name, node)| match node {
+          Node::Tree(_) => format!("{}/", name.to_string_lossy()),
+          Node::Blob(_) | Node::Gitlink(_) => name.to_string_lossy().into_owned(),
+        })
+        .collect::<BTreeSet<String>>();
+
+      for name in names {
+        output += &format!("{}\n", name);
+      }
+
+      output.into_bytes()
+    },
+    Object::Blob => bytes,
+  };
+
+  io::stdout().write_all(&output)?;
+
+  Ok(())
+}
+
+// Describes every changed file as a Git style patch.
+fn diff_trees(old_tree: &Tree, new_tree: &Tree) -> Result<String, Errors> {
+  let old_blobs = old_tree.blobs();
+  let new_blobs = new_tree.blobs();
+  let mut paths = old_blobs.keys().chain(new_blobs.keys()).collect::<Vec<&PathBuf>>();
+  let mut output = String::new();
+
+  paths.sort_by_key(|path| name_bytes(path));
+  paths.dedup();
+
+  for path in paths {
+    let old_blob = old_blobs.get(path).copied();
+    let new_blob = new_blobs.get(path).copied();
+    let path = path.display();
+
+    if old_blob == new_blob {
+      continue;
+    }
+
+    output += &format!("diff --git a/{} b/{}\n", path, path);
+
+    match (old_blob, new_blob) {
+      (None, Some(new_blob)) => output += &format!("new file mode {}\n", new_blob.mode().as_str()),
+      (Some(old_blob), None) => output += &format!("deleted file mode {}\n", old_blob.mode().as_str()),
+      (Some(old_blob), Some(new_blob)) if old_blob.mode() != new_blob.mode() => {
+        output += &format!("old mode {}\nnew mode {}\n", old_blob.mode().as_str(), new_blob.mode().as_str());
+      },
+      _ => (),
+    }
+
+    if old_blob.map(Blob::id) == new_blob.map(Blob::id) {
+      continue;
+    }
+
+    let old_bytes = old_blob.map(|blob| read_object_bytes(blob.id())).transpose()?.unwrap_or_default();
+    let new_bytes = new_blob.map(|blob| read_object_bytes(blob.id())).transpose()?.unwrap_or_default();
+    let old_path = old_blob.map_or(String::from(EMPTY_PATH), |_| format!("a/{}", path));
+    let new_path = new_blob.map_or(String::from(EMPTY_PATH), |_| format!("b/{}", path));
+
+    if old_bytes.contains(&0) || new_bytes.contains(&0) {
+      output += &format!("Binary files {} and {} differ\n", old_path, new_path);
+      continue;
+    }
+
+    let old_text = String::from_utf8_lossy(&old_bytes);
+    let new_text = String::from_utf8_lossy(&new_bytes);
+    let old_lines = old_text.lines().collect::<Vec<&str>>();
+    let new_lines = new_text.lines().collect::<Vec<&str>>();
+
+    output += &format!("--- {}\n+++ {}\n", old_path, new_path);
+    output += &unified(&old_lines, &new_lines, CONTEXT_LINES);
+  }
+
+  Ok(output)
+}
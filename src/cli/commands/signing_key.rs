@@ -0,0 +1,12 @@
+use crate::lib::{errors::Errors, signature::SigningKey, user::User};
+
+pub fn signing_key(generate: bool) -> Result<(), Errors> {
+  let key = match generate {
+    true => SigningKey::generate()?,
+    false => SigningKey::load()?,
+  };
+
+  println!("{} {}", User::get()?.email(), key.public_key());
+
+  Ok(())
+}
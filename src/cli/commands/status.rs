@@ -1,8 +1,64 @@
-use crate::{core::{head::{Head, Reference}, tree::Tree, index::Index, blob::Blob}, lib::{folder_files, locale, errors::Errors, constants::DELETED_INDEX_STAGE, relative}};
+use std::path::{Path, PathBuf};
+use crate::{core::{head::{Head, Reference}, tree::Tree, index::{Index, Stage}, blob::Blob}, lib::{folder_files, locale, errors::Errors, relative}};
+
+// The staged and the unstaged changes, each as status lists them, and the untracked files.
+type Changes = (Vec<String>, Vec<String>, Vec<PathBuf>);
 
 pub fn status() -> Result<(), Errors> {
   let head = Head::get()?;
-  let commit = head.commit();
+  let (changes_staged_for_commit, mut changes_not_staged_for_commit, untracked_files) = changes(&head)?;
+  let mut status = match head.reference() {
+    Reference::Branch(branch) => format!("On branch {}\n", branch.name()),
+    Reference::Commit(commit) => format!("HEAD detached at {}\n", commit.id())
+  };
+
+  changes_not_staged_for_commit.extend(untracked_files.iter().map(|path| format!("      modified:  {}\n", path.display())));
+  changes_not_staged_for_commit.sort();
+
+  if !changes_staged_for_commit.is_empty() {
+    status += "Changes to be committed:\n";
+    status += "  (use \"rgit restore <PATHS>...\" to unstage)\n";
+
+    for change in &changes_staged_for_commit {
+      status += &change;
+    }
+
+    status += "\n";
+  }
+
+  if !changes_not_staged_for_commit.is_empty() {
+    status += "Changes not staged for commit:\n";
+    status += "  (use \"rgit add <PATHS>...\" to update what will be committed)\n";
+
+    for change in &changes_not_staged_for_commit {
+      status += &change;
+    }
+
+    status += "\n";
+  }
+
+  if changes_staged_for_commit.is_empty() && changes_not_staged_for_commit.is_empty() {
+    status += "nothing to commit, working tree clean\n";
+  }
+
+  print!("{}", status);
+
+  Ok(())
+}
+
+// Whether status would report nothing to commit, untracked files aside.
+pub fn is_clean() -> Result<bool, Errors> {
+  let (staged, not_staged, _) = changes(&Head::get()?)?;
+  Ok(staged.is_empty() && not_staged.is_empty())
+}
+
+pub fn untracked_files() -> Result<Vec<PathBuf>, Errors> {
+  let (_, _, untracked_files) = changes(&Head::get()?)?;
+  Ok(untracked_files)
+}
+
+fn changes(head: &Head) -> Result<Changes, Errors> {
+  let commit = head.commit()?;
   let tree = match commit {
     Some(commit) => commit.tree()?,
     None => Tree::new(),
@@ -10,35 +66,37 @@ pub fn status() -> Result<(), Errors> {
   let index = Index::get()?;
   let blobs = tree.blobs();
   let file_paths = folder_files(locale())?;
-  let mut status = String::new();
-
-  status += &match head.reference() {
-    Reference::Branch(branch) => format!("On branch {}\n", branch.name()),
-    Reference::Commit(commit) => format!("HEAD detached at {}\n", commit.id())
-  };
-
   let mut changes_staged_for_commit = Vec::new();
   let mut changes_not_staged_for_commit = Vec::new();
+  let mut untracked_files = Vec::new();
 
   if !index.staged_paths().is_empty() {
     for (path, stage) in index.staged_paths() {
-      changes_staged_for_commit.push(if stage == DELETED_INDEX_STAGE  {
-        format!("      deleted:   {}\n", path)
-      } else {
-        format!("      modified:  {}\n", path)
+      changes_staged_for_commit.push(match stage {
+        Stage::Blob(blob) => modification(path, blobs.get(path).copied(), blob),
+        Stage::Deleted => format!("      deleted:   {}\n", path.display()),
       });
     }
   }
 
   for (blob_path, _) in &blobs {
-    let full_blob_path = locale().join(blob_path);
+    let full_blob_path = locale().as_std_path().join(blob_path);
 
     if !file_paths.contains(&full_blob_path) && !index.staged_paths().contains_key(blob_path) {
-      changes_not_staged_for_commit.push(format!("      deleted:   {}\n", blob_path));
+      changes_not_staged_for_commit.push(format!("      deleted:   {}\n", blob_path.display()));
     }
   }
 
   for file_path in file_paths {
+    // Files may be staged in part, leaving the rest of their changes unstaged.
+    if let Some(Stage::Blob(staged_blob)) = index.staged_paths().get(&relative(&file_path)) {
+      let blobified_file = Blob::from_path(&file_path)?;
+
+      if staged_blob != &blobified_file {
+        changes_not_staged_for_commit.push(modification(&relative(&file_path), Some(staged_blob), &blobified_file));
+      }
+    }
+
     if !index.staged_paths().contains_key(&relative(&file_path)) {
       let relative_file_path = relative(&file_path);
       let blob = blobs.get(&relative_file_path);
@@ -48,11 +106,11 @@ pub fn status() -> Result<(), Errors> {
           let blobified_file = Blob::from_path(&file_path)?;
 
           if blob != &&blobified_file {
-            changes_not_staged_for_commit.push(format!("      modified:  {}\n", relative_file_path));
+            changes_not_staged_for_commit.push(modification(&relative_file_path, Some(blob), &blobified_file));
           }
         },
         None => {
-          changes_not_staged_for_commit.push(format!("      modified:  {}\n", relative(file_path)));
+          untracked_files.push(relative_file_path);
         }
       }
     }
@@ -60,34 +118,16 @@ pub fn status() -> Result<(), Errors> {
 
   changes_staged_for_commit.sort();
   changes_not_staged_for_commit.sort();
+  untracked_files.sort();
 
-  if !changes_staged_for_commit.is_empty() {
-    status += "Changes to be committed:\n";
-    status += "  (use \"rgit restore <PATHS>...\" to unstage)\n";
-
-    for change in &changes_staged_for_commit {
-      status += &change;
-    }
-
-    status += "\n";
-  }
-
-  if !changes_not_staged_for_commit.is_empty() {
-    status += "Changes not staged for commit:\n";
-    status += "  (use \"rgit add <PATHS>...\" to update what will be committed)\n";
-
-    for change in &changes_not_staged_for_commit {
-      status += &change;
-    }
-
-    status += "\n";
-  }
+  Ok((changes_staged_for_commit, changes_not_staged_for_commit, untracked_files))
+}
 
-  if changes_staged_for_commit.is_empty() && changes_not_staged_for_commit.is_empty() {
-    status += "nothing to commit, working tree clean\n";
+fn modification(path: &Path, old: Option<&Blob>, new: &Blob) -> String {
+  match old {
+    Some(old) if old.id() == new.id() && old.mode() != new.mode() => {
+      format!("      modified:  {} (mode {} -> {})\n", path.display(), old.mode().as_str(), new.mode().as_str())
+    },
+    _ => format!("      modified:  {}\n", path.display()),
   }
-
-  print!("{}", status);
-
-  Ok(())
 }
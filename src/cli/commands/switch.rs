@@ -1,8 +1,14 @@
-use crate::{lib::{errors::Errors, object::FromId, locale}, core::{head::{Head, Reference}, commit::Commit, branch::Branch}, cli::clear_project};
+use std::path::PathBuf;
+use crate::{lib::{errors::Errors, object::FromId, hook::Hook, format::ObjectFormat, locale}, core::{head::{Head, Reference}, commit::Commit, branch::Branch, index::Index}, cli::clear_project};
 
 pub fn switch(new: bool, commit: bool, target: String) -> Result<(), Errors> {
+  switch_keeping(new, commit, target, &[])
+}
+
+// Switches leaving the given files in place, which the caller checks the new commit doesn't hold.
+pub fn switch_keeping(new: bool, commit: bool, target: String, kept: &[PathBuf]) -> Result<(), Errors> {
   let head = Head::get()?;
-  let commit_id = head.commit().map(|commit| String::from(commit.id()));
+  let commit_id = head.commit()?.map(|commit| String::from(commit.id()));
   let reference;
 
   if new {
@@ -13,19 +19,26 @@ pub fn switch(new: bool, commit: bool, target: String) -> Result<(), Errors> {
     reference = Reference::Branch(Branch::get(&target)?);
   }
 
-  clear_project()?;
+  let new_commit = Index::keep_staged(|| {
+    clear_project(kept)?;
+
+    Head::set(&reference)?;
+    let new_commit = match reference {
+      Reference::Branch(branch) => branch.commit()?,
+      Reference::Commit(commit) => Some(commit),
+    };
 
-  Head::set(&reference)?;
-  match reference {
-    Reference::Branch(branch) => {
-      if let Some(commit) = branch.commit() {
-        commit.tree()?.unpack(locale())?;
-      }
-    },
-    Reference::Commit(commit) => {
+    if let Some(commit) = &new_commit {
       commit.tree()?.unpack(locale())?;
     }
-  }
 
-  Ok(())
+    Ok(new_commit)
+  })?;
+
+  // The hook gets the previous and new commit ids, all zeros standing for none.
+  let zero_id = "0".repeat(ObjectFormat::get()?.byte_length() * 2);
+  let previous_id = commit_id.unwrap_or_else(|| zero_id.clone());
+  let new_id = new_commit.map_or(zero_id, |commit| commit.id().clone());
+
+  Hook::PostSwitch.run(&[&previous_id, &new_id])
 }
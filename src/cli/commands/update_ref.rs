@@ -0,0 +1,43 @@
+use crate::{lib::{errors::Errors, format::ObjectFormat}, core::{revision::Revision, branch::Branch, head::{Head, Reference}}};
+
+const BRANCH_REFERENCE_PREFIX: &str = "refs/heads/";
+const REFERENCE_PREFIX: &str = "refs/";
+const HEAD_REFERENCE: &str = "HEAD";
+
+// As in Git, an old value of all zeros expects the branch to have no commit yet. Tags and other
+// references outside `refs/heads/` are not supported.
+pub fn update_ref(reference: String, new_value: String, old_value: Option<String>) -> Result<(), Errors> {
+  if reference.starts_with(REFERENCE_PREFIX) && !reference.starts_with(BRANCH_REFERENCE_PREFIX) {
+    return Err(Errors::BadArguments(format!("cannot update '{}': only branches under '{}' are supported", reference, BRANCH_REFERENCE_PREFIX)));
+  }
+
+  let commit = Revision::resolve(&new_value)?;
+  let expected_id = match old_value.as_deref() {
+    Some(old_value) if is_zero_id(old_value) => Some(None),
+    Some(old_value) => Some(Some(Revision::resolve(old_value)?.id().clone())),
+    None => None,
+  };
+
+  let name = if reference == HEAD_REFERENCE {
+    match Head::get()?.reference() {
+      Reference::Branch(branch) => branch.name().clone(),
+      Reference::Commit(_) => return match expected_id {
+        Some(expected_id) => Head::replace(commit, expected_id.as_deref()),
+        None => Head::set(&Reference::Commit(commit)),
+      },
+    }
+  } else {
+    String::from(reference.strip_prefix(BRANCH_REFERENCE_PREFIX).unwrap_or(&reference))
+  };
+
+  match (expected_id, Branch::get(&name)) {
+    (Some(expected_id), _) => Branch::replace(&name, commit.id(), expected_id.as_deref()),
+    (None, Ok(_)) => Branch::set(&name, commit.id()),
+    (None, Err(Errors::UnrecognisedBranch(_))) => Branch::new(&name, Some(commit.id())).map(|_| ()),
+    (None, Err(error)) => Err(error),
+  }
+}
+
+fn is_zero_id(id: &str) -> bool {
+  ObjectFormat::is_id(id) && id.bytes().all(|byte| byte == b'0')
+}
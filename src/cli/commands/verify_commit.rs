@@ -0,0 +1,14 @@
+use crate::{lib::{errors::Errors, signature::SignatureStatus}, core::revision::Revision};
+
+pub fn verify_commit(revisions: Vec<String>) -> Result<(), Errors> {
+  for revision in revisions {
+    let commit = Revision::resolve(&revision)?;
+
+    match commit.verify()? {
+      SignatureStatus::Good(email) => println!("Good signature from {} on commit {}", email, commit.id()),
+      _ => return Err(Errors::BadSignature(commit.id().clone())),
+    }
+  }
+
+  Ok(())
+}
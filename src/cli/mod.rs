@@ -1,12 +1,13 @@
 mod commands;
 
-use std::{env, path::PathBuf, fs};
-use camino::Utf8PathBuf;
-use clap::Parser;
-use path_clean::PathClean;
-use crate::lib::{errors::Errors, constants::{PROJECT_ENV, REPOSITORY_PATH, REPOSITORY_FOLDER_NAME}, locale};
+use std::{env, ffi::OsString, path::{Component, Path, PathBuf}, fs, process, collections::HashSet};
+use clap::{Parser, IntoApp};
+use crate::lib::{errors::{Errors, Context}, config::Config, constants::{PROJECT_ENV, REPOSITORY_PATH, GIT_REPOSITORY_PATH}, layout::Layout, locale, relative, initialize_git_state};
 use commands::init::init;
-use self::commands::{Commands, add::add, restore::restore, commit::commit, switch::switch, config::config, status::status, branch::branch, log::log};
+use self::commands::{Commands, add::add, restore::restore, commit::commit, switch::switch, config::config, status::status, branch::branch, log::log, fast_export::fast_export, fast_import::fast_import, convert_objects::convert_objects, blame::blame, show::show, cat_file::cat_file, hash_object::hash_object, ls_tree::ls_tree, rev_parse::rev_parse, update_ref::update_ref, verify_commit::verify_commit, signing_key::signing_key, bisect::bisect, grep::grep};
+
+const ALIAS_SECTION: &str = "alias";
+const HELP_COMMAND: &str = "help";
 
 #[derive(Parser)]
 #[clap(name = "rgit")]
@@ -20,29 +21,86 @@ pub struct Interface {
 
 impl Interface {
   pub fn run() -> Result<(), Errors> {
-    let interface = Self::parse();
+    let interface = Self::parse_from(Self::expand_alias(env::args_os().collect())?);
 
-    if let Commands::Init = interface.commands {
-      return init();
+    if let Commands::Init { git, object_format } = interface.commands {
+      return init(git, object_format);
     }
 
     Self::detect_repository()?;
 
     match interface.commands {
-      Commands::Add { paths } => add(paths),
+      Commands::Add { paths, patch } => add(paths, patch),
       Commands::Restore { paths } => restore(paths),
-      Commands::Commit { message } => commit(message),
+      Commands::Commit { options } => commit(options),
       Commands::Switch { new, commit, target } => switch(new, commit, target),
-      Commands::Config { username, email } => config(username, email),
+      Commands::Config { action, global, system, list, username, email } => config(action, global, system, list, username, email),
       Commands::Status => status(),
-      Commands::Branch { delete } => branch(delete),
-      Commands::Log => log(),
+      Commands::Branch { options } => branch(options),
+      Commands::Log { options } => log(options),
+      Commands::FastExport => fast_export(),
+      Commands::FastImport => fast_import(),
+      Commands::ConvertObjects { object_format } => convert_objects(object_format),
+      Commands::Blame { path, revision, lines, porcelain } => blame(path, revision, lines, porcelain),
+      Commands::Show { object } => show(object),
+      Commands::CatFile { object, object_type, size, .. } => cat_file(object, object_type, size),
+      Commands::HashObject { paths, write, stdin } => hash_object(paths, write, stdin),
+      Commands::LsTree { tree_ish, recursive } => ls_tree(tree_ish, recursive),
+      Commands::RevParse { names } => rev_parse(names),
+      Commands::UpdateRef { reference, new_value, old_value } => update_ref(reference, new_value, old_value),
+      Commands::VerifyCommit { revisions } => verify_commit(revisions),
+      Commands::SigningKey { generate } => signing_key(generate),
+      Commands::Bisect { action } => bisect(action),
+      Commands::Grep { options } => grep(options),
       _ => Ok(())
     }
   }
 
   // private
 
+  // Replaces an `alias.<name>` config entry naming no built-in command with its expansion, which
+  // may name another alias. Expansions starting with `!` run as shell commands from the top of the
+  // repository, with the remaining arguments appended.
+  fn expand_alias(mut arguments: Vec<OsString>) -> Result<Vec<OsString>, Errors> {
+    let mut expanded = HashSet::new();
+
+    while let Some(name) = arguments.get(1).and_then(|name| name.to_str()).map(String::from) {
+      if name.starts_with('-') || name == HELP_COMMAND || Self::into_app().find_subcommand(&name).is_some() {
+        break;
+      }
+
+      Self::detect_repository().ok();
+
+      let expansion = match Config::load()?.get(&format!("{}.{}", ALIAS_SECTION, name)) {
+        Some(expansion) => expansion.clone(),
+        None => break,
+      };
+
+      if !expanded.insert(name.clone()) {
+        return Err(Errors::BadArguments(format!("alias '{}' expands into itself", name)));
+      }
+
+      if let Some(command) = expansion.strip_prefix('!') {
+        let folder = env::var(PROJECT_ENV).map(PathBuf::from).or_else(|_| env::current_dir())?;
+        let status = process::Command::new("sh")
+          .arg("-c")
+          .arg(format!("{} \"$@\"", command))
+          .arg(&name)
+          .args(&arguments[2..])
+          .current_dir(folder)
+          .status()
+          .context(|| format!("unable to run alias '{}'", name))?;
+
+        process::exit(status.code().unwrap_or(1));
+      }
+
+      let words = split_words(&expansion).ok_or_else(|| Errors::BadArguments(format!("alias '{}' has an unclosed quote", name)))?;
+      arguments.splice(1..2, words.into_iter().map(OsString::from));
+    }
+
+    Ok(arguments)
+  }
+
   fn detect_repository() -> Result<(), Errors> {
     let current_dir = env::current_dir()?;
     let mut current_path = Some(current_dir.as_path());
@@ -53,6 +111,11 @@ impl Interface {
         return Ok(());
       }
 
+      if path.join(GIT_REPOSITORY_PATH).exists() {
+        env::set_var(PROJECT_ENV, path);
+        return initialize_git_state();
+      }
+
       current_path = path.parent();
     }
 
@@ -60,39 +123,99 @@ impl Interface {
   }
 }
 
-pub fn configure_input_paths(paths: &[PathBuf]) -> Result<Vec<Utf8PathBuf>, Errors> {
+pub fn configure_input_paths(paths: &[PathBuf]) -> Result<Vec<PathBuf>, Errors> {
   let mut configured_paths = Vec::new();
   let locale = locale();
 
   for path in paths {
     if path.is_absolute() {
-      let utf8_path = Utf8PathBuf::from_path_buf(path.clean()).map_err(|_| Errors::BadPathEncoding)?;
+      let path = clean_path(path);
 
-      if !utf8_path.starts_with(&locale) {
-        return Err(Errors::UnrecognisedPath(utf8_path));
+      if !path.starts_with(&locale) {
+        return Err(Errors::UnrecognisedPath(path));
       }
 
-      configured_paths.push(utf8_path);
+      configured_paths.push(path);
     } else {
-      let utf8_path = Utf8PathBuf::from_path_buf(locale.as_std_path().join(path).clean()).map_err(|_| Errors::BadPathEncoding)?;
-      configured_paths.push(utf8_path);
+      configured_paths.push(clean_path(&locale.as_std_path().join(path)));
     }
   }
 
   Ok(configured_paths)
 }
 
-pub fn clear_project() -> Result<(), Errors> {
-  for entry in locale().read_dir()? {
+// Resolves `.` and `..` components of an absolute path without reading the file system, keeping
+// names that are not UTF-8.
+fn clean_path(path: &Path) -> PathBuf {
+  let mut cleaned = PathBuf::new();
+
+  for component in path.components() {
+    match component {
+      Component::CurDir => (),
+      Component::ParentDir => {
+        cleaned.pop();
+      },
+      component => cleaned.push(component),
+    }
+  }
+
+  cleaned
+}
+
+// Splits the way a shell does, honoring single and double quotes and backslash escapes.
+fn split_words(text: &str) -> Option<Vec<String>> {
+  let mut words = Vec::new();
+  let mut word: Option<String> = None;
+  let mut quote = None;
+  let mut characters = text.chars();
+
+  while let Some(character) = characters.next() {
+    match (quote, character) {
+      (Some(open), character) if character == open => quote = None,
+      (Some('"') | None, '\\') => word.get_or_insert_with(String::new).push(characters.next()?),
+      (Some(_), character) => word.get_or_insert_with(String::new).push(character),
+      (None, '\'' | '"') => {
+        quote = Some(character);
+        word.get_or_insert_with(String::new);
+      },
+      (None, character) if character.is_whitespace() => words.extend(word.take()),
+      (None, character) => word.get_or_insert_with(String::new).push(character),
+    }
+  }
+
+  if quote.is_some() {
+    return None;
+  }
+
+  words.extend(word);
+  Some(words)
+}
+
+pub fn clear_project(kept: &[PathBuf]) -> Result<(), Errors> {
+  clear_folder(locale().as_std_path(), kept)
+}
+
+// Kept paths are relative to the project, and the folders holding them are cleared around them.
+fn clear_folder(folder: &Path, kept: &[PathBuf]) -> Result<(), Errors> {
+  let folder_name = Layout::get().folder_name();
+
+  for entry in folder.read_dir()? {
     let entry = entry?;
     let path = entry.path();
+    let relative_path = relative(&path);
 
-    if entry.file_name() != REPOSITORY_FOLDER_NAME {
-      if path.is_dir() {
-        fs::remove_dir_all(&path)?;
+    if (folder == locale().as_std_path() && entry.file_name() == folder_name) || kept.contains(&relative_path) {
+      continue;
+    }
+
+    if entry.file_type()?.is_dir() {
+      if kept.iter().any(|kept_path| kept_path.starts_with(&relative_path)) {
+        clear_folder(&path, kept)?;
       } else {
-        fs::remove_file(&path)?;
+        fs::remove_dir_all(&path)?;
       }
+    } else {
+      fs::remove_file(&path)?;
     }
   }
 
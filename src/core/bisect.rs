@@ -0,0 +1,232 @@
+use std::{fs, collections::HashSet};
+use getset::Getters;
+use crate::lib::{errors::{Errors, Context}, locale, layout::Layout, lock::Lock, object::FromId};
+use super::{branch::Branch, commit::Commit, head::{Head, Reference}};
+
+const LOG_COMMAND: &str = "rgit bisect";
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Term {
+  Good,
+  Bad,
+  Skip,
+}
+
+impl Term {
+  pub fn name(&self) -> &'static str {
+    match self {
+      Term::Good => "good",
+      Term::Bad => "bad",
+      Term::Skip => "skip",
+    }
+  }
+
+  // private
+
+  fn parse(name: &str) -> Option<Self> {
+    match name {
+      "good" => Some(Term::Good),
+      "bad" => Some(Term::Bad),
+      "skip" => Some(Term::Skip),
+      _ => None,
+    }
+  }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Step {
+  Waiting { bad: bool, good: bool },
+  Test { commit: Commit, remaining: usize },
+  Found(Commit),
+  OnlySkipped(Vec<Commit>),
+}
+
+// A bisect session keeps the reference HEAD pointed at when it started and a log of the commits
+// marked since, from which the rest of its state is read back.
+#[derive(Getters, Debug, PartialEq)]
+pub struct Bisect {
+  #[getset(get = "pub")]
+  start_head: String,
+
+  #[getset(get = "pub")]
+  bad_id: Option<String>,
+
+  #[getset(get = "pub")]
+  good_ids: Vec<String>,
+
+  #[getset(get = "pub")]
+  skipped_ids: Vec<String>,
+}
+
+impl Bisect {
+  pub fn start() -> Result<Self, Errors> {
+    let layout = Layout::get();
+    let location = locale().join(layout.bisect_start_path());
+
+    if location.exists() {
+      return Err(Errors::ExistingBisect);
+    }
+
+    let start_head = match Head::get()?.reference() {
+      Reference::Branch(branch) => branch.name().clone(),
+      Reference::Commit(commit) => commit.id().clone(),
+    };
+
+    Lock::acquire(locale().join(layout.bisect_log_path()))?.commit(format!("{} start\n", LOG_COMMAND))?;
+    Lock::acquire(&location)?.commit(&start_head)?;
+
+    Ok(
+      Bisect {
+        start_head,
+        bad_id: None,
+        good_ids: Vec::new(),
+        skipped_ids: Vec::new(),
+      }
+    )
+  }
+
+  pub fn get() -> Result<Self, Errors> {
+    let layout = Layout::get();
+    let location = locale().join(layout.bisect_start_path());
+
+    if !location.exists() {
+      return Err(Errors::MissingBisect);
+    }
+
+    let start_head = fs::read_to_string(&location).context(|| format!("unable to read '{}'", location))?;
+    let mut bisect = Bisect {
+      start_head: String::from(start_head.trim()),
+      bad_id: None,
+      good_ids: Vec::new(),
+      skipped_ids: Vec::new(),
+    };
+
+    for line in Self::log()?.lines().filter(|line| !line.starts_with('#')) {
+      if let [_, _, term, id] = line.split_whitespace().collect::<Vec<&str>>()[..] {
+        match Term::parse(term) {
+          Some(term) => bisect.record(term, id),
+          None => continue,
+        }
+      }
+    }
+
+    Ok(bisect)
+  }
+
+  pub fn log() -> Result<String, Errors> {
+    let location = locale().join(Layout::get().bisect_log_path());
+
+    match location.exists() {
+      true => fs::read_to_string(&location).context(|| format!("unable to read '{}'", location)),
+      false => Err(Errors::MissingBisect),
+    }
+  }
+
+  pub fn mark(&mut self, term: Term, commit: &Commit) -> Result<(), Errors> {
+    self.append(&format!("# {}: {}\n{} {} {}\n", term.name(), describe(commit), LOG_COMMAND, term.name(), commit.id()))?;
+    self.record(term, commit.id());
+
+    Ok(())
+  }
+
+  // The commits left to test are those reachable from the bad commit but not from a good one,
+  // following first parents, newest first. The one tested next splits them in half, or is the
+  // closest one to the middle when that one was skipped.
+  pub fn next(&self) -> Result<Step, Errors> {
+    let bad_id = match &self.bad_id {
+      Some(bad_id) if !self.good_ids.is_empty() => bad_id,
+      _ => return Ok(Step::Waiting { bad: self.bad_id.is_some(), good: !self.good_ids.is_empty() }),
+    };
+
+    let mut good_ids = HashSet::new();
+
+    for id in &self.good_ids {
+      let mut current_commit = Some(Commit::from_id(id)?);
+
+      while let Some(commit) = current_commit {
+        if !good_ids.insert(commit.id().clone()) {
+          break;
+        }
+
+        current_commit = commit.parent();
+      }
+    }
+
+    if good_ids.contains(bad_id) {
+      return Err(Errors::BadArguments(format!("the bad commit {} is an ancestor of a good commit", bad_id)));
+    }
+
+    let mut candidates = Vec::new();
+    let mut current_commit = Some(Commit::from_id(bad_id)?);
+
+    while let Some(commit) = current_commit.filter(|commit| !good_ids.contains(commit.id())) {
+      current_commit = commit.parent();
+      candidates.push(commit);
+    }
+
+    let untested = &candidates[1..];
+
+    if untested.is_empty() {
+      return Ok(Step::Found(candidates.remove(0)));
+    }
+
+    let middle = untested.len() / 2;
+    let nearest = (0..untested.len())
+      .flat_map(|distance| [middle.checked_sub(distance), Some(middle + distance)])
+      .flatten()
+      .filter(|&index| index < untested.len())
+      .find(|&index| !self.skipped_ids.contains(untested[index].id()));
+
+    match nearest {
+      Some(index) => Ok(Step::Test { commit: untested[index].clone(), remaining: untested.len() / 2 }),
+      None => Ok(Step::OnlySkipped(candidates)),
+    }
+  }
+
+  pub fn finish(&self, commit: &Commit) -> Result<(), Errors> {
+    self.append(&format!("# first bad commit: {}\n", describe(commit)))
+  }
+
+  // The branch or commit the session started from.
+  pub fn start_reference(&self) -> Result<Reference, Errors> {
+    match Branch::get(&self.start_head) {
+      Ok(branch) => Ok(Reference::Branch(branch)),
+      Err(_) => Ok(Reference::Commit(Commit::from_id(&self.start_head)?)),
+    }
+  }
+
+  // Returns the branch or commit the session started from.
+  pub fn reset(self) -> Result<Reference, Errors> {
+    let layout = Layout::get();
+    let reference = self.start_reference()?;
+
+    Lock::acquire(locale().join(layout.bisect_log_path()))?.remove()?;
+    Lock::acquire(locale().join(layout.bisect_start_path()))?.remove()?;
+
+    Ok(reference)
+  }
+
+  // private
+
+  fn record(&mut self, term: Term, id: &str) {
+    let id = String::from(id);
+
+    match term {
+      Term::Bad => self.bad_id = Some(id),
+      Term::Good => self.good_ids.push(id),
+      Term::Skip => self.skipped_ids.push(id),
+    }
+  }
+
+  fn append(&self, text: &str) -> Result<(), Errors> {
+    let location = locale().join(Layout::get().bisect_log_path());
+    let lock = Lock::acquire(&location)?;
+    let log = fs::read_to_string(&location).context(|| format!("unable to read '{}'", location))?;
+
+    lock.commit(log + text)
+  }
+}
+
+pub fn describe(commit: &Commit) -> String {
+  format!("[{}] {}", commit.id(), commit.message().lines().next().unwrap_or(""))
+}
@@ -1,32 +1,49 @@
-use std::{fs::File, io::Read};
-use camino::Utf8Path;
+use std::{fs::{self, File}, io::Read, path::Path};
 use getset::Getters;
-use crate::lib::{errors::Errors, object::{FromId, Object}, write_object_bytes, locale, constants::OBJECTS_PATH};
+use crate::lib::{errors::{Errors, Context}, object::{FromId, Object, Mode}, write_object_bytes, file_mode, object_location, name_bytes};
 
 #[derive(Getters, Debug, PartialEq, Clone)]
 pub struct Blob {
   #[getset(get = "pub")]
   id: String,
+
+  #[getset(get = "pub")]
+  mode: Mode,
 }
 
 impl Blob {
   pub fn new<B: AsRef<[u8]>>(bytes: B) -> Result<Self, Errors> {
     let id = write_object_bytes(Object::Blob, bytes)?;
-    Ok(Blob { id })
+    Ok(Blob { id, mode: Mode::Regular })
   }
 
-  pub fn from_path<P: AsRef<Utf8Path>>(path: P) -> Result<Self, Errors> {
-    let mut file_bytes = Vec::new();
-    File::open(path.as_ref())?.read_to_end(&mut file_bytes)?;
+  pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, Errors> {
+    let path = path.as_ref();
+    let context = || format!("unable to read '{}'", path.display());
+    let metadata = fs::symlink_metadata(path).context(context)?;
+
+    let (file_bytes, mode) = if metadata.file_type().is_symlink() {
+      (name_bytes(fs::read_link(path).context(context)?), Mode::Symlink)
+    } else {
+      let mut file_bytes = Vec::new();
+      File::open(path).and_then(|mut file| file.read_to_end(&mut file_bytes)).context(context)?;
+      (file_bytes, file_mode(&metadata))
+    };
+
     let id = write_object_bytes(Object::Blob, file_bytes)?;
 
-    Ok(Blob { id })
+    Ok(Blob { id, mode })
+  }
+
+  pub fn with_mode(mut self, mode: Mode) -> Self {
+    self.mode = mode;
+    self
   }
 }
 
 impl FromId for Blob {
   fn from_id(id: &str) -> Result<Blob, Errors> {
-    let location = locale().join(OBJECTS_PATH).join(&id[..2]).join(&id[2..]);
+    let location = object_location(id)?;
 
     if !location.exists() {
       return Err(Errors::UnrecognisedObject(String::from(id)));
@@ -35,6 +52,7 @@ impl FromId for Blob {
     Ok(
       Blob {
         id: String::from(id),
+        mode: Mode::Regular,
       }
     )
   }
@@ -1,7 +1,11 @@
-use std::{fs::{File, OpenOptions, self}, io::{Read, Write}};
+use std::{fs::{self, File}, io::Read, collections::BTreeMap, path::Path};
+use camino::{Utf8Path, Utf8PathBuf};
 use getset::Getters;
-use crate::lib::{errors::Errors, locale, constants::BRANCHES_PATH, object::FromId};
-use super::commit::Commit;
+use walkdir::WalkDir;
+use crate::lib::{errors::{Errors, Context}, locale, layout::Layout, lock::Lock, constants::{GIT_HEAD_REFERENCE_PREFIX, GIT_BRANCH_REFERENCE_PREFIX, GIT_PACKED_REFS_PATH, LOCK_EXTENSION}, object::FromId};
+use super::{commit::Commit, head::{Head, Reference}};
+
+const FORBIDDEN_CHARACTERS: [char; 9] = [' ', '~', '^', ':', '?', '*', '[', '\\', '\x7f'];
 
 #[derive(Getters, Debug, PartialEq, Clone)]
 pub struct Branch {
@@ -14,13 +18,17 @@ pub struct Branch {
 
 impl Branch {
   pub fn new(name: &str, commit_id: Option<&str>) -> Result<Self, Errors> {
-    let location = locale().join(BRANCHES_PATH).join(name);
+    let location = Self::prepare_location(name, None)?;
 
-    if location.exists() {
+    let lock = Lock::acquire(&location)?;
+
+    if location.is_file() || packed_ids()?.contains_key(name) {
       return Err(Errors::ExistingBranch(String::from(name)));
     }
 
-    File::create(location)?.write_all(commit_id.unwrap_or("").as_bytes())?;
+    if let Some(contents) = contents(commit_id) {
+      lock.commit(contents)?;
+    }
 
     Ok(
       Branch {
@@ -31,17 +39,20 @@ impl Branch {
   }
 
   pub fn get(name: &str) -> Result<Self, Errors> {
-    let location = locale().join(BRANCHES_PATH).join(name);
+    let location = Self::location(name)?;
+    let mut commit_id = String::new();
 
-    if !location.exists() {
-      return Err(Errors::UnrecognisedBranch(String::from(name)));
+    if location.exists() {
+      File::open(&location)
+        .and_then(|mut file| file.read_to_string(&mut commit_id))
+        .context(|| format!("unable to read branch '{}'", location))?;
+    } else if let Some(packed_id) = packed_ids()?.remove(name) {
+      commit_id = packed_id;
     }
 
-    let mut commit_id = String::new();
-    File::open(location)?.read_to_string(&mut commit_id)?;
-
+    let commit_id = commit_id.trim();
     let commit_id = if commit_id != "" {
-      Some(commit_id)
+      Some(String::from(commit_id))
     } else {
       None
     };
@@ -55,38 +66,305 @@ impl Branch {
   }
 
   pub fn set(name: &str, commit_id: &str) -> Result<(), Errors> {
-    let location = locale().join(BRANCHES_PATH).join(name);
+    let location = Self::location(name)?;
+    let commit_id = match Layout::get() {
+      Layout::Rgit => String::from(commit_id),
+      Layout::Git => format!("{}\n", commit_id),
+    };
 
-    if !location.exists() {
-      return Err(Errors::UnrecognisedBranch(String::from(name)));
+    Lock::acquire(location)?.commit(commit_id)
+  }
+
+  // Moves the branch only while it still points at `expected_id`, with `None` expecting no commit.
+  pub fn replace(name: &str, commit_id: &str, expected_id: Option<&str>) -> Result<(), Errors> {
+    let layout = Layout::get();
+    let location = Self::prepare_location(name, None)?;
+    let lock = Lock::acquire(&location)?;
+
+    let current_id = match location.is_file() || packed_ids()?.contains_key(name) {
+      true => Branch::get(name)?.commit_id,
+      false => None,
+    };
+
+    if current_id.as_deref() != expected_id {
+      return Err(Errors::StaleReference(String::from(name)));
     }
 
-    OpenOptions::new()
-      .write(true)
-      .truncate(true)
-      .open(location)?
-      .write_all(commit_id.as_bytes())?;
+    match layout {
+      Layout::Rgit => lock.commit(commit_id),
+      Layout::Git => lock.commit(format!("{}\n", commit_id)),
+    }
+  }
 
-    Ok(())
+  pub fn list() -> Result<Vec<Self>, Errors> {
+    let root = locale().join(Layout::get().branches_path());
+    let mut branches = Vec::new();
+
+    for entry in WalkDir::new(&root) {
+      let entry = entry?;
+
+      if !entry.file_type().is_file() {
+        continue;
+      }
+
+      let path = Utf8PathBuf::from_path_buf(entry.into_path()).map_err(|_| Errors::BadPathEncoding)?;
+      let name = path.strip_prefix(&root).map_err(|_| Errors::BadPathEncoding)?.as_str().replace('\\', "/");
+
+      if name.ends_with(&format!(".{}", LOCK_EXTENSION)) {
+        continue;
+      }
+
+      branches.push(Branch::get(&name)?);
+    }
+
+    // Loose branches take precedence over packed ones of the same name, as in Git.
+    for (name, commit_id) in packed_ids()? {
+      if !branches.iter().any(|branch| branch.name == name) {
+        branches.push(Branch { name, commit_id: Some(commit_id) });
+      }
+    }
+
+    branches.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Ok(branches)
   }
 
+  // HEAD would be left pointing at nothing, so the checked out branch is never removed.
   pub fn remove(name: &str) -> Result<(), Errors> {
-    let location = locale().join(BRANCHES_PATH).join(name);
+    let root = locale().join(Layout::get().branches_path());
+    let location = root.join(name);
+
+    if matches!(Head::get()?.reference(), Reference::Branch(branch) if branch.name() == name) {
+      return Err(Errors::CurrentBranch(String::from(name)));
+    }
+
+    let is_packed = packed_ids()?.contains_key(name);
 
-    if !location.exists() {
+    if !is_name(name) || !(location.is_file() || is_packed) {
       return Err(Errors::UnrecognisedBranch(String::from(name)));
     }
 
-    fs::remove_file(location)?;
+    if is_packed {
+      remove_packed(name)?;
+    }
+
+    if location.is_file() {
+      Lock::acquire(&location)?.remove()?;
+    }
+
+    remove_empty_folders(&root, &location);
 
     Ok(())
   }
 
-  pub fn commit(&self) -> Option<Commit> {
-    if let Some(commit_id) = &self.commit_id {
-      Some(Commit::from_id(commit_id).unwrap())
+  pub fn copy(name: &str, new_name: &str) -> Result<Self, Errors> {
+    let branch = Self::get(name)?;
+    Self::new(new_name, branch.commit_id.as_deref())
+  }
+
+  // The source goes before the target is written, which lets `a` become `a/b`, and is written back
+  // when the target or HEAD cannot be. HEAD moves along when the branch is checked out.
+  pub fn rename(name: &str, new_name: &str) -> Result<Self, Errors> {
+    let branch = Self::get(name)?;
+    let root = locale().join(Layout::get().branches_path());
+    let (location, new_location) = (root.join(name), root.join(new_name));
+
+    if name == new_name {
+      return Err(Errors::ExistingBranch(String::from(new_name)));
+    }
+
+    // A target nested under the source can only be locked once the source file is gone.
+    let is_nested = Utf8Path::new(new_name).ancestors().skip(1).any(|ancestor| ancestor == name);
+    let lock = Lock::acquire(&location)?;
+    let new_lock = match is_nested {
+      true => Self::check_location(new_name, Some(name)).map(|_| None)?,
+      false => Some(Lock::acquire(Self::prepare_location(new_name, Some(name))?)?),
+    };
+
+    if new_location.is_file() || packed_ids()?.contains_key(new_name) {
+      return Err(Errors::ExistingBranch(String::from(new_name)));
+    }
+
+    let is_current = matches!(Head::get()?.reference(), Reference::Branch(current) if current.name() == name);
+    let new_branch = Branch { name: String::from(new_name), commit_id: branch.commit_id.clone() };
+
+    let result = (|| {
+      if packed_ids()?.contains_key(name) {
+        remove_packed(name)?;
+      }
+
+      lock.remove()?;
+      remove_empty_folders(&root, &location);
+
+      let new_lock = match new_lock {
+        Some(new_lock) => new_lock,
+        None => Lock::acquire(Self::prepare_location(new_name, None)?)?,
+      };
+
+      if let Some(contents) = contents(new_branch.commit_id.as_deref()) {
+        new_lock.commit(contents)?;
+      }
+
+      match is_current {
+        true => Head::set(&Reference::Branch(new_branch.clone())),
+        false => Ok(()),
+      }
+    })();
+
+    if result.is_err() {
+      if new_location.is_file() {
+        fs::remove_file(&new_location).ok();
+        remove_empty_folders(&root, &new_location);
+      }
+
+      if let (Ok(lock), Some(contents)) = (Self::prepare_location(name, None).and_then(Lock::acquire), contents(branch.commit_id.as_deref())) {
+        lock.commit(contents).ok();
+      }
+    }
+
+    result.map(|_| new_branch)
+  }
+
+  pub fn commit(&self) -> Result<Option<Commit>, Errors> {
+    self.commit_id.as_deref().map(Commit::from_id).transpose()
+  }
+
+  // private
+
+  fn location(name: &str) -> Result<Utf8PathBuf, Errors> {
+    let layout = Layout::get();
+    let location = locale().join(layout.branches_path()).join(name);
+
+    if is_name(name) && (location.is_file() || packed_ids()?.contains_key(name) || (layout == Layout::Git && Self::is_unborn(name)?)) {
+      Ok(location)
     } else {
-      None
+      Err(Errors::UnrecognisedBranch(String::from(name)))
+    }
+  }
+
+  fn prepare_location(name: &str, ignored: Option<&str>) -> Result<Utf8PathBuf, Errors> {
+    let location = Self::check_location(name, ignored)?;
+    let parent = location.parent().unwrap();
+    fs::create_dir_all(parent).context(|| format!("unable to create '{}'", parent))?;
+
+    Ok(location)
+  }
+
+  // Names may hold slashes, which nest the branch in folders, so a branch cannot share its name with
+  // a folder of other branches. The `ignored` branch, locked for a rename, is no conflict.
+  fn check_location(name: &str, ignored: Option<&str>) -> Result<Utf8PathBuf, Errors> {
+    if !is_name(name) {
+      return Err(Errors::BadBranchName(String::from(name)));
+    }
+
+    let root = locale().join(Layout::get().branches_path());
+    let location = root.join(name);
+    let is_ignored = |path: &Path| ignored.is_some_and(|ignored| {
+      path == root.join(ignored) || path == root.join(format!("{}.{}", ignored, LOCK_EXTENSION))
+    });
+    let conflict = Utf8Path::new(name).ancestors()
+      .skip(1)
+      .filter(|ancestor| !ancestor.as_str().is_empty())
+      .find(|ancestor| root.join(ancestor).is_file() && !is_ignored(root.join(ancestor).as_std_path()));
+
+    if let Some(ancestor) = conflict {
+      return Err(Errors::ExistingBranch(String::from(ancestor.as_str())));
+    }
+
+    let is_taken = location.is_dir() && WalkDir::new(&location).into_iter()
+      .flatten()
+      .any(|entry| entry.file_type().is_file() && !is_ignored(entry.path()));
+
+    if is_taken {
+      return Err(Errors::ExistingBranch(format!("{}/", name)));
+    }
+
+    Ok(location)
+  }
+
+  fn is_unborn(name: &str) -> Result<bool, Errors> {
+    let mut reference = String::new();
+    File::open(locale().join(Layout::Git.head_path()))?.read_to_string(&mut reference)?;
+
+    Ok(reference.trim().strip_prefix(GIT_HEAD_REFERENCE_PREFIX) == Some(name))
+  }
+}
+
+// Git has no file for a branch without commits until its first commit is made.
+fn contents(commit_id: Option<&str>) -> Option<String> {
+  match (Layout::get(), commit_id) {
+    (Layout::Git, Some(commit_id)) => Some(format!("{}\n", commit_id)),
+    (Layout::Git, None) => None,
+    (Layout::Rgit, _) => Some(String::from(commit_id.unwrap_or(""))),
+  }
+}
+
+// Folders left empty by the last branch in them go too.
+fn remove_empty_folders(root: &Utf8Path, location: &Utf8Path) {
+  for folder in location.ancestors().skip(1).take_while(|folder| *folder != root) {
+    if fs::remove_dir(folder).is_err() {
+      break;
+    }
+  }
+}
+
+// Follows Git's rules: no `..`, `@{`, control or special characters, and no component starting
+// with `.` or ending with `.lock`.
+fn is_name(name: &str) -> bool {
+  !name.is_empty()
+    && name != "@"
+    && name != "HEAD"
+    && !name.starts_with(['-', '/'])
+    && !name.ends_with(['/', '.'])
+    && !name.contains("..")
+    && !name.contains("//")
+    && !name.contains("@{")
+    && !name.chars().any(|character| character.is_control() || FORBIDDEN_CHARACTERS.contains(&character))
+    && name.split('/').all(|component| !component.starts_with('.') && !component.ends_with(&format!(".{}", LOCK_EXTENSION)))
+}
+
+// `git pack-refs` (run by `git gc`) moves branches into `packed-refs` as `<id> refs/heads/<name>`
+// lines, each optionally followed by a `^<id>` line peeling a tag.
+fn packed_ids() -> Result<BTreeMap<String, String>, Errors> {
+  let location = locale().join(GIT_PACKED_REFS_PATH);
+
+  if Layout::get() != Layout::Git || !location.exists() {
+    return Ok(BTreeMap::new());
+  }
+
+  let text = fs::read_to_string(&location).context(|| format!("unable to read '{}'", location))?;
+  let mut ids = BTreeMap::new();
+
+  for line in text.lines().filter(|line| !line.starts_with(['#', '^'])) {
+    let (id, reference) = line.split_once(' ').ok_or(Errors::BadObjectStructure).context(|| format!("unable to parse '{}'", location))?;
+
+    if let Some(name) = reference.strip_prefix(GIT_BRANCH_REFERENCE_PREFIX) {
+      ids.insert(String::from(name), String::from(id));
+    }
+  }
+
+  Ok(ids)
+}
+
+fn remove_packed(name: &str) -> Result<(), Errors> {
+  let location = locale().join(GIT_PACKED_REFS_PATH);
+  let lock = Lock::acquire(&location)?;
+  let text = fs::read_to_string(&location).context(|| format!("unable to read '{}'", location))?;
+  let reference = format!("{}{}", GIT_BRANCH_REFERENCE_PREFIX, name);
+  let mut kept = String::new();
+  let mut is_removed = false;
+
+  for line in text.lines() {
+    is_removed = match line.split_once(' ') {
+      _ if line.starts_with('^') => is_removed,
+      Some((_, packed_reference)) if !line.starts_with('#') => packed_reference == reference,
+      _ => false,
+    };
+
+    if !is_removed {
+      kept += &format!("{}\n", line);
     }
   }
+
+  lock.commit(kept)
 }
@@ -1,8 +1,10 @@
-use chrono::{DateTime, Utc};
+use chrono::DateTime;
 use getset::Getters;
-use crate::lib::{errors::Errors, user::User, object::{Object, FromId}, write_object_bytes, read_object_bytes, constants::OBJECTS_PATH, locale};
+use crate::lib::{errors::{Errors, Context}, user::User, identity::Identity, signature::{SigningKey, SignatureStatus, verify}, object::{Object, FromId}, layout::Layout, write_object_bytes, read_object_bytes, object_location};
 use super::tree::Tree;
 
+const SIGNATURE_HEADER: &str = "signature";
+
 #[derive(Getters, Debug, PartialEq, Clone)]
 pub struct Commit {
   #[getset(get = "pub")]
@@ -18,42 +20,31 @@ pub struct Commit {
   message: String,
 
   #[getset(get = "pub")]
-  date: DateTime<Utc>,
+  author: Identity,
+
+  #[getset(get = "pub")]
+  committer: Identity,
 
   #[getset(get = "pub")]
-  author: User,
+  signature: Option<String>,
 }
 
 impl Commit {
   pub fn new(parent_id: Option<&str>, tree_id: &str, message: &str) -> Result<Self, Errors> {
-    let tree_location = locale().join(OBJECTS_PATH).join(&tree_id[..2]).join(&tree_id[2..]);
+    Self::create(parent_id, tree_id, message, Identity::author()?, Identity::committer()?, None)
+  }
 
-    if !tree_location.exists() {
-      return Err(Errors::UnrecognisedObject(String::from(tree_id)));
-    }
+  // Commits the change as its author, as when importing history that records a single identity.
+  pub fn with_author(parent_id: Option<&str>, tree_id: &str, message: &str, author: Identity) -> Result<Self, Errors> {
+    Self::create(parent_id, tree_id, message, author.clone(), author, None)
+  }
 
-    let date = Utc::now();
-    let author = User::get()?;
-    let bytes = format!("{}\n{}\n{}\n{}\n{}\n{}",
-      parent_id.unwrap_or("0"),
-      tree_id,
-      message,
-      date.to_rfc3339(),
-      author.username(),
-      author.email(),
-    );
-    let id = write_object_bytes(Object::Commit, bytes)?;
+  pub fn with_identities(parent_id: Option<&str>, tree_id: &str, message: &str, author: Identity, committer: Identity) -> Result<Self, Errors> {
+    Self::create(parent_id, tree_id, message, author, committer, None)
+  }
 
-    Ok(
-      Commit {
-        id,
-        parent_id: parent_id.map(|id| String::from(id)),
-        tree_id: String::from(tree_id),
-        message: String::from(message),
-        date,
-        author: User::get()?,
-      }
-    )
+  pub fn signed(parent_id: Option<&str>, tree_id: &str, message: &str, author: Identity, committer: Identity, key: &SigningKey) -> Result<Self, Errors> {
+    Self::create(parent_id, tree_id, message, author, committer, Some(key))
   }
 
   pub fn tree(&self) -> Result<Tree, Errors> {
@@ -73,26 +64,136 @@ impl Commit {
       None => None,
     }
   }
-}
 
-impl FromId for Commit {
-  fn from_id(id: &str) -> Result<Self, Errors> {
-    let bytes = read_object_bytes(id)?;
-    let text = String::from_utf8(bytes)?;
-
-    if let [
-      parent_id,
-      tree_id,
-      message,
-      date,
-      username,
-      email,
-    ] = text.lines().collect::<Vec<&str>>()[..] {
-      let parent_id = if parent_id == "0" {
-        None
-      } else {
-        Some(String::from(parent_id))
+  // Headers `from_text` does not keep: the parents after the first and Git's `gpgsig`, `mergetag`,
+  // `encoding` and the like.
+  pub fn dropped_headers(&self) -> Result<Vec<String>, Errors> {
+    let text = String::from_utf8(read_object_bytes(&self.id)?)?;
+
+    if !text.starts_with("tree ") {
+      return Ok(Vec::new());
+    }
+
+    let (headers, _) = text.split_once("\n\n").ok_or(Errors::BadObjectStructure)?;
+    let mut has_parent = false;
+
+    Ok(
+      headers.lines()
+        .map(|header| header.split_once(' ').map_or(header, |(name, _)| name))
+        .filter(|name| match *name {
+          "" | "tree" | "author" | "committer" | SIGNATURE_HEADER => false,
+          "parent" if !has_parent => {
+            has_parent = true;
+            false
+          },
+          _ => true,
+        })
+        .map(String::from)
+        .collect()
+    )
+  }
+
+  // The committer signs the commit object, which the signature covers without its `signature` header.
+  pub fn verify(&self) -> Result<SignatureStatus, Errors> {
+    let signature = match &self.signature {
+      Some(signature) => signature,
+      None => return Ok(SignatureStatus::Unsigned),
+    };
+
+    let text = String::from_utf8(read_object_bytes(&self.id)?)?;
+    let (headers, message) = text.split_once("\n\n").ok_or(Errors::BadObjectStructure)?;
+    let headers = headers.lines()
+      .filter(|header| header.split_once(' ').map(|(name, _)| name) != Some(SIGNATURE_HEADER))
+      .map(|header| format!("{}\n", header))
+      .collect::<String>();
+
+    verify(format!("{}\n{}", headers, message), signature, self.committer.email())
+  }
+
+  // private
+
+  fn create(parent_id: Option<&str>, tree_id: &str, message: &str, author: Identity, committer: Identity, key: Option<&SigningKey>) -> Result<Self, Errors> {
+    let tree_location = object_location(tree_id)?;
+
+    if !tree_location.exists() {
+      return Err(Errors::UnrecognisedObject(String::from(tree_id)));
+    }
+
+    let layout = Layout::get();
+    let (author, committer) = match layout {
+      Layout::Rgit => (author, committer),
+      Layout::Git => (author.with_whole_seconds(), committer.with_whole_seconds()),
+    };
+    let parent = match parent_id {
+      Some(parent_id) => format!("parent {}\n", parent_id),
+      None => String::new(),
+    };
+    let headers = format!("tree {}\n{}author {}\ncommitter {}\n", tree_id, parent, author.format(layout), committer.format(layout));
+    let signature = key.map(|key| key.sign(format!("{}\n{}\n", headers, message)));
+    let bytes = match &signature {
+      Some(signature) => format!("{}{} {}\n\n{}\n", headers, SIGNATURE_HEADER, signature, message),
+      None => format!("{}\n{}\n", headers, message),
+    };
+    let id = write_object_bytes(Object::Commit, bytes)?;
+
+    Ok(
+      Commit {
+        id,
+        parent_id: parent_id.map(|id| String::from(id)),
+        tree_id: String::from(tree_id),
+        message: String::from(message),
+        author,
+        committer,
+        signature,
+      }
+    )
+  }
+
+  // Commits read `<header> <value>` lines, a blank line and the message, as in Git. Git commits may
+  // have several parents; only the first one is followed.
+  fn from_text(id: &str, text: &str) -> Result<Self, Errors> {
+    let (headers, message) = text.split_once("\n\n").ok_or(Errors::BadObjectStructure)?;
+    let mut parent_id = None;
+    let mut tree_id = None;
+    let mut author = None;
+    let mut committer = None;
+    let mut signature = None;
+
+    for header in headers.lines() {
+      match header.split_once(' ') {
+        Some(("tree", id)) => tree_id = Some(String::from(id)),
+        Some(("parent", id)) if parent_id.is_none() => parent_id = Some(String::from(id)),
+        Some(("author", identity)) => author = Some(Identity::parse(identity)?),
+        Some(("committer", identity)) => committer = Some(Identity::parse(identity)?),
+        Some((SIGNATURE_HEADER, value)) => signature = Some(String::from(value)),
+        _ => continue,
+      }
+    }
+
+    let committer = committer.ok_or(Errors::BadObjectStructure)?;
+
+    Ok(
+      Commit {
+        id: String::from(id),
+        parent_id,
+        tree_id: tree_id.ok_or(Errors::BadObjectStructure)?,
+        message: String::from(message.strip_suffix('\n').unwrap_or(message)),
+        author: author.unwrap_or_else(|| committer.clone()),
+        committer,
+        signature,
+      }
+    )
+  }
+
+  // Commits written before headers hold six lines: the parent id (`0` for none), the tree id, the
+  // message, the date and the author's username and email.
+  fn from_legacy_text(id: &str, text: &str) -> Result<Self, Errors> {
+    if let [parent_id, tree_id, message, date, username, email] = text.lines().collect::<Vec<&str>>()[..] {
+      let parent_id = match parent_id {
+        "0" => None,
+        parent_id => Some(String::from(parent_id)),
       };
+      let author = Identity::new(&User::new(username, email)?, DateTime::parse_from_rfc3339(date)?);
 
       Ok(
         Commit {
@@ -100,8 +201,9 @@ impl FromId for Commit {
           parent_id,
           tree_id: String::from(tree_id),
           message: String::from(message),
-          date: DateTime::parse_from_rfc3339(date)?.with_timezone(&Utc),
-          author: User::new(username, email)?,
+          author: author.clone(),
+          committer: author,
+          signature: None,
         }
       )
     } else {
@@ -109,3 +211,17 @@ impl FromId for Commit {
     }
   }
 }
+
+impl FromId for Commit {
+  fn from_id(id: &str) -> Result<Self, Errors> {
+    let bytes = read_object_bytes(id)?;
+    let context = || format!("unable to parse commit '{}'", id);
+    let text = String::from_utf8(bytes).context(context)?;
+
+    if Layout::get() == Layout::Git || text.starts_with("tree ") {
+      return Commit::from_text(id, &text).context(context);
+    }
+
+    Commit::from_legacy_text(id, &text).context(context)
+  }
+}
@@ -1,8 +1,10 @@
-use std::{fs::{File, OpenOptions}, io::{Read, Write}};
+use std::{fs::File, io::Read};
 use getset::Getters;
-use crate::lib::{errors::Errors, locale, constants::HEAD_PATH, object::FromId};
+use crate::lib::{errors::{Errors, Context}, locale, layout::Layout, lock::Lock, constants::GIT_HEAD_REFERENCE_PREFIX, object::FromId};
 use super::{branch::Branch, commit::Commit};
 
+const HEAD_NAME: &str = "HEAD";
+
 #[derive(Debug, PartialEq)]
 pub enum Reference {
   Branch(Branch),
@@ -17,14 +19,30 @@ pub struct Head {
 
 impl Head {
   pub fn get() -> Result<Self, Errors> {
-    let location = locale().join(HEAD_PATH);
+    let layout = Layout::get();
+    let location = locale().join(layout.head_path());
     let mut reference = String::new();
-    File::open(location)?.read_to_string(&mut reference)?;
+    File::open(&location)
+      .and_then(|mut file| file.read_to_string(&mut reference))
+      .context(|| format!("unable to read HEAD '{}'", location))?;
+
+    let reference = match layout {
+      Layout::Rgit => {
+        if reference.starts_with("detached:") {
+          Reference::Commit(Commit::from_id(&reference[9..])?)
+        } else {
+          Reference::Branch(Branch::get(&reference)?)
+        }
+      },
+      Layout::Git => {
+        let reference = reference.trim();
 
-    let reference = if reference.starts_with("detached:") {
-      Reference::Commit(Commit::from_id(&reference[9..])?)
-    } else {
-      Reference::Branch(Branch::get(&reference)?)
+        if let Some(name) = reference.strip_prefix(GIT_HEAD_REFERENCE_PREFIX) {
+          Reference::Branch(Branch::get(name)?)
+        } else {
+          Reference::Commit(Commit::from_id(reference)?)
+        }
+      },
     };
 
     Ok(
@@ -35,25 +53,33 @@ impl Head {
   }
 
   pub fn set(reference: &Reference) -> Result<(), Errors> {
-    let location = locale().join(HEAD_PATH);
-    let reference = match &reference {
-      Reference::Branch(branch) => String::from(branch.name()),
-      Reference::Commit(commit) => String::from("detached:") + commit.id(),
-    };
+    Lock::acquire(locale().join(Layout::get().head_path()))?.commit(contents(reference))
+  }
 
-    OpenOptions::new()
-      .write(true)
-      .truncate(true)
-      .open(location)?
-      .write_all(reference.as_bytes())?;
+  // Moves a detached HEAD only while it still points at `expected_id`, with `None` expecting no
+  // commit, which a detached HEAD always has.
+  pub fn replace(commit: Commit, expected_id: Option<&str>) -> Result<(), Errors> {
+    let lock = Lock::acquire(locale().join(Layout::get().head_path()))?;
 
-    Ok(())
+    match Self::get()?.reference {
+      Reference::Commit(current) if Some(current.id().as_str()) == expected_id => lock.commit(contents(&Reference::Commit(commit))),
+      _ => Err(Errors::StaleReference(String::from(HEAD_NAME))),
+    }
   }
 
-  pub fn commit(&self) -> Option<Commit> {
+  pub fn commit(&self) -> Result<Option<Commit>, Errors> {
     match &self.reference {
       Reference::Branch(branch) => branch.commit(),
-      Reference::Commit(commit) => Some(commit.clone()),
+      Reference::Commit(commit) => Ok(Some(commit.clone())),
     }
   }
 }
+
+fn contents(reference: &Reference) -> String {
+  match (Layout::get(), reference) {
+    (Layout::Rgit, Reference::Branch(branch)) => String::from(branch.name()),
+    (Layout::Rgit, Reference::Commit(commit)) => String::from("detached:") + commit.id(),
+    (Layout::Git, Reference::Branch(branch)) => format!("{}{}\n", GIT_HEAD_REFERENCE_PREFIX, branch.name()),
+    (Layout::Git, Reference::Commit(commit)) => format!("{}\n", commit.id()),
+  }
+}
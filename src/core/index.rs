@@ -1,62 +1,99 @@
-use std::{collections::HashMap, io::{Read, Write}, fs::{File, OpenOptions}, str::FromStr};
-use camino::{Utf8Path, Utf8PathBuf};
+use std::{collections::HashMap, io::Read, fs::{self, File}, path::{Path, PathBuf}};
 use getset::Getters;
-use crate::lib::{*, errors::Errors, constants::{INDEX_PATH, DELETED_INDEX_STAGE}};
+use crate::lib::{*, errors::{Errors, Context}, layout::Layout, lock::Lock, format::ObjectFormat, constants::{DELETED_INDEX_STAGE, DELETED_INDEX_MODE, GITLINK_MODE, GIT_INDEX_PATH}, object::{FromId, Mode, Entry, pack_entries, unpack_entries, pack_git_index, unpack_git_index, is_packed_entries}};
 use super::{tree::{Tree, Node}, head::Head, blob::Blob};
 
+#[derive(Debug, PartialEq, Clone)]
+pub enum Stage {
+  Blob(Blob),
+  Deleted,
+}
+
 #[derive(Getters)]
 pub struct Index {
   #[getset(get = "pub")]
-  staged_paths: HashMap<Utf8PathBuf, String>
+  staged_paths: HashMap<PathBuf, Stage>,
+
+  // Submodule commits Git's index holds differently from HEAD, none standing for a removed one.
+  gitlinks: HashMap<PathBuf, Option<String>>,
 }
 
 impl Index {
   pub fn get() -> Result<Self, Errors> {
-    let location = locale().join(INDEX_PATH);
+    if Layout::get() == Layout::Git {
+      return Self::from_git_index();
+    }
+
+    let location = locale().join(Layout::get().index_path());
+    let context = || format!("unable to read index '{}'", location);
     let mut compressed_data = Vec::new();
 
-    File::open(location)?.read_to_end(&mut compressed_data)?;
-    let text = if !compressed_data.is_empty() {
-      String::from_utf8(decompress(compressed_data)?)?
+    File::open(&location).and_then(|mut file| file.read_to_end(&mut compressed_data)).context(context)?;
+    let data = if !compressed_data.is_empty() {
+      decompress(compressed_data).context(context)?
+    } else {
+      Vec::new()
+    };
+
+    let entries = if is_packed_entries(&data) {
+      unpack_entries(&data).context(context)?
     } else {
-      String::new()
+      unpack_legacy_entries(&String::from_utf8(data).context(context)?)
     };
 
     let mut staged_paths = HashMap::new();
-    for line in text.lines() {
-      if let [path, stage] = line.split_whitespace().collect::<Vec<&str>>()[..] {
-        staged_paths.insert(Utf8PathBuf::from_str(path).unwrap(), String::from(stage));
-      }
+    for entry in entries {
+      let stage = if entry.id == DELETED_INDEX_STAGE {
+        Stage::Deleted
+      } else {
+        Stage::Blob(Blob::from_id(&entry.id)?.with_mode(Mode::parse(&entry.mode).context(context)?))
+      };
+
+      staged_paths.insert(PathBuf::from(name_from_bytes(&entry.name)), stage);
     }
 
-    Ok(Index { staged_paths })
+    Ok(Index { staged_paths, gitlinks: HashMap::new() })
   }
 
-  pub fn add<P: AsRef<Utf8Path>>(paths: &[P]) -> Result<(), Errors> {
+  pub fn add<P: AsRef<Path>>(paths: &[P]) -> Result<(), Errors> {
+    let lock = Self::lock()?;
     let mut index = Self::get()?;
-    let tree = match Head::get()?.commit() {
-      Some(commit) => commit.tree()?,
-      None => Tree::new(),
-    };
 
-    for path in paths {
-      let relative_path = relative(path);
+    index.stage_paths(paths, &head_tree()?)?;
+    index.save(lock)
+  }
 
-      if relative_path == "" {
-        index.stage_folder(path.as_ref(), &tree)?;
-      } else {
-        match tree.get(relative(path)) {
-          Some(Node::Blob(blob)) => index.stage_file(path.as_ref(), blob),
-          Some(Node::Tree(tree)) => index.stage_folder(path.as_ref(), tree),
-          None => index.stage_untracked(path.as_ref()),
-        }?;
-      }
+  // Stages the changes to every file in the HEAD tree, leaving untracked files alone.
+  pub fn add_tracked() -> Result<(), Errors> {
+    let lock = Self::lock()?;
+    let mut index = Self::get()?;
+    let locale = locale();
+
+    for (path, blob) in head_tree()?.blobs() {
+      index.stage_file(&locale.as_std_path().join(path), blob)?;
     }
 
-    index.save()
+    index.save(lock)
+  }
+
+  // Stages the given paths in an index of their own, which is never saved.
+  pub fn from_paths<P: AsRef<Path>>(paths: &[P]) -> Result<Self, Errors> {
+    let mut index = Index { staged_paths: HashMap::new(), gitlinks: HashMap::new() };
+
+    index.stage_paths(paths, &head_tree()?)?;
+    Ok(index)
   }
 
-  pub fn remove<P: AsRef<Utf8Path>>(paths: &[P]) -> Result<(), Errors> {
+  pub fn add_blob<P: AsRef<Path>>(path: P, blob: Blob) -> Result<(), Errors> {
+    let lock = Self::lock()?;
+    let mut index = Self::get()?;
+
+    index.staged_paths.insert(relative(path), Stage::Blob(blob));
+    index.save(lock)
+  }
+
+  pub fn remove<P: AsRef<Path>>(paths: &[P]) -> Result<(), Errors> {
+    let lock = Self::lock()?;
     let mut index = Self::get()?;
     let staged_paths = index.staged_paths.clone();
 
@@ -68,77 +105,190 @@ impl Index {
       }
     }
 
-    index.save()
+    index.save(lock)
+  }
+
+  // Rewrites the staged blobs, then runs `update` before saving, so the index is saved against the
+  // HEAD and object format `update` leaves behind.
+  pub fn rewrite<F: FnMut(&Blob) -> Result<Blob, Errors>, G: FnOnce() -> Result<(), Errors>>(mut rewrite: F, update: G) -> Result<(), Errors> {
+    let lock = Self::lock()?;
+    let mut index = Self::get()?;
+
+    for stage in index.staged_paths.values_mut() {
+      if let Stage::Blob(blob) = stage {
+        *blob = rewrite(blob)?;
+      }
+    }
+
+    update()?;
+    index.save(lock)
   }
 
   pub fn clear() -> Result<(), Errors> {
-    let location = locale().join(INDEX_PATH);
-    File::create(location)?.set_len(0)?;
+    let lock = Self::lock()?;
+    let gitlinks = Self::get()?.gitlinks;
 
-    Ok(())
+    Index { staged_paths: HashMap::new(), gitlinks }.save(lock)
+  }
+
+  // Keeps the staged changes staged while `update` moves HEAD. Git's index lists whole trees rather
+  // than changes, so it is written again on top of the new HEAD.
+  pub fn keep_staged<T, F: FnOnce() -> Result<T, Errors>>(update: F) -> Result<T, Errors> {
+    let lock = Self::lock()?;
+    let index = Self::get()?;
+    let result = update()?;
+
+    index.save(lock)?;
+    Ok(result)
   }
 
   // private
 
-  fn save(&self) -> Result<(), Errors> {
-    let location = locale().join(INDEX_PATH);
-    let mut data = String::new();
+  // Git's index lists every tracked file, so the staged paths are those it holds differently from
+  // HEAD. Repositories rgit created before it wrote Git's index have none, which stands for HEAD.
+  fn from_git_index() -> Result<Self, Errors> {
+    let location = locale().join(GIT_INDEX_PATH);
+    let context = || format!("unable to read index '{}'", location);
+    let mut staged_paths = HashMap::new();
+    let mut gitlinks = HashMap::new();
+
+    if !location.exists() {
+      return Ok(Index { staged_paths, gitlinks });
+    }
+
+    let bytes = fs::read(&location).context(context)?;
+    let mut head_entries = head_entries()?;
+
+    for entry in unpack_git_index(&bytes, ObjectFormat::get()?).context(context)? {
+      let path = PathBuf::from(name_from_bytes(&entry.name));
+
+      if head_entries.remove(&path).as_ref() == Some(&(entry.mode.clone(), entry.id.clone())) {
+        continue;
+      }
 
-    for (relative_path, id) in &self.staged_paths {
-      data += &format!("{} {}\n", relative_path, id);
+      if entry.mode == GITLINK_MODE {
+        gitlinks.insert(path, Some(entry.id));
+      } else {
+        staged_paths.insert(path, Stage::Blob(Blob::from_id(&entry.id)?.with_mode(Mode::parse(&entry.mode).context(context)?)));
+      }
     }
 
-    let compressed_data = if data != "" {
-      compress(data.trim_end().as_bytes())?
+    for (path, (mode, _)) in head_entries {
+      if mode == GITLINK_MODE {
+        gitlinks.insert(path, None);
+      } else {
+        staged_paths.insert(path, Stage::Deleted);
+      }
+    }
+
+    Ok(Index { staged_paths, gitlinks })
+  }
+
+  fn lock() -> Result<Lock, Errors> {
+    Lock::acquire(locale().join(Layout::get().index_path()))
+  }
+
+  fn save(&self, lock: Lock) -> Result<(), Errors> {
+    if Layout::get() == Layout::Git {
+      return self.save_git_index(lock);
+    }
+
+    let mut entries = Vec::new();
+
+    for (relative_path, stage) in &self.staged_paths {
+      entries.push(match stage {
+        Stage::Blob(blob) => Entry::new(blob.mode().as_str(), name_bytes(relative_path), blob.id()),
+        Stage::Deleted => Entry::new(DELETED_INDEX_MODE, name_bytes(relative_path), DELETED_INDEX_STAGE),
+      });
+    }
+
+    let compressed_data = if !entries.is_empty() {
+      compress(pack_entries(&mut entries))?
     } else {
       Vec::new()
     };
 
-    OpenOptions::new()
-      .write(true)
-      .truncate(true)
-      .create(true)
-      .open(location)?
-      .write_all(&compressed_data)?;
+    lock.commit(compressed_data)
+  }
+
+  fn save_git_index(&self, lock: Lock) -> Result<(), Errors> {
+    let mut head_entries = head_entries()?;
+
+    for (path, stage) in &self.staged_paths {
+      match stage {
+        Stage::Blob(blob) => head_entries.insert(path.clone(), (String::from(blob.mode().as_str()), blob.id().clone())),
+        Stage::Deleted => head_entries.remove(path),
+      };
+    }
+
+    for (path, id) in &self.gitlinks {
+      match id {
+        Some(id) => head_entries.insert(path.clone(), (String::from(GITLINK_MODE), id.clone())),
+        None => head_entries.remove(path),
+      };
+    }
+
+    let mut entries = head_entries.into_iter()
+      .map(|(path, (mode, id))| Entry::new(&mode, name_bytes(path), &id))
+      .collect::<Vec<Entry>>();
+
+    lock.commit(pack_git_index(&mut entries, ObjectFormat::get()?)?)
+  }
+
+  fn stage_paths<P: AsRef<Path>>(&mut self, paths: &[P], tree: &Tree) -> Result<(), Errors> {
+    for path in paths {
+      let relative_path = relative(path);
+
+      if relative_path.as_os_str().is_empty() {
+        self.stage_folder(path.as_ref(), tree)?;
+      } else {
+        match tree.get(relative_path) {
+          Some(Node::Blob(blob)) => self.stage_file(path.as_ref(), blob),
+          Some(Node::Tree(tree)) => self.stage_folder(path.as_ref(), tree),
+          Some(Node::Gitlink(_)) => Ok(()),
+          None => self.stage_untracked(path.as_ref()),
+        }?;
+      }
+    }
 
     Ok(())
   }
 
-  fn stage_untracked(&mut self, path: &Utf8Path) -> Result<(), Errors> {
-    if !path.exists() {
-      return Err(Errors::UnrecognisedPath(Utf8PathBuf::from(path)));
+  fn stage_untracked(&mut self, path: &Path) -> Result<(), Errors> {
+    if !path_exists(path) {
+      return Err(Errors::UnrecognisedPath(PathBuf::from(path)));
     }
 
-    if path.is_dir() {
+    if is_folder(path) {
       self.insert_folder(path)
     } else {
       self.insert_file(path)
     }
   }
 
-  fn stage_file(&mut self, path: &Utf8Path, blob: &Blob) -> Result<(), Errors> {
-    if !path.exists() {
+  fn stage_file(&mut self, path: &Path, blob: &Blob) -> Result<(), Errors> {
+    if !path_exists(path) {
       self.insert_deleted(path);
       return Ok(());
     }
 
-    if path.is_dir() {
+    if is_folder(path) {
       self.insert_folder(path)?;
     } else {
       let blobified_file = Blob::from_path(path)?;
 
       if blob != &blobified_file {
-        self.staged_paths.insert(relative(path), String::from(blobified_file.id()));
+        self.staged_paths.insert(relative(path), Stage::Blob(blobified_file));
       }
     }
 
     Ok(())
   }
 
-  fn stage_folder(&mut self, path: &Utf8Path, tree: &Tree) -> Result<(), Errors> {
+  fn stage_folder(&mut self, path: &Path, tree: &Tree) -> Result<(), Errors> {
     let blobs = tree.blobs();
 
-    if !path.exists() {
+    if !path_exists(path) {
       for (blob_path, _) in &blobs {
         self.insert_deleted(&path.join(blob_path));
       }
@@ -146,7 +296,7 @@ impl Index {
       return Ok(());
     }
 
-    if path.is_dir() {
+    if is_folder(path) {
       let file_paths = folder_files(path)?;
 
       for (blob_path, _) in &blobs {
@@ -166,7 +316,7 @@ impl Index {
             let blobified_file = Blob::from_path(&file_path)?;
 
             if blob != &&blobified_file {
-              self.staged_paths.insert(relative_file_path, String::from(blobified_file.id()));
+              self.staged_paths.insert(relative_file_path, Stage::Blob(blobified_file));
             }
           },
           None => {
@@ -181,7 +331,7 @@ impl Index {
     Ok(())
   }
 
-  fn insert_folder(&mut self, path: &Utf8Path) -> Result<(), Errors> {
+  fn insert_folder(&mut self, path: &Path) -> Result<(), Errors> {
     let file_paths = folder_files(path)?;
 
     for file_path in file_paths {
@@ -191,14 +341,58 @@ impl Index {
     Ok(())
   }
 
-  fn insert_file(&mut self, path: &Utf8Path) -> Result<(), Errors> {
+  fn insert_file(&mut self, path: &Path) -> Result<(), Errors> {
     let blob = Blob::from_path(path)?;
-    self.staged_paths.insert(relative(path), String::from(blob.id()));
+    self.staged_paths.insert(relative(path), Stage::Blob(blob));
 
     Ok(())
   }
 
-  fn insert_deleted(&mut self, path: &Utf8Path) {
-    self.staged_paths.insert(relative(path), String::from(DELETED_INDEX_STAGE));
+  fn insert_deleted(&mut self, path: &Path) {
+    self.staged_paths.insert(relative(path), Stage::Deleted);
   }
 }
+
+fn head_tree() -> Result<Tree, Errors> {
+  match Head::get()?.commit()? {
+    Some(commit) => commit.tree(),
+    None => Ok(Tree::new()),
+  }
+}
+
+// The mode and id of every file and submodule commit in the HEAD tree.
+fn head_entries() -> Result<HashMap<PathBuf, (String, String)>, Errors> {
+  let head_tree = head_tree()?;
+  let mut entries = HashMap::new();
+  let mut trees = vec![(PathBuf::new(), &head_tree)];
+
+  while let Some((prefix, tree)) = trees.pop() {
+    for (name, node) in tree.children() {
+      let path = prefix.join(name);
+
+      match node {
+        Node::Tree(tree) => trees.push((path, tree)),
+        Node::Blob(blob) => { entries.insert(path, (String::from(blob.mode().as_str()), blob.id().clone())); },
+        Node::Gitlink(id) => { entries.insert(path, (String::from(GITLINK_MODE), id.clone())); },
+      }
+    }
+  }
+
+  Ok(entries)
+}
+
+// Indexes written before entries were NUL-delimited hold one `<path> <stage> [<mode>]` line per entry.
+fn unpack_legacy_entries(text: &str) -> Vec<Entry> {
+  let mut entries = Vec::new();
+
+  for line in text.lines() {
+    match line.split_whitespace().collect::<Vec<&str>>()[..] {
+      [path, DELETED_INDEX_STAGE] => entries.push(Entry::new(DELETED_INDEX_MODE, path, DELETED_INDEX_STAGE)),
+      [path, id] => entries.push(Entry::new(Mode::Regular.as_str(), path, id)),
+      [path, id, mode] => entries.push(Entry::new(mode, path, id)),
+      _ => continue,
+    }
+  }
+
+  entries
+}
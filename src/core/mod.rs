@@ -4,3 +4,5 @@ pub mod commit;
 pub mod branch;
 pub mod head;
 pub mod index;
+pub mod revision;
+pub mod bisect;
@@ -0,0 +1,128 @@
+use std::path::PathBuf;
+use crate::lib::{errors::Errors, layout::Layout, format::ObjectFormat, object::FromId, locale, object_location};
+use super::{head::Head, branch::Branch, commit::Commit, tree::Node};
+
+const HEAD_REVISIONS: [&str; 2] = ["HEAD", "@"];
+const MINIMUM_SHORT_ID_LENGTH: usize = 4;
+
+// Names a commit the way Git revisions do: `HEAD` (or `@`), a branch name, a full or abbreviated
+// commit id, each optionally followed by `~<n>` or `^` steps to ancestors.
+pub struct Revision;
+
+impl Revision {
+  pub fn resolve(revision: &str) -> Result<Commit, Errors> {
+    let (base, steps) = match revision.find(['~', '^']) {
+      Some(position) => revision.split_at(position),
+      None => (revision, ""),
+    };
+
+    let mut commit = Self::resolve_base(base, revision)?;
+    let mut steps = steps.chars().peekable();
+
+    while let Some(step) = steps.next() {
+      let mut digits = String::new();
+
+      while let Some(digit) = steps.next_if(|character| character.is_ascii_digit()) {
+        digits.push(digit);
+      }
+
+      let count = match digits.as_str() {
+        "" => 1,
+        digits => digits.parse::<usize>().map_err(|_| Errors::UnrecognisedRevision(String::from(revision)))?,
+      };
+
+      let generations = match (step, count) {
+        ('~', count) => count,
+        ('^', 0) => 0,
+        ('^', 1) => 1,
+        _ => return Err(Errors::UnrecognisedRevision(String::from(revision))),
+      };
+
+      for _ in 0..generations {
+        commit = commit.parent().ok_or_else(|| Errors::UnrecognisedRevision(String::from(revision)))?;
+      }
+    }
+
+    Ok(commit)
+  }
+
+  // Also accepts `<revision>:<path>` for the file or folder at a path and ids of any object.
+  pub fn resolve_object(name: &str) -> Result<String, Errors> {
+    if let Some((revision, path)) = name.split_once(':') {
+      let commit = Self::resolve(revision)?;
+      let path = path.trim_matches('/');
+
+      if path.is_empty() {
+        return Ok(commit.tree_id().clone());
+      }
+
+      return match commit.tree()?.get(path) {
+        Some(Node::Tree(tree)) => Ok(tree.id().clone()),
+        Some(Node::Blob(blob)) => Ok(blob.id().clone()),
+        Some(Node::Gitlink(id)) => Ok(id.clone()),
+        None => Err(Errors::UnrecognisedPath(PathBuf::from(path))),
+      };
+    }
+
+    match Self::resolve(name) {
+      Ok(commit) => Ok(commit.id().clone()),
+      Err(Errors::UnrecognisedRevision(_)) => Self::resolve_id(name),
+      Err(error) => Err(error),
+    }
+  }
+
+  pub fn resolve_id(id: &str) -> Result<String, Errors> {
+    if ObjectFormat::is_id(id) {
+      return match object_location(id)?.exists() {
+        true => Ok(String::from(id)),
+        false => Err(Errors::UnrecognisedObject(String::from(id))),
+      };
+    }
+
+    if id.len() < MINIMUM_SHORT_ID_LENGTH || !id.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+      return Err(Errors::UnrecognisedObject(String::from(id)));
+    }
+
+    let id = id.to_lowercase();
+    let folder = locale().join(Layout::get().objects_path()).join(&id[..2]);
+    let mut matches = Vec::new();
+
+    if folder.exists() {
+      for entry in folder.read_dir()? {
+        let file_name = entry?.file_name();
+        let file_name = file_name.to_str().ok_or(Errors::BadPathEncoding)?;
+        let full_id = format!("{}{}", &id[..2], file_name);
+
+        if full_id.starts_with(&id) && ObjectFormat::is_id(&full_id) {
+          matches.push(full_id);
+        }
+      }
+    }
+
+    match matches.len() {
+      0 => Err(Errors::UnrecognisedObject(id)),
+      1 => Ok(matches.remove(0)),
+      _ => Err(Errors::AmbiguousObject(id)),
+    }
+  }
+
+  // private
+
+  fn resolve_base(base: &str, revision: &str) -> Result<Commit, Errors> {
+    if HEAD_REVISIONS.contains(&base) {
+      return Head::get()?.commit()?.ok_or_else(|| Errors::UnrecognisedRevision(String::from(revision)));
+    }
+
+    match Branch::get(base) {
+      Ok(branch) => return branch.commit()?.ok_or_else(|| Errors::UnrecognisedRevision(String::from(revision))),
+      Err(Errors::UnrecognisedBranch(_)) => (),
+      Err(error) => return Err(error),
+    }
+
+    match Self::resolve_id(base) {
+      Ok(id) => Commit::from_id(&id).map_err(|_| Errors::UnrecognisedRevision(String::from(revision))),
+      Err(Errors::UnrecognisedObject(_)) => Err(Errors::UnrecognisedRevision(String::from(revision))),
+      Err(error) => Err(error),
+    }
+  }
+}
@@ -1,20 +1,21 @@
-use std::{collections::{HashMap, VecDeque}, fs::{self, File}, io::Write};
-use camino::{Utf8Path, Utf8PathBuf};
+use std::{collections::{HashMap, VecDeque}, ffi::OsString, fs, path::{Path, PathBuf}};
 use getset::Getters;
-use crate::lib::{object::{Object, FromId}, errors::Errors, read_object_bytes, constants::{BLOB_TYPE, TREE_TYPE}, write_object_bytes};
+use crate::lib::{object::{Object, FromId, Mode, Entry, pack_entries, unpack_entries, pack_git_entries, unpack_git_entries, is_packed_entries}, layout::Layout, format::ObjectFormat, errors::{Errors, Context}, read_object_bytes, constants::{BLOB_TYPE, TREE_TYPE, TREE_MODE, GITLINK_MODE, REPOSITORY_FOLDER_NAME, GIT_REPOSITORY_FOLDER_NAME}, write_object_bytes, write_file, name_bytes, name_from_bytes};
 use super::blob::Blob;
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum Node {
   Tree(Tree),
   Blob(Blob),
+  // A submodule's commit, which lives in another repository, so it is kept but never checked out.
+  Gitlink(String),
 }
 
 impl PartialEq<Blob> for Node {
   fn eq(&self, other: &Blob) -> bool {
     match self {
-      Self::Tree(_) => false,
       Self::Blob(blob) => blob == other,
+      _ => false,
     }
   }
 }
@@ -23,7 +24,7 @@ impl PartialEq<Tree> for Node {
   fn eq(&self, other: &Tree) -> bool {
     match self {
       Self::Tree(tree) => tree == other,
-      Self::Blob(_) => false,
+      _ => false,
     }
   }
 }
@@ -50,7 +51,7 @@ pub struct Tree {
   id: String,
 
   #[getset(get = "pub")]
-  children: HashMap<String, Node>,
+  children: HashMap<OsString, Node>,
 }
 
 impl Tree {
@@ -61,93 +62,131 @@ impl Tree {
     }
   }
 
-  pub fn insert<P: AsRef<Utf8Path>>(&mut self, path: P, node: Node) {
+  pub fn insert<P: AsRef<Path>>(&mut self, path: P, node: Node) -> Result<(), Errors> {
     let path = path.as_ref();
 
-    if !path.as_str().contains(std::path::is_separator) {
-      self.children.insert(path.to_string(), node);
-      return;
+    if path.as_os_str().is_empty() || !path.iter().all(|name| is_valid_name(&name_bytes(name))) {
+      return Err(Errors::BadObjectStructure).context(|| format!("invalid path '{}'", path.display()));
     }
 
-    let mut it = path.iter();
-    let name = it.next().unwrap();
-
-    if let Some(Node::Tree(tree)) = self.children.get_mut(name) {
-      tree.insert(it.as_path(), node);
-    } else {
-      let mut tree = Tree::new();
-      tree.insert(it.as_path(), node);
-      self.children.insert(String::from(name), Node::Tree(tree));
-    }
+    self.insert_node(path, node);
+    Ok(())
   }
 
-  pub fn remove<P: AsRef<Utf8Path>>(&mut self, path: P) {
-    let path = path.as_ref();
+  pub fn remove<P: AsRef<Path>>(&mut self, path: P) {
+    let mut components = path.as_ref().iter();
+    let name = match components.next() {
+      Some(name) => name,
+      None => return,
+    };
+    let rest = components.as_path();
 
-    if !path.as_str().contains(std::path::is_separator) {
-      self.children.remove(path.as_str());
+    if rest.as_os_str().is_empty() {
+      self.children.remove(name);
       return;
     }
 
-    let mut it = path.iter();
-    let name = it.next().unwrap();
-
     if let Some(Node::Tree(tree)) = self.children.get_mut(name) {
-      tree.remove(it.as_path());
+      tree.remove(rest);
     }
   }
 
-  pub fn get<P: AsRef<Utf8Path>>(&self, path: P) -> Option<&Node> {
-    let path = path.as_ref();
+  pub fn get<P: AsRef<Path>>(&self, path: P) -> Option<&Node> {
+    let mut components = path.as_ref().iter();
+    let name = components.next()?;
+    let rest = components.as_path();
 
-    if !path.as_str().contains(std::path::is_separator) {
-      return self.children.get(path.as_str());
+    if rest.as_os_str().is_empty() {
+      return self.children.get(name);
     }
 
-    let mut it = path.iter();
-    let name = it.next().unwrap();
-
     if let Some(Node::Tree(tree)) = self.children.get(name) {
-      tree.get(it.as_path())
+      tree.get(rest)
     } else {
       None
     }
   }
 
-  pub fn pack(&mut self) -> Result<&String, Errors> {
-    let mut text = String::new();
-    let mut data = Vec::new();
+  // Reads a single level of the tree, leaving its subtrees and blobs unread.
+  pub fn entries(id: &str) -> Result<Vec<Entry>, Errors> {
+    let bytes = read_object_bytes(id)?;
+    let context = || format!("unable to parse tree '{}'", id);
+    let entries = if Layout::get() == Layout::Git {
+      unpack_git_entries(&bytes, ObjectFormat::get()?).context(context)?
+    } else if is_packed_entries(&bytes) {
+      unpack_entries(&bytes).context(context)?
+    } else {
+      unpack_legacy_entries(&String::from_utf8(bytes).context(context)?).context(context)?
+    };
 
-    for (name, node) in &mut self.children {
-      let (object_type, id) = match node {
-        Node::Blob(blob) => (BLOB_TYPE, blob.id()),
-        Node::Tree(tree) => (TREE_TYPE, tree.pack()?),
+    if let Some(entry) = entries.iter().find(|entry| !is_valid_name(&entry.name)) {
+      return Err(Errors::BadObjectStructure).context(|| format!("tree '{}' has an invalid entry name '{}'", id, String::from_utf8_lossy(&entry.name)));
+    }
+
+    Ok(entries)
+  }
+
+  // Finds what a path holds by reading only the trees along it.
+  pub fn entry<P: AsRef<Path>>(id: &str, path: P) -> Result<Option<Entry>, Errors> {
+    let mut id = String::from(id);
+    let mut components = path.as_ref().iter().peekable();
+
+    while let Some(name) = components.next() {
+      let entry = match Self::entries(&id)?.into_iter().find(|entry| entry.name == name_bytes(name)) {
+        Some(entry) => entry,
+        None => return Ok(None),
       };
 
-      data.push(format!("{} {} {}\n", object_type, name, id));
+      if components.peek().is_none() {
+        return Ok(Some(entry));
+      }
+
+      if entry.mode != TREE_MODE {
+        return Ok(None);
+      }
+
+      id = entry.id;
     }
 
-    data.sort();
+    Ok(None)
+  }
+
+  pub fn pack(&mut self) -> Result<&String, Errors> {
+    let mut entries = Vec::new();
 
-    for line in data {
-      text += &line;
+    for (name, node) in &mut self.children {
+      let (mode, id) = match node {
+        Node::Blob(blob) => (blob.mode().as_str(), blob.id()),
+        Node::Tree(tree) => (TREE_MODE, tree.pack()?),
+        Node::Gitlink(id) => (GITLINK_MODE, &*id),
+      };
+
+      entries.push(Entry::new(mode, name_bytes(name), id));
     }
 
-    self.id = write_object_bytes(Object::Tree, text.trim_end())?;
+    let bytes = match Layout::get() {
+      Layout::Rgit => pack_entries(&mut entries),
+      Layout::Git => pack_git_entries(&mut entries)?,
+    };
+
+    self.id = write_object_bytes(Object::Tree, bytes)?;
     Ok(self.id())
   }
 
-  pub fn unpack<P: AsRef<Utf8Path>>(&self, prefix: P) -> Result<(), Errors> {
+  pub fn unpack<P: AsRef<Path>>(&self, prefix: P) -> Result<(), Errors> {
+    let prefix = prefix.as_ref();
+
     for (name, node) in self.children() {
       match node {
         Node::Tree(tree) => {
-          tree.unpack(prefix.as_ref().join(name))?;
+          tree.unpack(prefix.join(name))?;
         },
         Node::Blob(blob) => {
           let bytes = read_object_bytes(blob.id())?;
-          fs::create_dir_all(prefix.as_ref())?;
-          File::create(prefix.as_ref().join(name))?.write_all(&bytes)?;
+          fs::create_dir_all(prefix).context(|| format!("unable to create folder '{}'", prefix.display()))?;
+          write_file(prefix.join(name), &bytes, *blob.mode())?;
         },
+        Node::Gitlink(_) => (),
       }
     }
 
@@ -158,13 +197,13 @@ impl Tree {
     let mut queue = VecDeque::new();
 
     for (name, node) in &self.children {
-      queue.push_front((Utf8PathBuf::from(name), node));
+      queue.push_front((PathBuf::from(name), node));
     }
 
     BlobIterator { queue }
   }
 
-  pub fn blobs(&self) -> HashMap<Utf8PathBuf, &Blob> {
+  pub fn blobs(&self) -> HashMap<PathBuf, &Blob> {
     let mut blobs = HashMap::new();
 
     for (path, blob) in self.blob_iter() {
@@ -173,26 +212,42 @@ impl Tree {
 
     blobs
   }
+
+  // private
+
+  fn insert_node(&mut self, path: &Path, node: Node) {
+    let mut components = path.iter();
+    let name = components.next().unwrap();
+    let rest = components.as_path();
+
+    if rest.as_os_str().is_empty() {
+      self.children.insert(name.to_os_string(), node);
+      return;
+    }
+
+    if let Some(Node::Tree(tree)) = self.children.get_mut(name) {
+      tree.insert_node(rest, node);
+    } else {
+      let mut tree = Tree::new();
+      tree.insert_node(rest, node);
+      self.children.insert(name.to_os_string(), Node::Tree(tree));
+    }
+  }
 }
 
 impl FromId for Tree {
   fn from_id(id: &str) -> Result<Self, Errors> {
-    let bytes = read_object_bytes(id)?;
-    let text = String::from_utf8(bytes)?;
+    let context = || format!("unable to parse tree '{}'", id);
     let mut children = HashMap::new();
 
-    for line in text.lines() {
-      if let [object_type, name, id] = line.split_whitespace().collect::<Vec<&str>>()[..] {
-        let node = match object_type {
-          BLOB_TYPE => Node::Blob(Blob::from_id(id)?),
-          TREE_TYPE => Node::Tree(Tree::from_id(id)?),
-          _ => return Err(Errors::UnrecognisedNodeType),
-        };
-
-        children.insert(String::from(name), node);
-      } else {
-        return Err(Errors::BadObjectStructure);
-      }
+    for entry in Tree::entries(id)? {
+      let node = match entry.mode.as_str() {
+        TREE_MODE => Node::Tree(Tree::from_id(&entry.id)?),
+        GITLINK_MODE => Node::Gitlink(entry.id),
+        mode => Node::Blob(Blob::from_id(&entry.id)?.with_mode(Mode::parse(mode).context(context)?)),
+      };
+
+      children.insert(name_from_bytes(&entry.name), node);
     }
 
     Ok(
@@ -204,23 +259,58 @@ impl FromId for Tree {
   }
 }
 
+// Refuses names that would leave their folder or reach into a repository folder on checkout, as
+// `git fsck` does.
+pub fn is_valid_name(name: &[u8]) -> bool {
+  let is_repository_folder = [REPOSITORY_FOLDER_NAME, GIT_REPOSITORY_FOLDER_NAME].iter()
+    .any(|folder_name| name.eq_ignore_ascii_case(folder_name.as_bytes()));
+
+  !matches!(name, b"" | b"." | b"..") && !name.contains(&b'/') && !name.contains(&0) && !is_repository_folder
+}
+
+// Trees written before entries were NUL-delimited hold one `<type> [<mode>] <name> <id>` line per entry.
+fn unpack_legacy_entries(text: &str) -> Result<Vec<Entry>, Errors> {
+  let mut entries = Vec::new();
+
+  for line in text.lines() {
+    let (object_type, mode, name, id) = match line.split_whitespace().collect::<Vec<&str>>()[..] {
+      [object_type, mode, name, id] => (object_type, mode, name, id),
+      [object_type, name, id] => (object_type, Mode::Regular.as_str(), name, id),
+      _ => return Err(Errors::BadObjectStructure),
+    };
+
+    let mode = match object_type {
+      BLOB_TYPE => mode,
+      TREE_TYPE => TREE_MODE,
+      _ => return Err(Errors::UnrecognisedNodeType),
+    };
+
+    entries.push(Entry::new(mode, name, id));
+  }
+
+  Ok(entries)
+}
+
 pub struct BlobIterator<'a> {
-  queue: VecDeque<(Utf8PathBuf, &'a Node)>,
+  queue: VecDeque<(PathBuf, &'a Node)>,
 }
 
 impl<'a> Iterator for BlobIterator<'a> {
-  type Item = (Utf8PathBuf, &'a Blob);
+  type Item = (PathBuf, &'a Blob);
 
   fn next(&mut self) -> Option<Self::Item> {
-    while let Some((path, Node::Tree(tree))) = self.queue.front() {
-      let path_clone = path.clone();
-      for (name, node) in &tree.children {
-        self.queue.push_back((path_clone.join(name), node));
+    while let Some((path, node)) = self.queue.pop_front() {
+      match node {
+        Node::Tree(tree) => {
+          for (name, node) in &tree.children {
+            self.queue.push_back((path.join(name), node));
+          }
+        },
+        Node::Blob(blob) => return Some((path, blob)),
+        Node::Gitlink(_) => (),
       }
-
-      self.queue.pop_front();
     }
 
-    self.queue.pop_front().map(|(path, node)| (path.clone(), node.into_blob().unwrap()))
+    None
   }
 }
@@ -0,0 +1,324 @@
+use std::{env, fs};
+use camino::Utf8PathBuf;
+use getset::Getters;
+use super::{locale, errors::{Errors, Context}, layout::Layout, lock::Lock, user::{USERNAME_KEY, EMAIL_KEY}, constants::{PROJECT_ENV, SYSTEM_CONFIG_ENV, GLOBAL_CONFIG_ENV, SYSTEM_CONFIG_PATH, GLOBAL_CONFIG_FILE_NAME, GIT_GLOBAL_CONFIG_ENV, GIT_GLOBAL_CONFIG_FILE_NAME, GIT_CONFIG_PATH}};
+
+const LEGACY_DEFAULT_USER: &str = "admin";
+
+// Configuration files are read from the system, the user's home folder and the repository, with
+// later files overriding earlier ones.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Scope {
+  System,
+  Global,
+  Repository,
+}
+
+impl Scope {
+  pub fn location(&self) -> Option<Utf8PathBuf> {
+    match self {
+      Scope::System => Some(env::var(SYSTEM_CONFIG_ENV).map_or(Utf8PathBuf::from(SYSTEM_CONFIG_PATH), Utf8PathBuf::from)),
+      Scope::Global => match env::var(GLOBAL_CONFIG_ENV) {
+        Ok(path) => Some(Utf8PathBuf::from(path)),
+        Err(_) => env::var("HOME").ok().map(|home| Utf8PathBuf::from(home).join(GLOBAL_CONFIG_FILE_NAME)),
+      },
+      Scope::Repository => env::var(PROJECT_ENV).ok().map(|_| locale().join(Layout::get().config_path())),
+    }
+  }
+
+  // Git's own files, which a repository in the Git layout shares with Git.
+  pub fn git_location(&self) -> Option<Utf8PathBuf> {
+    match self {
+      Scope::System => None,
+      Scope::Global => match env::var(GIT_GLOBAL_CONFIG_ENV) {
+        Ok(path) => Some(Utf8PathBuf::from(path)),
+        Err(_) => env::var("HOME").ok().map(|home| Utf8PathBuf::from(home).join(GIT_GLOBAL_CONFIG_FILE_NAME)),
+      },
+      Scope::Repository => env::var(PROJECT_ENV).ok().map(|_| locale().join(GIT_CONFIG_PATH)),
+    }
+  }
+}
+
+// Only the identity is taken from Git's files, as Git's other settings need not mean the same
+// to rgit.
+const GIT_SHARED_KEYS: [&str; 2] = [USERNAME_KEY, EMAIL_KEY];
+
+// Keys read `<section>.<name>` or `<section>.<subsection>.<name>`, where the section and name are
+// case insensitive. Files use Git's format:
+//
+//   [user]
+//     name = Jane Doe
+//   [branch "main"]
+//     remote = origin
+#[derive(Getters, Debug, PartialEq, Clone, Default)]
+pub struct Config {
+  #[getset(get = "pub")]
+  entries: Vec<(String, String)>,
+}
+
+impl Config {
+  pub fn load() -> Result<Self, Errors> {
+    let mut entries = Vec::new();
+
+    let is_git = env::var(PROJECT_ENV).is_ok() && Layout::get() == Layout::Git;
+
+    for scope in [Scope::System, Scope::Global, Scope::Repository] {
+      if is_git {
+        entries.extend(Self::read_git(scope)?.entries);
+      }
+
+      entries.extend(Self::read(scope)?.entries);
+    }
+
+    Ok(Config { entries })
+  }
+
+  pub fn read(scope: Scope) -> Result<Self, Errors> {
+    Self::read_file(scope.location())
+  }
+
+  pub fn set(scope: Scope, key: &str, value: &str) -> Result<(), Errors> {
+    let key = normalize_key(key)?;
+
+    Self::update(scope, |config| {
+      match config.entries.iter().rposition(|(entry_key, _)| *entry_key == key) {
+        Some(position) => config.entries[position].1 = String::from(value),
+        None => {
+          let section = section_name(&key);
+          let position = config.entries.iter().rposition(|(entry_key, _)| section_name(entry_key) == section);
+          config.entries.insert(position.map_or(config.entries.len(), |position| position + 1), (key, String::from(value)));
+        },
+      }
+
+      Ok(())
+    })
+  }
+
+  pub fn unset(scope: Scope, key: &str) -> Result<(), Errors> {
+    let key = normalize_key(key)?;
+
+    Self::update(scope, |config| {
+      let length = config.entries.len();
+      config.entries.retain(|(entry_key, _)| *entry_key != key);
+
+      match config.entries.len() == length {
+        true => Err(Errors::MissingConfigKey(key.clone())),
+        false => Ok(()),
+      }
+    })
+  }
+
+  // The last value wins, so repository settings override global and system ones.
+  pub fn get(&self, key: &str) -> Option<&String> {
+    let key = normalize_key(key).ok()?;
+
+    self.entries.iter()
+      .rev()
+      .find(|(entry_key, _)| *entry_key == key)
+      .map(|(_, value)| value)
+  }
+
+  pub fn parse(text: &str) -> Result<Self, Errors> {
+    if is_legacy(text) {
+      return Ok(parse_legacy(text));
+    }
+
+    let mut entries = Vec::new();
+    let mut section = None;
+
+    for line in text.lines().map(str::trim) {
+      if line.is_empty() || line.starts_with(['#', ';']) {
+        continue;
+      }
+
+      if let Some(header) = line.strip_prefix('[') {
+        section = Some(parse_section(header)?);
+        continue;
+      }
+
+      let section = section.as_ref().ok_or(Errors::BadObjectStructure)?;
+      let (name, value) = match line.split_once('=') {
+        Some((name, value)) => (name.trim(), parse_value(value)?),
+        None => (line, String::from("true")),
+      };
+
+      if !is_name(name) {
+        return Err(Errors::BadObjectStructure);
+      }
+
+      entries.push((format!("{}.{}", section, name.to_lowercase()), value));
+    }
+
+    Ok(Config { entries })
+  }
+
+  pub fn format(&self) -> String {
+    let mut text = String::new();
+    let mut current_section = None;
+
+    for (key, value) in &self.entries {
+      let section = section_name(key);
+
+      if current_section != Some(section) {
+        text += &match section.split_once('.') {
+          Some((section, subsection)) => format!("[{} \"{}\"]\n", section, escape(subsection)),
+          None => format!("[{}]\n", section),
+        };
+        current_section = Some(section);
+      }
+
+      text += &format!("\t{} = {}\n", &key[section.len() + 1..], format_value(value));
+    }
+
+    text
+  }
+
+  // private
+
+  fn read_file(location: Option<Utf8PathBuf>) -> Result<Self, Errors> {
+    match location {
+      Some(location) if location.exists() => {
+        let text = fs::read_to_string(&location).context(|| format!("unable to read config '{}'", location))?;
+        Self::parse(&text).context(|| format!("unable to parse config '{}'", location))
+      },
+      _ => Ok(Config::default()),
+    }
+  }
+
+  fn read_git(scope: Scope) -> Result<Self, Errors> {
+    let mut config = Self::read_file(scope.git_location())?;
+    config.entries.retain(|(key, _)| GIT_SHARED_KEYS.contains(&key.as_str()));
+
+    Ok(config)
+  }
+
+  fn update<F: FnOnce(&mut Config) -> Result<(), Errors>>(scope: Scope, change: F) -> Result<(), Errors> {
+    let location = match (scope, scope.location()) {
+      (_, Some(location)) => location,
+      (Scope::Repository, None) => return Err(Errors::MissingRepository),
+      (_, None) => return Err(Errors::BadArguments(String::from("unable to locate the global config (HOME is not set)"))),
+    };
+    let lock = Lock::acquire(&location)?;
+    let mut config = Self::read(scope)?;
+
+    change(&mut config)?;
+
+    lock.commit(config.format())
+  }
+}
+
+pub fn normalize_key(key: &str) -> Result<String, Errors> {
+  let error = || Errors::BadArguments(format!("invalid key '{}' (expected <section>.<name>)", key));
+  let (section, name) = key.split_once('.').ok_or_else(error)?;
+  let (subsection, name) = match name.rsplit_once('.') {
+    Some((subsection, name)) => (Some(subsection), name),
+    None => (None, name),
+  };
+
+  if !is_name(section) || !is_name(name) || subsection.is_some_and(|subsection| subsection.contains('\n')) {
+    return Err(error());
+  }
+
+  Ok(match subsection {
+    Some(subsection) => format!("{}.{}.{}", section.to_lowercase(), subsection, name.to_lowercase()),
+    None => format!("{}.{}", section.to_lowercase(), name.to_lowercase()),
+  })
+}
+
+fn section_name(key: &str) -> &str {
+  key.rsplit_once('.').map_or(key, |(section, _)| section)
+}
+
+fn is_name(name: &str) -> bool {
+  !name.is_empty() && name.chars().all(|character| character.is_ascii_alphanumeric() || character == '-')
+}
+
+// Headers read `[section]`, `[section "subsection"]` or the older `[section.subsection]`.
+fn parse_section(header: &str) -> Result<String, Errors> {
+  let header = header.strip_suffix(']').ok_or(Errors::BadObjectStructure)?;
+
+  let (section, subsection) = match header.split_once(char::is_whitespace) {
+    Some((section, subsection)) => {
+      let subsection = subsection.trim().strip_prefix('"').and_then(|subsection| subsection.strip_suffix('"')).ok_or(Errors::BadObjectStructure)?;
+      (section, Some(subsection.replace("\\\"", "\"").replace("\\\\", "\\")))
+    },
+    None => match header.split_once('.') {
+      Some((section, subsection)) => (section, Some(subsection.to_lowercase())),
+      None => (header, None),
+    },
+  };
+
+  if !is_name(section) {
+    return Err(Errors::BadObjectStructure);
+  }
+
+  Ok(match subsection {
+    Some(subsection) => format!("{}.{}", section.to_lowercase(), subsection),
+    None => section.to_lowercase(),
+  })
+}
+
+// Values may be quoted to keep surrounding spaces and comment characters, and support the
+// `\"`, `\\`, `\n` and `\t` escapes. Unquoted `#` and `;` start a comment.
+fn parse_value(text: &str) -> Result<String, Errors> {
+  let mut value = String::new();
+  let mut quoted = false;
+  let mut characters = text.trim().chars();
+
+  while let Some(character) = characters.next() {
+    match character {
+      '"' => quoted = !quoted,
+      '\\' => match characters.next() {
+        Some('"') => value.push('"'),
+        Some('\\') => value.push('\\'),
+        Some('n') => value.push('\n'),
+        Some('t') => value.push('\t'),
+        _ => return Err(Errors::BadObjectStructure),
+      },
+      '#' | ';' if !quoted => break,
+      character => value.push(character),
+    }
+  }
+
+  if quoted {
+    return Err(Errors::BadObjectStructure);
+  }
+
+  Ok(String::from(value.trim_end()))
+}
+
+fn format_value(value: &str) -> String {
+  let escaped = escape(value).replace('\n', "\\n").replace('\t', "\\t");
+
+  if value.trim() != value || value.contains(['#', ';']) {
+    format!("\"{}\"", escaped)
+  } else {
+    escaped
+  }
+}
+
+fn escape(text: &str) -> String {
+  text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+// Repositories created before sectioned configs hold a single `<username> <email>` line, which
+// was `admin admin` unless the user changed it.
+fn is_legacy(text: &str) -> bool {
+  let text = text.trim();
+  !text.is_empty() && !text.contains(['[', '=', '\n'])
+}
+
+fn parse_legacy(text: &str) -> Config {
+  let (username, email) = text.trim().rsplit_once(char::is_whitespace).unwrap_or((text.trim(), ""));
+
+  if (username.trim(), email) == (LEGACY_DEFAULT_USER, LEGACY_DEFAULT_USER) {
+    return Config::default();
+  }
+
+  Config {
+    entries: vec![
+      (String::from(USERNAME_KEY), String::from(username.trim())),
+      (String::from(EMAIL_KEY), String::from(email)),
+    ],
+  }
+}
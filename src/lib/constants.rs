@@ -6,10 +6,64 @@ pub const BRANCHES_PATH: &str = ".rgit/branches/";
 pub const HEAD_PATH: &str = ".rgit/HEAD";
 pub const CONFIG_PATH: &str = ".rgit/config";
 pub const INDEX_PATH: &str = ".rgit/index";
+pub const OBJECT_FORMAT_PATH: &str = ".rgit/objectformat";
+pub const HOOKS_PATH: &str = ".rgit/hooks/";
+pub const COMMIT_MESSAGE_PATH: &str = ".rgit/COMMIT_EDITMSG";
+pub const HUNK_EDIT_PATH: &str = ".rgit/addp-hunk-edit.diff";
+pub const BISECT_START_PATH: &str = ".rgit/BISECT_START";
+pub const BISECT_LOG_PATH: &str = ".rgit/BISECT_LOG";
 
 pub const BLOB_TYPE: &str = "blob";
 pub const TREE_TYPE: &str = "tree";
 pub const COMMIT_TYPE: &str = "cmit";
 
+pub const TREE_MODE: &str = "040000";
+pub const GITLINK_MODE: &str = "160000";
+
 pub const REPOSITORY_FOLDER_NAME: &str = ".rgit";
 pub const DELETED_INDEX_STAGE: &str = "DELETED";
+pub const DELETED_INDEX_MODE: &str = "000000";
+pub const LOCK_EXTENSION: &str = "lock";
+pub const SHORT_ID_LENGTH: usize = 7;
+
+pub const SYSTEM_CONFIG_ENV: &str = "RGIT_CONFIG_SYSTEM";
+pub const GLOBAL_CONFIG_ENV: &str = "RGIT_CONFIG_GLOBAL";
+pub const AUTHOR_NAME_ENV: &str = "RGIT_AUTHOR_NAME";
+pub const AUTHOR_EMAIL_ENV: &str = "RGIT_AUTHOR_EMAIL";
+pub const AUTHOR_DATE_ENV: &str = "RGIT_AUTHOR_DATE";
+pub const COMMITTER_NAME_ENV: &str = "RGIT_COMMITTER_NAME";
+pub const COMMITTER_EMAIL_ENV: &str = "RGIT_COMMITTER_EMAIL";
+pub const COMMITTER_DATE_ENV: &str = "RGIT_COMMITTER_DATE";
+pub const SYSTEM_CONFIG_PATH: &str = "/etc/rgitconfig";
+pub const GLOBAL_CONFIG_FILE_NAME: &str = ".rgitconfig";
+pub const GIT_GLOBAL_CONFIG_ENV: &str = "GIT_CONFIG_GLOBAL";
+pub const GIT_GLOBAL_CONFIG_FILE_NAME: &str = ".gitconfig";
+
+pub const GIT_REPOSITORY_PATH: &str = ".git/";
+pub const GIT_OBJECTS_PATH: &str = ".git/objects/";
+pub const GIT_PACKS_PATH: &str = ".git/objects/pack/";
+pub const GIT_REFERENCES_PATH: &str = ".git/refs/";
+pub const GIT_BRANCHES_PATH: &str = ".git/refs/heads/";
+pub const GIT_TAGS_PATH: &str = ".git/refs/tags/";
+pub const GIT_HEAD_PATH: &str = ".git/HEAD";
+pub const GIT_PACKED_REFS_PATH: &str = ".git/packed-refs";
+pub const GIT_CONFIG_PATH: &str = ".git/config";
+pub const GIT_HOOKS_PATH: &str = ".git/hooks/";
+pub const GIT_COMMIT_MESSAGE_PATH: &str = ".git/COMMIT_EDITMSG";
+pub const GIT_HUNK_EDIT_PATH: &str = ".git/addp-hunk-edit.diff";
+pub const GIT_INDEX_PATH: &str = ".git/index";
+pub const GIT_STATE_PATH: &str = ".git/rgit/";
+pub const GIT_STATE_CONFIG_PATH: &str = ".git/rgit/config";
+pub const GIT_STATE_BISECT_START_PATH: &str = ".git/rgit/BISECT_START";
+pub const GIT_STATE_BISECT_LOG_PATH: &str = ".git/rgit/BISECT_LOG";
+
+pub const GIT_BLOB_TYPE: &str = "blob";
+pub const GIT_TREE_TYPE: &str = "tree";
+pub const GIT_COMMIT_TYPE: &str = "commit";
+pub const GIT_TREE_MODE: &str = "40000";
+pub const GIT_INDEX_SIGNATURE: &[u8] = b"DIRC";
+pub const GIT_INDEX_VERSION: u32 = 2;
+pub const GIT_HEAD_REFERENCE_PREFIX: &str = "ref: refs/heads/";
+pub const GIT_BRANCH_REFERENCE_PREFIX: &str = "refs/heads/";
+
+pub const GIT_REPOSITORY_FOLDER_NAME: &str = ".git";
@@ -0,0 +1,287 @@
+use getset::Getters;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Edit {
+  Equal(usize, usize),
+  Delete(usize),
+  Insert(usize),
+}
+
+// Computes a shortest edit script turning `old` into `new` with Myers' algorithm, in its linear
+// space form: the middle of an optimal path is found by searching from both ends at once, and the
+// halves on either side of it are diffed recursively. Edits hold indices into `old` (Equal,
+// Delete) and `new` (Equal, Insert) and are ordered front to back.
+pub fn diff<T: PartialEq>(old: &[T], new: &[T]) -> Vec<Edit> {
+  let mut edits = Vec::new();
+  diff_between(old, new, 0, 0, &mut edits);
+  edits
+}
+
+fn diff_between<T: PartialEq>(old: &[T], new: &[T], old_offset: usize, new_offset: usize, edits: &mut Vec<Edit>) {
+  let prefix = old.iter().zip(new).take_while(|(old, new)| old == new).count();
+  let suffix = old[prefix..].iter().rev().zip(new[prefix..].iter().rev()).take_while(|(old, new)| old == new).count();
+  let (old_middle, new_middle) = (&old[prefix..old.len() - suffix], &new[prefix..new.len() - suffix]);
+  let (old_start, new_start) = (old_offset + prefix, new_offset + prefix);
+
+  edits.extend((0..prefix).map(|index| Edit::Equal(old_offset + index, new_offset + index)));
+
+  match middle(old_middle, new_middle) {
+    Some((x, y)) => {
+      diff_between(&old_middle[..x], &new_middle[..y], old_start, new_start, edits);
+      diff_between(&old_middle[x..], &new_middle[y..], old_start + x, new_start + y, edits);
+    },
+    None => {
+      edits.extend((0..old_middle.len()).map(|index| Edit::Delete(old_start + index)));
+      edits.extend((0..new_middle.len()).map(|index| Edit::Insert(new_start + index)));
+    },
+  }
+
+  let (old_end, new_end) = (old_start + old_middle.len(), new_start + new_middle.len());
+  edits.extend((0..suffix).map(|index| Edit::Equal(old_end + index, new_end + index)));
+}
+
+// Walks forward from the start and backward from the end, one edit at a time, until the paths
+// meet, and returns the meeting point. Only the furthest point reached on each diagonal is kept,
+// and diagonals that leave the grid are no longer followed.
+fn middle<T: PartialEq>(old: &[T], new: &[T]) -> Option<(usize, usize)> {
+  if old.is_empty() || new.is_empty() {
+    return None;
+  }
+
+  let (n, m) = (old.len() as isize, new.len() as isize);
+  let max = (n + m + 1) / 2;
+  let length = 2 * max + 2;
+  let delta = n - m;
+  let is_odd = delta % 2 != 0;
+  let mut forward = vec![-1isize; length as usize];
+  let mut backward = forward.clone();
+  let (mut forward_start, mut forward_end, mut backward_start, mut backward_end) = (0, 0, 0, 0);
+  forward[max as usize + 1] = 0;
+  backward[max as usize + 1] = 0;
+
+  for d in 0..max {
+    for k in (-d + forward_start..=d - forward_end).step_by(2) {
+      let index = (k + max) as usize;
+      let mut x = if k == -d || (k != d && forward[index - 1] < forward[index + 1]) {
+        forward[index + 1]
+      } else {
+        forward[index - 1] + 1
+      };
+      let mut y = x - k;
+
+      while x < n && y < m && old[x as usize] == new[y as usize] {
+        x += 1;
+        y += 1;
+      }
+
+      forward[index] = x;
+
+      if x > n {
+        forward_end += 2;
+      } else if y > m {
+        forward_start += 2;
+      } else if is_odd {
+        let backward_index = max + delta - k;
+
+        if (0..length).contains(&backward_index) && backward[backward_index as usize] != -1 && x >= n - backward[backward_index as usize] {
+          return Some((x as usize, y as usize));
+        }
+      }
+    }
+
+    for k in (-d + backward_start..=d - backward_end).step_by(2) {
+      let index = (k + max) as usize;
+      let mut x = if k == -d || (k != d && backward[index - 1] < backward[index + 1]) {
+        backward[index + 1]
+      } else {
+        backward[index - 1] + 1
+      };
+      let mut y = x - k;
+
+      while x < n && y < m && old[(n - x - 1) as usize] == new[(m - y - 1) as usize] {
+        x += 1;
+        y += 1;
+      }
+
+      backward[index] = x;
+
+      if x > n {
+        backward_end += 2;
+      } else if y > m {
+        backward_start += 2;
+      } else if !is_odd {
+        let forward_index = max + delta - k;
+
+        if (0..length).contains(&forward_index) && forward[forward_index as usize] != -1 {
+          let forward_x = forward[forward_index as usize];
+
+          if forward_x >= n - x {
+            return Some((forward_x as usize, (forward_x + max - forward_index) as usize));
+          }
+        }
+      }
+    }
+  }
+
+  None
+}
+
+// A run of changes with the unchanged lines around them. Lines are prefixed with ` `, `-` or `+`
+// as in unified diffs, and the starts are indices into the old and new sequences.
+#[derive(Getters, Debug, PartialEq, Clone)]
+pub struct Hunk {
+  #[getset(get = "pub")]
+  old_start: usize,
+
+  #[getset(get = "pub")]
+  new_start: usize,
+
+  #[getset(get = "pub")]
+  lines: Vec<String>,
+}
+
+impl Hunk {
+  pub fn header(&self) -> String {
+    let old_count = self.lines.iter().filter(|line| !line.starts_with('+')).count();
+    let new_count = self.lines.iter().filter(|line| !line.starts_with('-')).count();
+
+    format!("@@ -{} +{} @@", hunk_range(self.old_start, old_count), hunk_range(self.new_start, new_count))
+  }
+
+  // Splits the hunk between its groups of changes, sharing out the unchanged lines in between.
+  pub fn split(&self) -> Vec<Hunk> {
+    let changes = self.lines.iter()
+      .enumerate()
+      .filter(|(_, line)| !line.starts_with(' '))
+      .map(|(index, _)| index)
+      .collect::<Vec<usize>>();
+    let mut boundaries = changes.windows(2)
+      .filter(|pair| pair[1] - pair[0] > 1)
+      .map(|pair| pair[0] + 1 + (pair[1] - pair[0]) / 2)
+      .collect::<Vec<usize>>();
+    boundaries.push(self.lines.len());
+
+    let mut hunks = Vec::new();
+    let (mut start, mut old_start, mut new_start) = (0, self.old_start, self.new_start);
+
+    for end in boundaries {
+      let lines = Vec::from(&self.lines[start..end]);
+      let old_count = lines.iter().filter(|line| !line.starts_with('+')).count();
+      let new_count = lines.iter().filter(|line| !line.starts_with('-')).count();
+
+      hunks.push(Hunk { old_start, new_start, lines });
+      start = end;
+      old_start += old_count;
+      new_start += new_count;
+    }
+
+    hunks
+  }
+
+  // Takes the lines of an edited copy of the hunk, which must keep its unchanged and removed lines.
+  pub fn with_lines(&self, lines: Vec<String>) -> Option<Hunk> {
+    let old_side = |lines: &[String]| lines.iter()
+      .filter(|line| !line.starts_with('+'))
+      .map(|line| String::from(&line[1..]))
+      .collect::<Vec<String>>();
+
+    if lines.iter().any(|line| !line.starts_with([' ', '-', '+'])) || old_side(&lines) != old_side(&self.lines) {
+      return None;
+    }
+
+    Some(Hunk { old_start: self.old_start, new_start: self.new_start, lines })
+  }
+}
+
+// Groups the differences into hunks with `context` unchanged lines around each change.
+pub fn hunks<S: AsRef<str>>(old: &[S], new: &[S], context: usize) -> Vec<Hunk> {
+  let old = old.iter().map(AsRef::as_ref).collect::<Vec<&str>>();
+  let new = new.iter().map(AsRef::as_ref).collect::<Vec<&str>>();
+  let edits = diff(&old, &new);
+  let changes = edits.iter()
+    .enumerate()
+    .filter(|(_, edit)| !matches!(edit, Edit::Equal(_, _)))
+    .map(|(index, _)| index)
+    .collect::<Vec<usize>>();
+
+  let mut positions = Vec::new();
+  let (mut old_position, mut new_position) = (0, 0);
+
+  for edit in &edits {
+    positions.push((old_position, new_position));
+
+    match edit {
+      Edit::Equal(_, _) => {
+        old_position += 1;
+        new_position += 1;
+      },
+      Edit::Delete(_) => old_position += 1,
+      Edit::Insert(_) => new_position += 1,
+    }
+  }
+
+  let mut hunks = Vec::new();
+  let mut index = 0;
+
+  while index < changes.len() {
+    let mut last = index;
+
+    while last + 1 < changes.len() && changes[last + 1] - changes[last] <= 2 * context + 1 {
+      last += 1;
+    }
+
+    let start = changes[index].saturating_sub(context);
+    let end = (changes[last] + context + 1).min(edits.len());
+    let (old_start, new_start) = positions[start];
+    let lines = edits[start..end].iter()
+      .map(|edit| match edit {
+        Edit::Equal(old_index, _) => format!(" {}", old[*old_index]),
+        Edit::Delete(old_index) => format!("-{}", old[*old_index]),
+        Edit::Insert(new_index) => format!("+{}", new[*new_index]),
+      })
+      .collect();
+
+    hunks.push(Hunk { old_start, new_start, lines });
+    index = last + 1;
+  }
+
+  hunks
+}
+
+// Renders the differences as unified diff hunks with `context` unchanged lines around each change.
+pub fn unified<S: AsRef<str>>(old: &[S], new: &[S], context: usize) -> String {
+  let mut output = String::new();
+
+  for hunk in hunks(old, new, context) {
+    output += &format!("{}\n", hunk.header());
+
+    for line in hunk.lines() {
+      output += &format!("{}\n", line);
+    }
+  }
+
+  output
+}
+
+// Applies hunks of `old`, which must be ordered and must not overlap, and returns the new lines.
+pub fn patch<S: AsRef<str>>(old: &[S], hunks: &[Hunk]) -> Vec<String> {
+  let mut lines = Vec::new();
+  let mut position = 0;
+
+  for hunk in hunks {
+    lines.extend(old[position..hunk.old_start].iter().map(|line| String::from(line.as_ref())));
+    lines.extend(hunk.lines.iter().filter(|line| !line.starts_with('-')).map(|line| String::from(&line[1..])));
+    position = hunk.old_start + hunk.lines.iter().filter(|line| !line.starts_with('+')).count();
+  }
+
+  lines.extend(old[position..].iter().map(|line| String::from(line.as_ref())));
+  lines
+}
+
+fn hunk_range(start: usize, count: usize) -> String {
+  match count {
+    0 => format!("{},0", start),
+    1 => format!("{}", start + 1),
+    count => format!("{},{}", start + 1, count),
+  }
+}
@@ -1,14 +1,40 @@
+use std::{fmt, process, path::PathBuf};
 use camino::Utf8PathBuf;
 
+pub const USER_ERROR_CODE: i32 = 1;
+pub const REPOSITORY_ERROR_CODE: i32 = 3;
+pub const CORRUPTION_ERROR_CODE: i32 = 4;
+pub const IO_ERROR_CODE: i32 = 5;
+
 #[derive(Debug)]
 pub enum Errors {
   MissingRepository,
   ExistingRepository,
   ExistingBranch(String),
   BadPathEncoding,
-  UnrecognisedPath(Utf8PathBuf),
+  UnrecognisedPath(PathBuf),
   UnrecognisedObject(String),
   UnrecognisedBranch(String),
+  UnrecognisedObjectFormat(String),
+  UnrecognisedPattern(String),
+  NoMatch,
+  UnrecognisedRevision(String),
+  AmbiguousObject(String),
+  BadArguments(String),
+  LockedFile(Utf8PathBuf),
+  StaleReference(String),
+  MissingConfigKey(String),
+  FailedHook(String),
+  BadSignature(String),
+  CurrentBranch(String),
+  BadBranchName(String),
+  UnmergedBranch(String),
+  UnmergedPath(String),
+  MissingBisect,
+  ExistingBisect,
+  PackedObject(String),
+  LocalChanges,
+  UntrackedFile(String),
 
   BadNodeConvertion,
   BadObjectStructure,
@@ -17,6 +43,112 @@ pub enum Errors {
   Utf8ConvertionError(std::string::FromUtf8Error),
   DateTimeParseError(chrono::ParseError),
   WalkDirError(walkdir::Error),
+
+  Context(String, Box<Errors>),
+}
+
+impl Errors {
+  // Exit codes group failures by their cause: 1 for bad user input, 3 for the repository being in
+  // the wrong state, 4 for malformed objects or files and 5 for operating system failures. Code 2
+  // is left to argument parsing errors.
+  pub fn exit_code(&self) -> i32 {
+    match self {
+      Errors::ExistingBranch(_) |
+      Errors::BadPathEncoding |
+      Errors::UnrecognisedPath(_) |
+      Errors::UnrecognisedObject(_) |
+      Errors::UnrecognisedBranch(_) |
+      Errors::UnrecognisedObjectFormat(_) |
+      Errors::UnrecognisedPattern(_) |
+      Errors::NoMatch |
+      Errors::UnrecognisedRevision(_) |
+      Errors::AmbiguousObject(_) |
+      Errors::BadArguments(_) |
+      Errors::MissingConfigKey(_) |
+      Errors::FailedHook(_) |
+      Errors::BadSignature(_) |
+      Errors::BadBranchName(_) => USER_ERROR_CODE,
+
+      Errors::MissingRepository |
+      Errors::ExistingRepository |
+      Errors::LockedFile(_) |
+      Errors::StaleReference(_) |
+      Errors::CurrentBranch(_) |
+      Errors::UnmergedBranch(_) |
+      Errors::UnmergedPath(_) |
+      Errors::MissingBisect |
+      Errors::ExistingBisect |
+      Errors::PackedObject(_) |
+      Errors::LocalChanges |
+      Errors::UntrackedFile(_) => REPOSITORY_ERROR_CODE,
+
+      Errors::BadNodeConvertion |
+      Errors::BadObjectStructure |
+      Errors::UnrecognisedNodeType |
+      Errors::Utf8ConvertionError(_) |
+      Errors::DateTimeParseError(_) => CORRUPTION_ERROR_CODE,
+
+      Errors::IOError(_) |
+      Errors::WalkDirError(_) => IO_ERROR_CODE,
+
+      Errors::Context(_, error) => error.exit_code(),
+    }
+  }
+}
+
+impl fmt::Display for Errors {
+  fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+    match self {
+      Errors::MissingRepository => write!(formatter, "not a rgit repository (or any of the parent directories)"),
+      Errors::ExistingRepository => write!(formatter, "rgit repository already instantiated in current working environment"),
+      Errors::ExistingBranch(name) => write!(formatter, "a branch named '{}' already exists", name),
+      Errors::BadPathEncoding => write!(formatter, "bad path encoding (only utf8 is supported)"),
+      Errors::UnrecognisedPath(path) => write!(formatter, "path '{}' did not match any files", path.display()),
+      Errors::UnrecognisedObject(id) => write!(formatter, "object identificator '{}' did not match any object", id),
+      Errors::UnrecognisedBranch(name) => write!(formatter, "branch name '{}' does not exist", name),
+      Errors::UnrecognisedObjectFormat(name) => write!(formatter, "unknown object format '{}' (expected sha1 or sha256)", name),
+      Errors::UnrecognisedPattern(pattern) => write!(formatter, "invalid pattern '{}'", pattern),
+      Errors::NoMatch => write!(formatter, "no match found"),
+      Errors::UnrecognisedRevision(revision) => write!(formatter, "revision '{}' did not match any commit", revision),
+      Errors::AmbiguousObject(id) => write!(formatter, "short object identificator '{}' is ambiguous", id),
+      Errors::BadArguments(message) => write!(formatter, "{}", message),
+      Errors::LockedFile(path) => write!(formatter, "unable to create '{}': another rgit process seems to be running in this repository", path),
+      Errors::BadBranchName(name) => write!(formatter, "'{}' is not a valid branch name", name),
+      Errors::CurrentBranch(name) => write!(formatter, "cannot delete branch '{}' while it is checked out", name),
+      Errors::UnmergedBranch(name) => write!(formatter, "the branch '{}' is not fully merged (use -D to delete it anyway)", name),
+      Errors::UnmergedPath(path) => write!(formatter, "'{}' has unresolved merge conflicts in Git's index (resolve them with git first)", path),
+      Errors::PackedObject(id) => write!(formatter, "object '{}' is in a pack file, which rgit cannot read (unpack it with 'git unpack-objects')", id),
+      Errors::MissingBisect => write!(formatter, "not bisecting (run 'rgit bisect start' first)"),
+      Errors::ExistingBisect => write!(formatter, "already bisecting (run 'rgit bisect reset' first)"),
+      Errors::LocalChanges => write!(formatter, "the working tree has changes, which checking out another commit would lose (commit them first)"),
+      Errors::UntrackedFile(path) => write!(formatter, "untracked file '{}' would be overwritten by checking out another commit (move or remove it first)", path),
+      Errors::BadSignature(id) => write!(formatter, "commit '{}' has no good signature from a trusted key", id),
+      Errors::FailedHook(name) => write!(formatter, "the {} hook failed", name),
+      Errors::MissingConfigKey(key) => write!(formatter, "key '{}' is not set", key),
+      Errors::StaleReference(name) => write!(formatter, "reference '{}' does not point at the expected commit", name),
+      Errors::BadNodeConvertion => write!(formatter, "expected a file but found a folder (or the reverse)"),
+      Errors::BadObjectStructure => write!(formatter, "malformed object or repository file"),
+      Errors::UnrecognisedNodeType => write!(formatter, "unknown tree entry type"),
+      Errors::IOError(error) => write!(formatter, "{}", error),
+      Errors::Utf8ConvertionError(error) => write!(formatter, "invalid utf8 content ({})", error),
+      Errors::DateTimeParseError(error) => write!(formatter, "invalid date ({})", error),
+      Errors::WalkDirError(error) => write!(formatter, "{}", error),
+      Errors::Context(context, error) => write!(formatter, "{}: {}", context, error),
+    }
+  }
+}
+
+impl std::error::Error for Errors {
+  fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+    match self {
+      Errors::IOError(error) => Some(error),
+      Errors::Utf8ConvertionError(error) => Some(error),
+      Errors::DateTimeParseError(error) => Some(error),
+      Errors::WalkDirError(error) => Some(error),
+      Errors::Context(_, error) => Some(error.as_ref()),
+      _ => None,
+    }
+  }
 }
 
 impl From<std::io::Error> for Errors {
@@ -43,19 +175,26 @@ impl From<walkdir::Error> for Errors {
   }
 }
 
+// Describes the operation that failed, e.g. `.context(|| format!("unable to read '{}'", path))`.
+pub trait Context<T> {
+  fn context<F: FnOnce() -> String>(self, context: F) -> Result<T, Errors>;
+}
+
+impl<T, E: Into<Errors>> Context<T> for Result<T, E> {
+  fn context<F: FnOnce() -> String>(self, context: F) -> Result<T, Errors> {
+    self.map_err(|error| Errors::Context(context(), Box::new(error.into())))
+  }
+}
+
 pub struct ErrorsInterface;
 
 impl ErrorsInterface {
-  pub fn handle(error: Errors) {
-    match error {
-      Errors::MissingRepository => println!("error: not a rgit repository (or any of the parent directories)"),
-      Errors::ExistingRepository => println!("error: rgit repository already instantiated in current working environment"),
-      Errors::ExistingBranch(name) => println!("error: a branch named '{}' already exists", name),
-      Errors::BadPathEncoding => println!("error: bad path encoding (only utf8 is supported)"),
-      Errors::UnrecognisedPath(path) => println!("error: path '{}' did not match any files", path),
-      Errors::UnrecognisedObject(id) => println!("error: object identificator '{}' did not match any object", id),
-      Errors::UnrecognisedBranch(name) => println!("error: branch name '{}' does not exist", name),
-      _ => print!("fatal: Internal Error")
-    };
+  pub fn handle(error: Errors) -> ! {
+    // A search finding nothing only tells so through its exit code, as with Git's grep.
+    if !matches!(error, Errors::NoMatch) {
+      eprintln!("error: {}", error);
+    }
+
+    process::exit(error.exit_code());
   }
 }
@@ -0,0 +1,150 @@
+use std::{cell::Cell, fs};
+use sha1::{Sha1, Digest};
+use sha2::Sha256;
+use super::{locale, errors::{Errors, Context}, layout::Layout, lock::Lock, constants::{OBJECT_FORMAT_PATH, GIT_CONFIG_PATH}};
+
+thread_local! {
+  static WRITTEN_FORMAT: Cell<Option<ObjectFormat>> = const { Cell::new(None) };
+}
+
+// The hash algorithm naming objects, chosen when the repository is created. Repositories
+// without a recorded setting use SHA-1.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ObjectFormat {
+  Sha1,
+  Sha256,
+}
+
+impl ObjectFormat {
+  pub fn get() -> Result<Self, Errors> {
+    match Layout::get() {
+      Layout::Rgit => {
+        let location = locale().join(OBJECT_FORMAT_PATH);
+
+        if location.exists() {
+          Self::parse(fs::read_to_string(&location).context(|| format!("unable to read '{}'", location))?.trim())
+        } else {
+          Ok(ObjectFormat::Sha1)
+        }
+      },
+      Layout::Git => {
+        let location = locale().join(GIT_CONFIG_PATH);
+        let config = fs::read_to_string(&location).context(|| format!("unable to read config '{}'", location))?;
+        let mut section = String::new();
+
+        for line in config.lines().map(|line| line.trim()) {
+          if line.starts_with('[') {
+            section = line.trim_matches(|character| character == '[' || character == ']').to_lowercase();
+          } else if let Some((key, value)) = line.split_once('=') {
+            if section == "extensions" && key.trim().eq_ignore_ascii_case("objectformat") {
+              return Self::parse(value.trim());
+            }
+          }
+        }
+
+        Ok(ObjectFormat::Sha1)
+      },
+    }
+  }
+
+  // The format new objects are hashed in, which a conversion overrides while it writes the objects
+  // of the format it is about to switch to.
+  pub fn written() -> Result<Self, Errors> {
+    match WRITTEN_FORMAT.with(Cell::get) {
+      Some(format) => Ok(format),
+      None => Self::get(),
+    }
+  }
+
+  pub fn write_as<T, F: FnOnce() -> Result<T, Errors>>(format: ObjectFormat, write: F) -> Result<T, Errors> {
+    WRITTEN_FORMAT.with(|written| written.set(Some(format)));
+    let result = write();
+    WRITTEN_FORMAT.with(|written| written.set(None));
+
+    result
+  }
+
+  pub fn set(format: ObjectFormat) -> Result<(), Errors> {
+    match Layout::get() {
+      Layout::Rgit => {
+        Lock::acquire(locale().join(OBJECT_FORMAT_PATH))?.commit(format.name())?;
+      },
+      Layout::Git => {
+        let location = locale().join(GIT_CONFIG_PATH);
+        let lock = Lock::acquire(&location)?;
+        let mut config = String::new();
+        let mut section = String::new();
+
+        for line in fs::read_to_string(&location)?.lines() {
+          let trimmed = line.trim();
+
+          if trimmed.starts_with('[') {
+            section = trimmed.trim_matches(|character| character == '[' || character == ']').to_lowercase();
+          } else if section == "extensions" && trimmed.to_lowercase().starts_with("objectformat") {
+            continue;
+          } else if section == "core" && trimmed.starts_with("repositoryformatversion") {
+            config += &format!("\trepositoryformatversion = {}\n", if format == ObjectFormat::Sha1 { 0 } else { 1 });
+            continue;
+          }
+
+          config += line;
+          config += "\n";
+        }
+
+        let mut config = config.replace("[extensions]\n[", "[");
+
+        if config.ends_with("[extensions]\n") {
+          config.truncate(config.len() - "[extensions]\n".len());
+        }
+
+        if format != ObjectFormat::Sha1 {
+          config += &format!("[extensions]\n\tobjectformat = {}\n", format.name());
+        }
+
+        lock.commit(config)?;
+      },
+    }
+
+    Ok(())
+  }
+
+  pub fn parse(name: &str) -> Result<Self, Errors> {
+    match name {
+      "sha1" => Ok(ObjectFormat::Sha1),
+      "sha256" => Ok(ObjectFormat::Sha256),
+      _ => Err(Errors::UnrecognisedObjectFormat(String::from(name))),
+    }
+  }
+
+  pub fn name(&self) -> &'static str {
+    match self {
+      ObjectFormat::Sha1 => "sha1",
+      ObjectFormat::Sha256 => "sha256",
+    }
+  }
+
+  pub fn byte_length(&self) -> usize {
+    match self {
+      ObjectFormat::Sha1 => 20,
+      ObjectFormat::Sha256 => 32,
+    }
+  }
+
+  pub fn id_length(&self) -> usize {
+    self.byte_length() * 2
+  }
+
+  pub fn hash<B: AsRef<[u8]>>(&self, bytes: B) -> String {
+    match self {
+      ObjectFormat::Sha1 => hex::encode(Sha1::digest(bytes)),
+      ObjectFormat::Sha256 => hex::encode(Sha256::digest(bytes)),
+    }
+  }
+
+  // Ids of every supported format are accepted, so objects can still be read while a
+  // repository is being converted from one format to another.
+  pub fn is_id(id: &str) -> bool {
+    [ObjectFormat::Sha1, ObjectFormat::Sha256].iter().any(|format| format.id_length() == id.len())
+      && id.bytes().all(|byte| byte.is_ascii_hexdigit())
+  }
+}
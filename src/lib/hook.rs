@@ -0,0 +1,44 @@
+use std::{fs, process::Command};
+use super::{locale, errors::{Errors, Context}, layout::Layout, object::Mode, file_mode};
+
+// Hooks are executables in `.rgit/hooks/` (`.git/hooks/` for Git repositories) named after the
+// point they run at. Missing or non-executable hooks are skipped. They run from the top of the
+// repository and a non-zero exit status makes them fail.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Hook {
+  PreCommit,
+  CommitMsg,
+  PostCommit,
+  PostSwitch,
+}
+
+impl Hook {
+  pub fn name(&self) -> &'static str {
+    match self {
+      Hook::PreCommit => "pre-commit",
+      Hook::CommitMsg => "commit-msg",
+      Hook::PostCommit => "post-commit",
+      Hook::PostSwitch => "post-switch",
+    }
+  }
+
+  pub fn run(&self, arguments: &[&str]) -> Result<(), Errors> {
+    let location = locale().join(Layout::get().hooks_path()).join(self.name());
+
+    match fs::metadata(&location) {
+      Ok(metadata) if metadata.is_file() && file_mode(&metadata) == Mode::Executable => (),
+      _ => return Ok(()),
+    }
+
+    let status = Command::new(&location)
+      .args(arguments)
+      .current_dir(locale())
+      .status()
+      .context(|| format!("unable to run the {} hook", self.name()))?;
+
+    match status.success() {
+      true => Ok(()),
+      false => Err(Errors::FailedHook(String::from(self.name()))),
+    }
+  }
+}
@@ -0,0 +1,115 @@
+use std::env;
+use chrono::{DateTime, FixedOffset, Local, NaiveDate, TimeZone, Timelike, Utc};
+use getset::Getters;
+use super::{errors::Errors, user::User, layout::Layout, constants::{AUTHOR_NAME_ENV, AUTHOR_EMAIL_ENV, AUTHOR_DATE_ENV, COMMITTER_NAME_ENV, COMMITTER_EMAIL_ENV, COMMITTER_DATE_ENV}};
+
+// Who made a change and when, keeping the UTC offset of the place it was made.
+#[derive(Getters, Debug, PartialEq, Clone)]
+pub struct Identity {
+  #[getset(get = "pub")]
+  username: String,
+
+  #[getset(get = "pub")]
+  email: String,
+
+  #[getset(get = "pub")]
+  date: DateTime<FixedOffset>,
+}
+
+impl Identity {
+  pub fn new(user: &User, date: DateTime<FixedOffset>) -> Self {
+    Identity {
+      username: user.username().clone(),
+      email: user.email().clone(),
+      date,
+    }
+  }
+
+  pub fn author() -> Result<Self, Errors> {
+    Self::from_env(AUTHOR_NAME_ENV, AUTHOR_EMAIL_ENV, AUTHOR_DATE_ENV)
+  }
+
+  pub fn committer() -> Result<Self, Errors> {
+    Self::from_env(COMMITTER_NAME_ENV, COMMITTER_EMAIL_ENV, COMMITTER_DATE_ENV)
+  }
+
+  // Reads `<name> <<email>> <date>`, where the date is in any format `parse_date` accepts.
+  pub fn parse(text: &str) -> Result<Self, Errors> {
+    let (user, date) = text.rsplit_once('>').ok_or(Errors::BadObjectStructure)?;
+    let user = parse_user(&format!("{}>", user))?;
+
+    Ok(Identity::new(&user, parse_date(date.trim()).map_err(|_| Errors::BadObjectStructure)?))
+  }
+
+  // Git stores whole seconds since the epoch and the offset, and rgit stores RFC 3339 dates.
+  pub fn format(&self, layout: Layout) -> String {
+    match layout {
+      Layout::Rgit => format!("{} <{}> {}", self.username, self.email, self.date.to_rfc3339()),
+      Layout::Git => format!("{} <{}> {} {}", self.username, self.email, self.date.timestamp(), self.date.format("%z")),
+    }
+  }
+
+  pub fn with_user(mut self, user: &User) -> Self {
+    self.username = user.username().clone();
+    self.email = user.email().clone();
+    self
+  }
+
+  pub fn with_date(mut self, date: DateTime<FixedOffset>) -> Self {
+    self.date = date;
+    self
+  }
+
+  pub fn with_whole_seconds(self) -> Self {
+    let date = self.date.with_nanosecond(0).unwrap_or(self.date);
+    self.with_date(date)
+  }
+
+  // private
+
+  fn from_env(name_env: &str, email_env: &str, date_env: &str) -> Result<Self, Errors> {
+    let user = User::get()?;
+    let username = env::var(name_env).unwrap_or_else(|_| user.username().clone());
+    let email = env::var(email_env).unwrap_or_else(|_| user.email().clone());
+    let date = match env::var(date_env) {
+      Ok(date) => parse_date(&date)?,
+      Err(_) => now(),
+    };
+
+    Ok(Identity { username, email, date })
+  }
+}
+
+// Reads `<name> <<email>>`.
+pub fn parse_user(text: &str) -> Result<User, Errors> {
+  let error = || Errors::BadArguments(format!("invalid identity '{}' (expected <name> <<email>>)", text));
+  let (username, email) = text.trim().split_once('<').ok_or_else(error)?;
+  let email = email.strip_suffix('>').ok_or_else(error)?;
+
+  User::new(username.trim(), email)
+}
+
+// Accepts RFC 3339, RFC 2822, `<yyyy-mm-dd hh:mm:ss> <offset>`, `<yyyy-mm-dd>` at midnight UTC and Git's
+// `<seconds since epoch> <offset>` or `@<seconds since epoch>`.
+pub fn parse_date(text: &str) -> Result<DateTime<FixedOffset>, Errors> {
+  let text = text.trim();
+  let timestamp = |text: &str| text.parse::<i64>().ok().map(|timestamp| Utc.timestamp(timestamp, 0));
+
+  let date = DateTime::parse_from_rfc3339(text).ok()
+    .or_else(|| DateTime::parse_from_rfc2822(text).ok())
+    .or_else(|| DateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S %z").ok())
+    .or_else(|| NaiveDate::parse_from_str(text, "%Y-%m-%d").ok().map(|date| Utc.from_utc_datetime(&date.and_hms(0, 0, 0)).with_timezone(&FixedOffset::east(0))))
+    .or_else(|| text.strip_prefix('@').and_then(timestamp).map(|date| date.with_timezone(&FixedOffset::east(0))))
+    .or_else(|| {
+      let (seconds, offset) = text.split_once(' ')?;
+      let offset = DateTime::parse_from_str(&format!("1970-01-01 00:00:00 {}", offset), "%Y-%m-%d %H:%M:%S %z").ok()?;
+      Some(timestamp(seconds)?.with_timezone(offset.offset()))
+    });
+
+  date.ok_or_else(|| Errors::BadArguments(format!("invalid date '{}'", text)))
+}
+
+fn now() -> DateTime<FixedOffset> {
+  let now = Local::now();
+  now.with_timezone(now.offset())
+}
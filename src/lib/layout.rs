@@ -0,0 +1,106 @@
+use super::{locale, constants::*};
+
+// Rgit keeps its repository in `.rgit/`. The Git layout operates directly on a `.git/` directory, using
+// Git's loose object format and index, and keeps the rgit-only state (user config and bisect state)
+// under `.git/rgit/`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Layout {
+  Rgit,
+  Git,
+}
+
+impl Layout {
+  pub fn get() -> Self {
+    let locale = locale();
+
+    if !locale.join(REPOSITORY_PATH).exists() && locale.join(GIT_REPOSITORY_PATH).exists() {
+      Layout::Git
+    } else {
+      Layout::Rgit
+    }
+  }
+
+  pub fn folder_name(&self) -> &'static str {
+    match self {
+      Layout::Rgit => REPOSITORY_FOLDER_NAME,
+      Layout::Git => GIT_REPOSITORY_FOLDER_NAME,
+    }
+  }
+
+  pub fn repository_path(&self) -> &'static str {
+    match self {
+      Layout::Rgit => REPOSITORY_PATH,
+      Layout::Git => GIT_REPOSITORY_PATH,
+    }
+  }
+
+  pub fn objects_path(&self) -> &'static str {
+    match self {
+      Layout::Rgit => OBJECTS_PATH,
+      Layout::Git => GIT_OBJECTS_PATH,
+    }
+  }
+
+  pub fn branches_path(&self) -> &'static str {
+    match self {
+      Layout::Rgit => BRANCHES_PATH,
+      Layout::Git => GIT_BRANCHES_PATH,
+    }
+  }
+
+  pub fn head_path(&self) -> &'static str {
+    match self {
+      Layout::Rgit => HEAD_PATH,
+      Layout::Git => GIT_HEAD_PATH,
+    }
+  }
+
+  pub fn config_path(&self) -> &'static str {
+    match self {
+      Layout::Rgit => CONFIG_PATH,
+      Layout::Git => GIT_STATE_CONFIG_PATH,
+    }
+  }
+
+  pub fn index_path(&self) -> &'static str {
+    match self {
+      Layout::Rgit => INDEX_PATH,
+      Layout::Git => GIT_INDEX_PATH,
+    }
+  }
+
+  pub fn hooks_path(&self) -> &'static str {
+    match self {
+      Layout::Rgit => HOOKS_PATH,
+      Layout::Git => GIT_HOOKS_PATH,
+    }
+  }
+
+  pub fn commit_message_path(&self) -> &'static str {
+    match self {
+      Layout::Rgit => COMMIT_MESSAGE_PATH,
+      Layout::Git => GIT_COMMIT_MESSAGE_PATH,
+    }
+  }
+
+  pub fn hunk_edit_path(&self) -> &'static str {
+    match self {
+      Layout::Rgit => HUNK_EDIT_PATH,
+      Layout::Git => GIT_HUNK_EDIT_PATH,
+    }
+  }
+
+  pub fn bisect_start_path(&self) -> &'static str {
+    match self {
+      Layout::Rgit => BISECT_START_PATH,
+      Layout::Git => GIT_STATE_BISECT_START_PATH,
+    }
+  }
+
+  pub fn bisect_log_path(&self) -> &'static str {
+    match self {
+      Layout::Rgit => BISECT_LOG_PATH,
+      Layout::Git => GIT_STATE_BISECT_LOG_PATH,
+    }
+  }
+}
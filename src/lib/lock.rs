@@ -0,0 +1,105 @@
+use std::{fs::{File, OpenOptions, self}, io::{ErrorKind, Write}, process, sync::atomic::{AtomicUsize, Ordering}};
+use camino::{Utf8Path, Utf8PathBuf};
+use super::{errors::{Errors, Context}, constants::LOCK_EXTENSION};
+
+static TEMPORARY_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+// A lock is held by creating `<path>.lock` exclusively. The new contents are written to the lock
+// file, which then replaces the original path, so readers only ever see a complete file. A lock
+// dropped without being committed is removed and leaves the original untouched.
+pub struct Lock {
+  path: Utf8PathBuf,
+  lock_path: Utf8PathBuf,
+  file: Option<File>,
+}
+
+impl Lock {
+  pub fn acquire<P: AsRef<Utf8Path>>(path: P) -> Result<Self, Errors> {
+    let path = Utf8PathBuf::from(path.as_ref());
+    let lock_path = Utf8PathBuf::from(format!("{}.{}", path, LOCK_EXTENSION));
+
+    let file = match OpenOptions::new().write(true).create_new(true).open(&lock_path) {
+      Ok(file) => file,
+      Err(error) if error.kind() == ErrorKind::AlreadyExists => return Err(Errors::LockedFile(lock_path)),
+      Err(error) => return Err(error).context(|| format!("unable to create '{}'", lock_path)),
+    };
+
+    Ok(Lock { path, lock_path, file: Some(file) })
+  }
+
+  pub fn commit<B: AsRef<[u8]>>(mut self, bytes: B) -> Result<(), Errors> {
+    let mut file = self.file.take().unwrap();
+    let context = || format!("unable to write '{}'", self.path);
+
+    let result = file.write_all(bytes.as_ref())
+      .and_then(|_| file.sync_all())
+      .and_then(|_| fs::rename(&self.lock_path, &self.path))
+      .context(context);
+
+    if result.is_err() {
+      fs::remove_file(&self.lock_path).ok();
+    }
+
+    result?;
+    sync_folder(&self.path).context(context)
+  }
+
+  pub fn remove(mut self) -> Result<(), Errors> {
+    self.file.take();
+    let context = || format!("unable to remove '{}'", self.path);
+
+    if self.path.exists() {
+      fs::remove_file(&self.path).context(context)?;
+    }
+
+    fs::remove_file(&self.lock_path).context(context)?;
+
+    sync_folder(&self.path).context(context)
+  }
+}
+
+impl Drop for Lock {
+  fn drop(&mut self) {
+    if self.file.take().is_some() {
+      fs::remove_file(&self.lock_path).ok();
+    }
+  }
+}
+
+// Writes files that are never modified in place, such as objects, through a uniquely named
+// temporary file so a concurrent reader never sees a partial write.
+pub fn write_atomically<P: AsRef<Utf8Path>, B: AsRef<[u8]>>(path: P, bytes: B) -> Result<(), Errors> {
+  let path = path.as_ref();
+  let counter = TEMPORARY_COUNTER.fetch_add(1, Ordering::Relaxed);
+  let temporary_path = Utf8PathBuf::from(format!("{}.{}.{}.tmp", path, process::id(), counter));
+
+  let result = (|| {
+    let mut file = File::create(&temporary_path)?;
+
+    file.write_all(bytes.as_ref())?;
+    file.sync_all()?;
+    fs::rename(&temporary_path, path)?;
+
+    sync_folder(path)
+  })();
+
+  if result.is_err() {
+    fs::remove_file(&temporary_path).ok();
+  }
+
+  result
+}
+
+#[cfg(unix)]
+fn sync_folder(path: &Utf8Path) -> Result<(), Errors> {
+  if let Some(folder) = path.parent() {
+    File::open(folder)?.sync_all()?;
+  }
+
+  Ok(())
+}
+
+#[cfg(not(unix))]
+fn sync_folder(_path: &Utf8Path) -> Result<(), Errors> {
+  Ok(())
+}
@@ -1,27 +1,61 @@
 pub mod errors;
 pub mod constants;
 pub mod user;
+pub mod identity;
 pub mod object;
+pub mod layout;
+pub mod format;
+pub mod lock;
+pub mod diff;
+pub mod config;
+pub mod hook;
+pub mod signature;
 
-use std::{env, io::{Read, Write}, fs::{File, self}, collections::HashSet};
-use camino::{Utf8PathBuf, Utf8Path};
+use std::{env, io::{Read, Write}, fs::{File, Metadata, self}, collections::HashSet, ffi::{OsStr, OsString}, path::{Path, PathBuf}};
+use camino::Utf8PathBuf;
 use flate2::{read::{ZlibEncoder, ZlibDecoder}, Compression};
-use pathdiff::diff_utf8_paths;
-use sha1::{Sha1, Digest};
+use pathdiff::diff_paths;
 use walkdir::WalkDir;
-use self::{errors::Errors, constants::*, object::Object};
+use self::{errors::{Errors, Context}, constants::*, object::{Object, Mode}, layout::Layout, format::ObjectFormat, lock::write_atomically};
 
 pub fn locale() -> Utf8PathBuf {
   Utf8PathBuf::from(env::var(PROJECT_ENV).unwrap())
 }
 
-pub fn relative<P: AsRef<Utf8Path>>(path: P) -> Utf8PathBuf {
-  match diff_utf8_paths(path, locale()) {
+// Paths inside the repository need not be UTF-8, so they are std paths rather than the UTF-8 ones
+// used for the repository itself.
+pub fn relative<P: AsRef<Path>>(path: P) -> PathBuf {
+  match diff_paths(path, locale()) {
     Some(path) => path,
-    None => Utf8PathBuf::new()
+    None => PathBuf::new()
   }
 }
 
+// Names are stored as the bytes the file system gives them.
+#[cfg(unix)]
+pub fn name_bytes<S: AsRef<OsStr>>(name: S) -> Vec<u8> {
+  use std::os::unix::ffi::OsStrExt;
+
+  Vec::from(name.as_ref().as_bytes())
+}
+
+#[cfg(not(unix))]
+pub fn name_bytes<S: AsRef<OsStr>>(name: S) -> Vec<u8> {
+  Vec::from(name.as_ref().to_string_lossy().as_bytes())
+}
+
+#[cfg(unix)]
+pub fn name_from_bytes(bytes: &[u8]) -> OsString {
+  use std::os::unix::ffi::OsStrExt;
+
+  OsStr::from_bytes(bytes).to_os_string()
+}
+
+#[cfg(not(unix))]
+pub fn name_from_bytes(bytes: &[u8]) -> OsString {
+  OsString::from(String::from_utf8_lossy(bytes).into_owned())
+}
+
 pub fn initialize() -> Result<(), Errors> {
   let locale = locale();
   let repository = locale.join(REPOSITORY_PATH);
@@ -39,19 +73,59 @@ pub fn initialize() -> Result<(), Errors> {
   fs::create_dir_all(&repository)?;
   fs::create_dir_all(&objects)?;
   fs::create_dir_all(&branches)?;
+  fs::create_dir_all(locale.join(HOOKS_PATH))?;
 
   File::create(branches.join("master"))?;
   File::create(head)?.write_all(b"master")?;
-  File::create(config)?.write_all(b"admin admin")?;
+  File::create(config)?;
   File::create(index)?;
 
   Ok(())
 }
 
-pub fn hash<B: AsRef<[u8]>>(bytes: B) -> String {
-  let mut hasher = Sha1::new();
-  hasher.update(bytes);
-  hex::encode(hasher.finalize())
+pub fn initialize_git() -> Result<(), Errors> {
+  let locale = locale();
+  let repository = locale.join(GIT_REPOSITORY_PATH);
+
+  if repository.exists() {
+    return Err(Errors::ExistingRepository);
+  }
+
+  let objects = locale.join(GIT_OBJECTS_PATH);
+  let branches = locale.join(GIT_BRANCHES_PATH);
+  let tags = locale.join(GIT_TAGS_PATH);
+  let head = locale.join(GIT_HEAD_PATH);
+  let config = locale.join(GIT_CONFIG_PATH);
+
+  fs::create_dir_all(&repository)?;
+  fs::create_dir_all(&objects)?;
+  fs::create_dir_all(&branches)?;
+  fs::create_dir_all(&tags)?;
+  fs::create_dir_all(locale.join(GIT_HOOKS_PATH))?;
+
+  File::create(head)?.write_all(format!("{}master\n", GIT_HEAD_REFERENCE_PREFIX).as_bytes())?;
+  File::create(config)?.write_all(b"[core]\n\trepositoryformatversion = 0\n\tfilemode = true\n\tbare = false\n")?;
+
+  initialize_git_state()
+}
+
+// Repositories created by Git have no rgit state yet, so it is created on first use.
+pub fn initialize_git_state() -> Result<(), Errors> {
+  let locale = locale();
+  let state = locale.join(GIT_STATE_PATH);
+
+  if state.exists() {
+    return Ok(());
+  }
+
+  fs::create_dir_all(&state)?;
+  File::create(locale.join(GIT_STATE_CONFIG_PATH))?;
+
+  Ok(())
+}
+
+pub fn hash<B: AsRef<[u8]>>(bytes: B) -> Result<String, Errors> {
+  Ok(ObjectFormat::written()?.hash(bytes))
 }
 
 pub fn compress<B: AsRef<[u8]>>(bytes: B) -> Result<Vec<u8>, Errors> {
@@ -66,59 +140,186 @@ pub fn decompress<B: AsRef<[u8]>>(bytes: B) -> Result<Vec<u8>, Errors> {
   Ok(decompressed)
 }
 
+pub fn object_location(id: &str) -> Result<Utf8PathBuf, Errors> {
+  if !ObjectFormat::is_id(id) {
+    return Err(Errors::UnrecognisedObject(String::from(id)));
+  }
+
+  Ok(locale().join(Layout::get().objects_path()).join(&id[..2]).join(&id[2..]))
+}
+
+// `git gc` moves loose objects into pack files, which rgit cannot read.
+fn has_packs() -> bool {
+  let packs = match locale().join(GIT_PACKS_PATH).read_dir() {
+    Ok(packs) => packs,
+    Err(_) => return false,
+  };
+
+  Layout::get() == Layout::Git && packs.flatten().any(|entry| entry.path().extension().is_some_and(|extension| extension == "pack"))
+}
+
 pub fn read_object_bytes(id: &str) -> Result<Vec<u8>, Errors> {
-  let location = locale().join(OBJECTS_PATH).join(&id[..2]).join(&id[2..]);
+  read_object(id).map(|(_, bytes)| bytes)
+}
+
+pub fn read_object(id: &str) -> Result<(Object, Vec<u8>), Errors> {
+  let location = object_location(id)?;
+
+  if !location.exists() && has_packs() {
+    return Err(Errors::PackedObject(String::from(id)));
+  }
 
   if !location.exists() {
     return Err(Errors::UnrecognisedObject(String::from(id)));
   }
 
+  let context = || format!("unable to read object '{}'", id);
   let mut compressed = Vec::new();
-  File::open(location)?.read_to_end(&mut compressed)?;
+  File::open(location).and_then(|mut file| file.read_to_end(&mut compressed)).context(context)?;
 
-  let decompressed = decompress(&compressed)?;
-  Ok(Vec::from(&decompressed[4..]))
-}
+  let decompressed = decompress(&compressed).map_err(|_| Errors::BadObjectStructure).context(context)?;
+  let header_length = match Layout::get() {
+    Layout::Rgit => 4,
+    Layout::Git => decompressed.iter().position(|byte| *byte == 0).ok_or(Errors::BadObjectStructure).context(context)? + 1,
+  };
 
-pub fn write_object_bytes<B: AsRef<[u8]>>(object_type: Object, bytes: B) -> Result<String, Errors> {
-  let tag = match object_type {
-    Object::Blob => BLOB_TYPE,
-    Object::Tree => TREE_TYPE,
-    Object::Commit => COMMIT_TYPE,
+  if decompressed.len() < header_length {
+    return Err(Errors::BadObjectStructure).context(context);
+  }
+
+  let header = String::from_utf8_lossy(&decompressed[..header_length]);
+  let object_type = match header.split([' ', '\0']).next().unwrap_or("") {
+    BLOB_TYPE => Object::Blob,
+    TREE_TYPE => Object::Tree,
+    COMMIT_TYPE | GIT_COMMIT_TYPE => Object::Commit,
+    _ => return Err(Errors::BadObjectStructure).context(context),
   };
 
-  let bytes = [tag.as_bytes(), bytes.as_ref()].concat();
+  Ok((object_type, Vec::from(&decompressed[header_length..])))
+}
+
+pub fn write_object_bytes<B: AsRef<[u8]>>(object_type: Object, bytes: B) -> Result<String, Errors> {
+  let bytes = object_with_header(object_type, bytes);
   let compressed = compress(&bytes)?;
 
-  let id = hash(&bytes);
-  let location = locale().join(OBJECTS_PATH).join(&id[..2]).join(&id[2..]);
+  let id = hash(&bytes)?;
+  let location = object_location(&id)?;
 
   if !location.exists() {
-    fs::create_dir_all(location.parent().unwrap())?;
-    File::create(location)?.write_all(&compressed)?;
+    let context = || format!("unable to write object '{}'", id);
+
+    fs::create_dir_all(location.parent().unwrap()).context(context)?;
+    write_atomically(location, &compressed).context(context)?;
   }
 
   Ok(id)
 }
 
-pub fn folder_files<P: AsRef<Utf8Path>>(path: P) -> Result<HashSet<Utf8PathBuf>, Errors> {
-  let walker = WalkDir::new(locale().join(path))
+pub fn hash_object_bytes<B: AsRef<[u8]>>(object_type: Object, bytes: B) -> Result<String, Errors> {
+  hash(object_with_header(object_type, bytes))
+}
+
+pub fn folder_files<P: AsRef<Path>>(path: P) -> Result<HashSet<PathBuf>, Errors> {
+  let folder_name = Layout::get().folder_name();
+  let walker = WalkDir::new(locale().as_std_path().join(path))
     .into_iter()
-    .filter_entry(|entry| entry.file_name().to_str().unwrap_or(REPOSITORY_FOLDER_NAME) != REPOSITORY_FOLDER_NAME);
+    .filter_entry(|entry| entry.file_name() != folder_name);
   let mut file_paths = HashSet::new();
 
   for entry in walker {
     let entry = entry?;
-    let entry_path = entry.into_path();
-
-    if entry_path.is_file() {
-      if let Ok(file_path) = Utf8PathBuf::from_path_buf(entry_path) {
-        file_paths.insert(file_path);
-      } else {
-        return Err(Errors::BadPathEncoding);
-      }
+    let file_type = entry.file_type();
+
+    if file_type.is_file() || file_type.is_symlink() {
+      file_paths.insert(entry.into_path());
     }
   }
 
   Ok(file_paths)
 }
+
+pub fn path_exists<P: AsRef<Path>>(path: P) -> bool {
+  fs::symlink_metadata(path.as_ref()).is_ok()
+}
+
+pub fn is_folder<P: AsRef<Path>>(path: P) -> bool {
+  fs::symlink_metadata(path.as_ref()).map(|metadata| metadata.is_dir()).unwrap_or(false)
+}
+
+#[cfg(unix)]
+pub fn file_mode(metadata: &Metadata) -> Mode {
+  use std::os::unix::fs::PermissionsExt;
+
+  if metadata.permissions().mode() & 0o111 != 0 {
+    Mode::Executable
+  } else {
+    Mode::Regular
+  }
+}
+
+#[cfg(not(unix))]
+pub fn file_mode(_metadata: &Metadata) -> Mode {
+  Mode::Regular
+}
+
+pub fn write_file<P: AsRef<Path>, B: AsRef<[u8]>>(path: P, bytes: B, mode: Mode) -> Result<(), Errors> {
+  let path = path.as_ref();
+  let context = || format!("unable to write '{}'", path.display());
+
+  if path_exists(path) && !is_folder(path) {
+    fs::remove_file(path).context(context)?;
+  }
+
+  if mode == Mode::Symlink {
+    return create_symlink(&name_from_bytes(bytes.as_ref()), path).context(context);
+  }
+
+  File::create(path).and_then(|mut file| file.write_all(bytes.as_ref())).context(context)?;
+  set_file_mode(path, mode).context(context)
+}
+
+fn object_with_header<B: AsRef<[u8]>>(object_type: Object, bytes: B) -> Vec<u8> {
+  let header = match Layout::get() {
+    Layout::Rgit => String::from(match object_type {
+      Object::Blob => BLOB_TYPE,
+      Object::Tree => TREE_TYPE,
+      Object::Commit => COMMIT_TYPE,
+    }),
+    Layout::Git => format!("{} {}\0", object_type.as_str(), bytes.as_ref().len()),
+  };
+
+  [header.as_bytes(), bytes.as_ref()].concat()
+}
+
+#[cfg(unix)]
+fn create_symlink(target: &OsStr, path: &Path) -> Result<(), Errors> {
+  std::os::unix::fs::symlink(target, path)?;
+  Ok(())
+}
+
+#[cfg(not(unix))]
+fn create_symlink(target: &OsStr, path: &Path) -> Result<(), Errors> {
+  File::create(path)?.write_all(&name_bytes(target))?;
+  Ok(())
+}
+
+#[cfg(unix)]
+fn set_file_mode(path: &Path, mode: Mode) -> Result<(), Errors> {
+  use std::os::unix::fs::PermissionsExt;
+
+  let mut permissions = fs::metadata(path)?.permissions();
+  let bits = permissions.mode();
+
+  permissions.set_mode(match mode {
+    Mode::Executable => bits | ((bits & 0o444) >> 2),
+    _ => bits & !0o111,
+  });
+  fs::set_permissions(path, permissions)?;
+
+  Ok(())
+}
+
+#[cfg(not(unix))]
+fn set_file_mode(_path: &Path, _mode: Mode) -> Result<(), Errors> {
+  Ok(())
+}
@@ -1,11 +1,308 @@
-use super::errors::Errors;
+use super::{errors::Errors, format::ObjectFormat, constants::{TREE_MODE, GIT_TREE_MODE, GIT_BLOB_TYPE, GIT_TREE_TYPE, GIT_COMMIT_TYPE, GIT_INDEX_SIGNATURE, GIT_INDEX_VERSION}};
+
+const GIT_INDEX_HEADER_LENGTH: usize = 12;
+const GIT_INDEX_STAT_LENGTH: usize = 40;
+const GIT_INDEX_MODE_OFFSET: usize = 24;
+const GIT_INDEX_NAME_MASK: u16 = 0x0fff;
+const GIT_INDEX_STAGE_MASK: u16 = 0x3000;
+const GIT_INDEX_EXTENDED_FLAG: u16 = 0x4000;
 
 pub trait FromId {
   fn from_id(id: &str) -> Result<Self, Errors> where Self: Sized;
 }
 
+#[derive(Debug, PartialEq, Clone, Copy)]
 pub enum Object {
   Blob,
   Tree,
   Commit,
 }
+
+impl Object {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      Object::Blob => GIT_BLOB_TYPE,
+      Object::Tree => GIT_TREE_TYPE,
+      Object::Commit => GIT_COMMIT_TYPE,
+    }
+  }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Mode {
+  Regular,
+  Executable,
+  Symlink,
+}
+
+impl Mode {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      Mode::Regular => "100644",
+      Mode::Executable => "100755",
+      Mode::Symlink => "120000",
+    }
+  }
+
+  pub fn parse(mode: &str) -> Result<Self, Errors> {
+    match mode {
+      "100644" | "100664" => Ok(Mode::Regular),
+      "100755" => Ok(Mode::Executable),
+      "120000" => Ok(Mode::Symlink),
+      _ => Err(Errors::BadObjectStructure),
+    }
+  }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Entry {
+  pub mode: String,
+  pub name: Vec<u8>,
+  pub id: String,
+}
+
+impl Entry {
+  pub fn new<B: AsRef<[u8]>>(mode: &str, name: B, id: &str) -> Self {
+    Entry {
+      mode: String::from(mode),
+      name: Vec::from(name.as_ref()),
+      id: String::from(id),
+    }
+  }
+}
+
+// Entries are stored as `<mode> <name>\0<id>\n`, so names may contain spaces,
+// newlines and any other byte except NUL.
+pub fn pack_entries(entries: &mut [Entry]) -> Vec<u8> {
+  entries.sort_by(|a, b| a.name.cmp(&b.name));
+
+  let mut bytes = Vec::new();
+
+  for entry in entries.iter() {
+    bytes.extend_from_slice(entry.mode.as_bytes());
+    bytes.push(b' ');
+    bytes.extend_from_slice(&entry.name);
+    bytes.push(0);
+    bytes.extend_from_slice(entry.id.as_bytes());
+    bytes.push(b'\n');
+  }
+
+  bytes
+}
+
+pub fn unpack_entries(bytes: &[u8]) -> Result<Vec<Entry>, Errors> {
+  let mut entries = Vec::new();
+  let mut rest = bytes;
+
+  while !rest.is_empty() {
+    let (mode, after_mode) = split_at_byte(rest, b' ')?;
+    let (name, after_name) = split_at_byte(after_mode, 0)?;
+    let (id, after_id) = split_at_byte(after_name, b'\n')?;
+
+    entries.push(
+      Entry {
+        mode: String::from_utf8(Vec::from(mode))?,
+        name: Vec::from(name),
+        id: String::from_utf8(Vec::from(id))?,
+      }
+    );
+
+    rest = after_id;
+  }
+
+  Ok(entries)
+}
+
+// Git stores entries as `<mode> <name>\0<raw id>`, sorting trees as if their names ended in a slash.
+pub fn pack_git_entries(entries: &mut [Entry]) -> Result<Vec<u8>, Errors> {
+  entries.sort_by_key(git_sort_key);
+
+  let mut bytes = Vec::new();
+
+  for entry in entries.iter() {
+    let mode = if entry.mode == TREE_MODE { GIT_TREE_MODE } else { &entry.mode };
+
+    bytes.extend_from_slice(mode.as_bytes());
+    bytes.push(b' ');
+    bytes.extend_from_slice(&entry.name);
+    bytes.push(0);
+    bytes.extend_from_slice(&hex::decode(&entry.id).map_err(|_| Errors::BadObjectStructure)?);
+  }
+
+  Ok(bytes)
+}
+
+pub fn unpack_git_entries(bytes: &[u8], format: ObjectFormat) -> Result<Vec<Entry>, Errors> {
+  let id_length = format.byte_length();
+  let mut entries = Vec::new();
+  let mut rest = bytes;
+
+  while !rest.is_empty() {
+    let (mode, after_mode) = split_at_byte(rest, b' ')?;
+    let (name, after_name) = split_at_byte(after_mode, 0)?;
+
+    if after_name.len() < id_length {
+      return Err(Errors::BadObjectStructure);
+    }
+
+    let mode = String::from_utf8(Vec::from(mode))?;
+
+    entries.push(
+      Entry {
+        mode: if mode == GIT_TREE_MODE { String::from(TREE_MODE) } else { mode },
+        name: Vec::from(name),
+        id: hex::encode(&after_name[..id_length]),
+      }
+    );
+
+    rest = &after_name[id_length..];
+  }
+
+  Ok(entries)
+}
+
+// Git's index lists every tracked file between a `DIRC <version> <count>` header and a checksum of
+// everything before it. Entries hold stat data, the mode, the raw id, flags and the name, padded
+// with NULs to a multiple of eight bytes. Rgit writes the stat data as zeros, which makes Git compare
+// the contents of a file the first time it looks at it.
+pub fn pack_git_index(entries: &mut [Entry], format: ObjectFormat) -> Result<Vec<u8>, Errors> {
+  entries.sort_by(|a, b| a.name.cmp(&b.name));
+
+  let mut bytes = Vec::from(GIT_INDEX_SIGNATURE);
+  bytes.extend_from_slice(&GIT_INDEX_VERSION.to_be_bytes());
+  bytes.extend_from_slice(&(entries.len() as u32).to_be_bytes());
+
+  for entry in entries.iter() {
+    let start = bytes.len();
+    let mode = u32::from_str_radix(&entry.mode, 8).map_err(|_| Errors::BadObjectStructure)?;
+
+    bytes.extend_from_slice(&[0; GIT_INDEX_MODE_OFFSET]);
+    bytes.extend_from_slice(&mode.to_be_bytes());
+    bytes.extend_from_slice(&[0; GIT_INDEX_STAT_LENGTH - GIT_INDEX_MODE_OFFSET - 4]);
+    bytes.extend_from_slice(&hex::decode(&entry.id).map_err(|_| Errors::BadObjectStructure)?);
+    bytes.extend_from_slice(&(entry.name.len().min(GIT_INDEX_NAME_MASK as usize) as u16).to_be_bytes());
+    bytes.extend_from_slice(&entry.name);
+    bytes.resize(start + padded_length(bytes.len() - start), 0);
+  }
+
+  let checksum = hex::decode(format.hash(&bytes)).map_err(|_| Errors::BadObjectStructure)?;
+  bytes.extend_from_slice(&checksum);
+
+  Ok(bytes)
+}
+
+// Reads versions 2 to 4, where version 4 stores each name as the number of bytes to drop from the
+// end of the previous name followed by the bytes to append. Extensions after the entries are skipped.
+pub fn unpack_git_index(bytes: &[u8], format: ObjectFormat) -> Result<Vec<Entry>, Errors> {
+  let id_length = format.byte_length();
+
+  if bytes.len() < GIT_INDEX_HEADER_LENGTH + id_length || &bytes[..4] != GIT_INDEX_SIGNATURE {
+    return Err(Errors::BadObjectStructure);
+  }
+
+  let (content, checksum) = bytes.split_at(bytes.len() - id_length);
+
+  if checksum.iter().any(|byte| *byte != 0) && hex::encode(checksum) != format.hash(content) {
+    return Err(Errors::BadObjectStructure);
+  }
+
+  let version = read_u32(content, 4)?;
+  let count = read_u32(content, 8)?;
+  let mut entries = Vec::new();
+  let mut name = Vec::new();
+  let mut position = GIT_INDEX_HEADER_LENGTH;
+
+  if !(2..=4).contains(&version) {
+    return Err(Errors::BadObjectStructure);
+  }
+
+  for _ in 0..count {
+    let flags_position = position + GIT_INDEX_STAT_LENGTH + id_length;
+    let mode = read_u32(content, position + GIT_INDEX_MODE_OFFSET)?;
+    let id = hex::encode(content.get(flags_position - id_length..flags_position).ok_or(Errors::BadObjectStructure)?);
+    let flags = read_u16(content, flags_position)?;
+    let mut name_position = flags_position + 2;
+
+    if flags & GIT_INDEX_EXTENDED_FLAG != 0 {
+      name_position += 2;
+    }
+
+    if version == 4 {
+      let (dropped, after_offset) = read_offset(content, name_position)?;
+      let (suffix, rest) = split_at_byte(content.get(after_offset..).ok_or(Errors::BadObjectStructure)?, 0)?;
+
+      name.truncate(name.len().checked_sub(dropped).ok_or(Errors::BadObjectStructure)?);
+      name.extend_from_slice(suffix);
+      position = content.len() - rest.len();
+    } else {
+      let (suffix, _) = split_at_byte(content.get(name_position..).ok_or(Errors::BadObjectStructure)?, 0)?;
+
+      name = Vec::from(suffix);
+      position += padded_length(name_position - position + name.len());
+    }
+
+    if flags & GIT_INDEX_STAGE_MASK != 0 {
+      return Err(Errors::UnmergedPath(String::from_utf8_lossy(&name).into_owned()));
+    }
+
+    entries.push(
+      Entry {
+        mode: format!("{:o}", mode),
+        name: name.clone(),
+        id,
+      }
+    );
+  }
+
+  Ok(entries)
+}
+
+pub fn is_packed_entries(bytes: &[u8]) -> bool {
+  bytes.contains(&0)
+}
+
+fn git_sort_key(entry: &Entry) -> Vec<u8> {
+  let mut key = entry.name.clone();
+
+  if entry.mode == TREE_MODE {
+    key.push(b'/');
+  }
+
+  key
+}
+
+// Names end in one to eight NULs.
+fn padded_length(length: usize) -> usize {
+  (length + 8) / 8 * 8
+}
+
+fn read_u32(bytes: &[u8], position: usize) -> Result<u32, Errors> {
+  let bytes = bytes.get(position..position + 4).ok_or(Errors::BadObjectStructure)?;
+  Ok(u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+fn read_u16(bytes: &[u8], position: usize) -> Result<u16, Errors> {
+  let bytes = bytes.get(position..position + 2).ok_or(Errors::BadObjectStructure)?;
+  Ok(u16::from_be_bytes([bytes[0], bytes[1]]))
+}
+
+// Git's variable length offsets add one before every shift, so that each length has one encoding.
+fn read_offset(bytes: &[u8], mut position: usize) -> Result<(usize, usize), Errors> {
+  let mut byte = *bytes.get(position).ok_or(Errors::BadObjectStructure)?;
+  let mut offset = (byte & 0x7f) as usize;
+
+  while byte & 0x80 != 0 {
+    position += 1;
+    byte = *bytes.get(position).ok_or(Errors::BadObjectStructure)?;
+    offset = offset.checked_add(1).and_then(|offset| offset.checked_mul(0x80)).ok_or(Errors::BadObjectStructure)? | (byte & 0x7f) as usize;
+  }
+
+  Ok((offset, position + 1))
+}
+
+fn split_at_byte(bytes: &[u8], separator: u8) -> Result<(&[u8], &[u8]), Errors> {
+  match bytes.iter().position(|byte| *byte == separator) {
+    Some(position) => Ok((&bytes[..position], &bytes[position + 1..])),
+    None => Err(Errors::BadObjectStructure),
+  }
+}
@@ -0,0 +1,106 @@
+use std::{env, fs::{self, OpenOptions}, io::{self, Write}};
+use camino::{Utf8Path, Utf8PathBuf};
+use ed25519_dalek::{self as ed25519, Signer};
+use super::{locale, errors::{Errors, Context}, config::Config};
+
+pub const SIGNING_KEY_KEY: &str = "user.signingkey";
+pub const TRUSTED_KEYS_KEY: &str = "signing.trustedkeys";
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum SignatureStatus {
+  Unsigned,
+  Good(String),
+  Bad(String),
+}
+
+// Signing keys are files holding an ed25519 secret key in hex, named by `user.signingkey`.
+pub struct SigningKey {
+  key: ed25519::SigningKey,
+}
+
+impl SigningKey {
+  pub fn load() -> Result<Self, Errors> {
+    let location = configured_path(SIGNING_KEY_KEY)?;
+    let context = || format!("unable to read signing key '{}'", location);
+    let text = fs::read_to_string(&location).context(context)?;
+    let bytes = decode::<{ ed25519::SECRET_KEY_LENGTH }>(text.trim()).ok_or(Errors::BadObjectStructure).context(context)?;
+
+    Ok(SigningKey { key: ed25519::SigningKey::from_bytes(&bytes) })
+  }
+
+  pub fn generate() -> Result<Self, Errors> {
+    let location = configured_path(SIGNING_KEY_KEY)?;
+
+    if location.exists() {
+      return Err(Errors::BadArguments(format!("signing key '{}' already exists", location)));
+    }
+
+    let mut bytes = [0; ed25519::SECRET_KEY_LENGTH];
+    getrandom::getrandom(&mut bytes).map_err(|error| Errors::BadArguments(format!("unable to generate a key ({})", error)))?;
+    create_private_file(&location, format!("{}\n", hex::encode(bytes))).context(|| format!("unable to write signing key '{}'", location))?;
+
+    Ok(SigningKey { key: ed25519::SigningKey::from_bytes(&bytes) })
+  }
+
+  pub fn sign<B: AsRef<[u8]>>(&self, bytes: B) -> String {
+    hex::encode(self.key.sign(bytes.as_ref()).to_bytes())
+  }
+
+  pub fn public_key(&self) -> String {
+    hex::encode(self.key.verifying_key().to_bytes())
+  }
+}
+
+// Trusted keys are listed one per line as `<email> <public key in hex>` in the file named by
+// `signing.trustedkeys`. A signature is good only when it verifies with a key trusted for `email`,
+// which makes it prove authorship rather than just integrity.
+pub fn verify<B: AsRef<[u8]>>(bytes: B, signature: &str, email: &str) -> Result<SignatureStatus, Errors> {
+  let location = configured_path(TRUSTED_KEYS_KEY)?;
+  let text = fs::read_to_string(&location).context(|| format!("unable to read trusted keys '{}'", location))?;
+  let signature = match decode::<{ ed25519::SIGNATURE_LENGTH }>(signature) {
+    Some(signature) => ed25519::Signature::from_bytes(&signature),
+    None => return Ok(SignatureStatus::Bad(String::from(email))),
+  };
+
+  for line in text.lines().map(str::trim).filter(|line| !line.is_empty() && !line.starts_with('#')) {
+    let (principal, public_key) = line.split_once(char::is_whitespace).ok_or(Errors::BadObjectStructure).context(|| format!("unable to parse trusted keys '{}'", location))?;
+
+    let public_key = match decode::<{ ed25519::PUBLIC_KEY_LENGTH }>(public_key.trim()).map(|bytes| ed25519::VerifyingKey::from_bytes(&bytes)) {
+      Some(Ok(public_key)) if principal == email => public_key,
+      _ => continue,
+    };
+
+    if public_key.verify_strict(bytes.as_ref(), &signature).is_ok() {
+      return Ok(SignatureStatus::Good(String::from(email)));
+    }
+  }
+
+  Ok(SignatureStatus::Bad(String::from(email)))
+}
+
+// Paths starting with `~/` are relative to the home folder and other relative paths to the repository.
+fn configured_path(key: &str) -> Result<Utf8PathBuf, Errors> {
+  let config = Config::load()?;
+  let path = config.get(key).ok_or_else(|| Errors::BadArguments(format!("no file configured (set {})", key)))?;
+
+  Ok(match (path.strip_prefix("~/"), env::var("HOME")) {
+    (Some(path), Ok(home)) => Utf8PathBuf::from(home).join(path),
+    _ => locale().join(path),
+  })
+}
+
+fn decode<const LENGTH: usize>(text: &str) -> Option<[u8; LENGTH]> {
+  hex::decode(text).ok()?.try_into().ok()
+}
+
+#[cfg(unix)]
+fn create_private_file(path: &Utf8Path, bytes: String) -> io::Result<()> {
+  use std::os::unix::fs::OpenOptionsExt;
+
+  OpenOptions::new().write(true).create_new(true).mode(0o600).open(path)?.write_all(bytes.as_bytes())
+}
+
+#[cfg(not(unix))]
+fn create_private_file(path: &Utf8Path, bytes: String) -> io::Result<()> {
+  OpenOptions::new().write(true).create_new(true).open(path)?.write_all(bytes.as_bytes())
+}
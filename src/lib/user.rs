@@ -1,7 +1,10 @@
-use std::{fs::{File, OpenOptions}, io::{Read, Write}};
 use getset::Getters;
 use crate::lib::errors::Errors;
-use super::{locale, constants::CONFIG_PATH};
+use super::config::{Config, Scope};
+
+pub const USERNAME_KEY: &str = "user.name";
+pub const EMAIL_KEY: &str = "user.email";
+const DEFAULT_USER: &str = "admin";
 
 #[derive(Getters, Debug, PartialEq, Clone)]
 pub struct User {
@@ -22,51 +25,24 @@ impl User {
     )
   }
 
+  // Falls back to `admin` for repositories nobody has configured.
   pub fn get() -> Result<Self, Errors> {
-    let location = locale().join(CONFIG_PATH);
-    let mut config = String::new();
-
-    File::open(location)?.read_to_string(&mut config)?;
+    let config = Config::load()?;
+    let value = |key: &str| config.get(key).map_or(String::from(DEFAULT_USER), String::clone);
 
-    if let [username, email] = config.split_whitespace().collect::<Vec<&str>>()[..] {
-      Ok(
-        User {
-          username: String::from(username),
-          email: String::from(email),
-        }
-      )
-    } else {
-      return Err(Errors::BadObjectStructure);
-    }
+    Ok(
+      User {
+        username: value(USERNAME_KEY),
+        email: value(EMAIL_KEY),
+      }
+    )
   }
 
   pub fn set_username(username: &str) -> Result<(), Errors> {
-    let user = User::get()?;
-    let location = locale().join(CONFIG_PATH);
-    let config = format!("{} {}", username, user.email());
-
-    OpenOptions::new()
-      .write(true)
-      .truncate(true)
-      .create(true)
-      .open(location)?
-      .write_all(config.as_bytes())?;
-
-    Ok(())
+    Config::set(Scope::Repository, USERNAME_KEY, username)
   }
 
   pub fn set_email(email: &str) -> Result<(), Errors> {
-    let user = User::get()?;
-    let location = locale().join(CONFIG_PATH);
-    let config = format!("{} {}", user.username(), email);
-
-    OpenOptions::new()
-      .write(true)
-      .truncate(true)
-      .create(true)
-      .open(location)?
-      .write_all(config.as_bytes())?;
-
-    Ok(())
+    Config::set(Scope::Repository, EMAIL_KEY, email)
   }
 }
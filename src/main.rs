@@ -9,6 +9,6 @@ mod tests;
 
 fn main() {
   if let Err(error) = cli::Interface::run() {
-    lib::errors::ErrorsInterface::handle(error);
+    lib::errors::ErrorsInterface::handle(error)
   }
 }
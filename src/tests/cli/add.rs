@@ -1,5 +1,6 @@
+use std::{fs, path::Path};
 use serial_test::serial;
-use crate::{tests::run_acceptance, lib::locale};
+use crate::{tests::run_acceptance, core::index::{Index, Stage}, lib::{locale, errors::USER_ERROR_CODE, config::{Config, Scope}, read_object_bytes}};
 
 #[test]
 #[serial]
@@ -21,17 +22,83 @@ fn add_does_not_handle_options() {
 #[serial]
 fn add_handles_multiple_arguments() {
   run_acceptance("add", |command| {
+    fs::create_dir_all(locale().join("a/a")).unwrap();
+    fs::write(locale().join("a/a/a"), "a\n").unwrap();
+
     command.args(["a/a", "a/a/a"]).assert().success();
   });
 }
 
+#[test]
+#[serial]
+fn add_given_missing_path_among_multiple_arguments_fails() {
+  run_acceptance("add", |command| {
+    fs::create_dir_all(locale().join("a")).unwrap();
+    fs::write(locale().join("a/a"), "a\n").unwrap();
+
+    command.args(["a/a", "a/b"]).assert().code(USER_ERROR_CODE);
+  });
+}
+
+#[test]
+#[serial]
+#[cfg(unix)]
+fn add_given_non_utf8_path_stages_the_file() {
+  use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+
+  run_acceptance("add", |command| {
+    let name = OsStr::from_bytes(b"caf\xe9");
+    fs::write(locale().as_std_path().join(name), "a\n").unwrap();
+
+    command.arg(name).assert().success();
+
+    assert!(Index::get().unwrap().staged_paths().contains_key(Path::new(name)));
+  });
+}
+
 #[test]
 #[serial]
 fn add_outputs_unrecognized_path_on_no_matching_path() {
   run_acceptance("add", |command| {
-    let output = command.args(["d"]).output().unwrap().stdout;
+    let output = command.args(["d"]).output().unwrap().stderr;
     let expected_output = format!("error: path '{}' did not match any files\n", locale().join("d"));
 
     assert_eq!(output, expected_output.as_bytes());
   });
 }
+
+#[test]
+#[serial]
+fn add_with_patch_option_stages_the_picked_hunks() {
+  run_acceptance("add", |command| {
+    fs::write(locale().join("file"), "1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n").unwrap();
+    Index::add(&[locale().join("file")]).unwrap();
+    fs::write(locale().join("file"), "one\n2\n3\n4\n5\n6\n7\n8\n9\nten\n").unwrap();
+
+    command.arg("-p").write_stdin("n\ny\n").assert().success();
+
+    assert_eq!(staged_text("file"), "1\n2\n3\n4\n5\n6\n7\n8\n9\nten\n");
+  });
+}
+
+#[test]
+#[serial]
+fn add_with_patch_option_stages_the_edited_hunk() {
+  run_acceptance("add", |command| {
+    fs::write(locale().join("file"), "a\nb").unwrap();
+    Index::add(&[locale().join("file")]).unwrap();
+    fs::write(locale().join("file"), "a\nc").unwrap();
+    Config::set(Scope::Repository, "core.editor", "sed -i s/^+c/+edited/").unwrap();
+
+    command.args(["-p", "file"]).write_stdin("e\n").assert().success();
+
+    assert_eq!(staged_text("file"), "a\nedited");
+  });
+}
+
+fn staged_text(path: &str) -> String {
+  match Index::get().unwrap().staged_paths().get(Path::new(path)) {
+    Some(Stage::Blob(blob)) => String::from_utf8(read_object_bytes(blob.id()).unwrap()).unwrap(),
+    _ => panic!("'{}' is not staged", path),
+  }
+}
@@ -0,0 +1,58 @@
+use serial_test::serial;
+use crate::{tests::{run_acceptance, factory::commit}, core::branch::Branch, lib::{config::{Config, Scope}, errors::USER_ERROR_CODE}};
+
+#[test]
+#[serial]
+fn alias_expands_into_the_command_with_its_arguments() {
+  run_acceptance("last", |command| {
+    let commit = commit();
+    Branch::set("master", commit.id()).unwrap();
+    Config::set(Scope::Global, "alias.last", "log -n 1 --format '%H %s'").unwrap();
+
+    command.assert().success().stdout(format!("{} {}\n", commit.id(), commit.message()));
+  });
+}
+
+#[test]
+#[serial]
+fn alias_may_name_another_alias() {
+  run_acceptance("ids", |command| {
+    let commit = commit();
+    Branch::set("master", commit.id()).unwrap();
+    Config::set(Scope::Global, "alias.ids", "rp HEAD").unwrap();
+    Config::set(Scope::Repository, "alias.rp", "rev-parse").unwrap();
+
+    command.arg("HEAD:").assert().success().stdout(format!("{}\n{}\n", commit.id(), commit.tree_id()));
+  });
+}
+
+#[test]
+#[serial]
+fn alias_starting_with_exclamation_mark_runs_a_shell_command() {
+  run_acceptance("greet", |command| {
+    Config::set(Scope::Repository, "alias.greet", "!echo hello").unwrap();
+
+    command.arg("world").assert().success().stdout("hello world\n");
+  });
+}
+
+#[test]
+#[serial]
+fn alias_does_not_override_commands() {
+  run_acceptance("status", |command| {
+    Config::set(Scope::Repository, "alias.status", "!echo hello").unwrap();
+
+    command.assert().success().stdout("On branch master\nnothing to commit, working tree clean\n");
+  });
+}
+
+#[test]
+#[serial]
+fn alias_expanding_into_itself_exits_with_user_error_code() {
+  run_acceptance("a", |command| {
+    Config::set(Scope::Repository, "alias.a", "b").unwrap();
+    Config::set(Scope::Repository, "alias.b", "a --oneline").unwrap();
+
+    command.assert().code(USER_ERROR_CODE);
+  });
+}
@@ -0,0 +1,136 @@
+use std::fs;
+use serial_test::serial;
+use crate::{tests::{run_acceptance, factory::{commit, blob, TREE_PATHS}}, core::{bisect::{Bisect, Term}, branch::Branch, commit::Commit, head::{Head, Reference}, tree::{Tree, Node}}, cli::clear_project, lib::{locale, errors::REPOSITORY_ERROR_CODE}};
+
+// Points master at a line of ten commits sharing a tree, checks it out and returns them, oldest first.
+fn history() -> Vec<Commit> {
+  let mut commits = vec![commit()];
+
+  for index in 1..10 {
+    let parent = commits.last().unwrap();
+    commits.push(Commit::new(Some(parent.id()), parent.tree_id(), &format!("commit {}", index)).unwrap());
+  }
+
+  Branch::set("master", commits[9].id()).unwrap();
+  commits[9].tree().unwrap().unpack(locale()).unwrap();
+  commits
+}
+
+#[test]
+#[serial]
+fn bisect_start_given_bad_and_good_commits_switches_to_the_middle_one() {
+  run_acceptance("bisect", |command| {
+    let commits = history();
+
+    command.args(["start", "HEAD", "HEAD~9"]).assert().success();
+    assert_eq!(Head::get().unwrap().reference(), &Reference::Commit(commits[4].clone()));
+  });
+}
+
+#[test]
+#[serial]
+fn bisect_good_switches_to_the_middle_of_the_newer_half() {
+  run_acceptance("bisect", |command| {
+    let commits = history();
+    let mut bisect = Bisect::start().unwrap();
+    bisect.mark(Term::Bad, &commits[9]).unwrap();
+    bisect.mark(Term::Good, &commits[0]).unwrap();
+
+    command.args(["good", commits[4].id()]).assert().success();
+    assert_eq!(Head::get().unwrap().reference(), &Reference::Commit(commits[6].clone()));
+  });
+}
+
+#[test]
+#[serial]
+fn bisect_run_finds_the_first_bad_commit() {
+  run_acceptance("bisect", |command| {
+    let commits = history();
+    let good_ids = commits[..6].iter().map(|commit| format!("{}\n", commit.id())).collect::<String>();
+    fs::write(locale().join(".rgit/good-ids"), good_ids).unwrap();
+    let mut bisect = Bisect::start().unwrap();
+    bisect.mark(Term::Bad, &commits[9]).unwrap();
+    bisect.mark(Term::Good, &commits[0]).unwrap();
+
+    let output = command.args(["run", "sh", "-c", "grep -q \"$(cut -d: -f2 .rgit/HEAD)\" .rgit/good-ids"]).output().unwrap();
+
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stdout).unwrap().contains(&format!("{} is the first bad commit", commits[6].id())));
+  });
+}
+
+#[test]
+#[serial]
+fn bisect_reset_switches_back_to_the_starting_branch() {
+  run_acceptance("bisect", |command| {
+    let commits = history();
+    Bisect::start().unwrap();
+    Head::set(&Reference::Commit(commits[3].clone())).unwrap();
+
+    command.arg("reset").assert().success();
+    assert_eq!(Head::get().unwrap().reference(), &Reference::Branch(Branch::get("master").unwrap()));
+  });
+}
+
+#[test]
+#[serial]
+fn bisect_log_without_start_returns_repository_error() {
+  run_acceptance("bisect", |command| {
+    command.arg("log").assert().code(REPOSITORY_ERROR_CODE);
+  });
+}
+
+#[test]
+#[serial]
+fn bisect_start_given_untracked_file_keeps_it_and_starts() {
+  run_acceptance("bisect", |command| {
+    let commits = history();
+    fs::write(locale().join("notes"), "notes").unwrap();
+
+    command.args(["start", "HEAD", "HEAD~9"]).assert().success();
+    assert_eq!(Head::get().unwrap().reference(), &Reference::Commit(commits[4].clone()));
+    assert_eq!(fs::read_to_string(locale().join("notes")).unwrap(), "notes");
+  });
+}
+
+#[test]
+#[serial]
+fn bisect_reset_given_untracked_file_the_start_holds_keeps_it_and_the_session() {
+  run_acceptance("bisect", |command| {
+    history();
+    let bisect = Bisect::start().unwrap();
+    let mut tree = Tree::new();
+    tree.insert("other", Node::Blob(blob())).unwrap();
+    tree.pack().unwrap();
+    let other = Commit::new(None, tree.id(), "other").unwrap();
+    Head::set(&Reference::Commit(other.clone())).unwrap();
+    clear_project(&[]).unwrap();
+    tree.unpack(locale()).unwrap();
+    let path = locale().as_std_path().join(TREE_PATHS[0]);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(&path, "untracked").unwrap();
+
+    command.arg("reset").assert().code(REPOSITORY_ERROR_CODE);
+    assert_eq!(Bisect::get().unwrap(), bisect);
+    assert_eq!(Head::get().unwrap().reference(), &Reference::Commit(other));
+    assert_eq!(fs::read_to_string(&path).unwrap(), "untracked");
+  });
+}
+
+#[test]
+#[serial]
+fn bisect_good_given_modified_file_keeps_it_and_marks_nothing() {
+  run_acceptance("bisect", |command| {
+    let commits = history();
+    let mut bisect = Bisect::start().unwrap();
+    bisect.mark(Term::Bad, &commits[9]).unwrap();
+    bisect.mark(Term::Good, &commits[0]).unwrap();
+    let (path, _) = commits[9].tree().unwrap().blobs().into_iter().next().unwrap();
+    fs::write(locale().as_std_path().join(&path), "changed").unwrap();
+
+    command.args(["good", commits[4].id()]).assert().code(REPOSITORY_ERROR_CODE);
+    assert_eq!(Bisect::get().unwrap(), bisect);
+    assert_eq!(Head::get().unwrap().reference(), &Reference::Branch(Branch::get("master").unwrap()));
+    assert_eq!(fs::read_to_string(locale().as_std_path().join(&path)).unwrap(), "changed");
+  });
+}
@@ -0,0 +1,134 @@
+use std::fs;
+use chrono::{TimeZone, Utc};
+use serial_test::serial;
+use crate::{tests::run_acceptance, core::{commit::Commit, branch::Branch, blob::Blob, tree::{Tree, Node}}, lib::{user::User, identity::Identity, errors::USER_ERROR_CODE, constants::SHORT_ID_LENGTH, object_location}};
+
+// Commits `notes` three times: Jane writes "a b c", John changes "b" and Jane appends "d".
+fn history() -> Vec<Commit> {
+  let versions = [("Jane", "a\nb\nc\n"), ("John", "a\nB\nc\n"), ("Jane", "a\nB\nc\nd\n")];
+  let mut commits: Vec<Commit> = Vec::new();
+
+  for (day, (name, text)) in versions.iter().enumerate() {
+    let mut tree = Tree::new();
+    tree.insert("notes", Node::Blob(Blob::new(text).unwrap())).unwrap();
+
+    let user = User::new(name, &format!("{}@mail.com", name.to_lowercase())).unwrap();
+    let date = Utc.ymd(2022, 1, day as u32 + 1).and_hms(12, 0, 0);
+    let parent_id = commits.last().map(|commit| commit.id().as_str());
+    let commit = Commit::with_author(parent_id, tree.pack().unwrap(), &format!("version {}", day + 1), Identity::new(&user, date.into())).unwrap();
+
+    commits.push(commit);
+  }
+
+  Branch::set("master", commits[2].id()).unwrap();
+  commits
+}
+
+fn output(command: &mut assert_cmd::Command) -> String {
+  String::from_utf8(command.output().unwrap().stdout).unwrap()
+}
+
+#[test]
+#[serial]
+fn blame_does_not_handle_empty_arguments() {
+  run_acceptance("blame", |command| {
+    command.assert().failure();
+  });
+}
+
+#[test]
+#[serial]
+fn blame_attributes_each_line_to_the_commit_that_changed_it() {
+  run_acceptance("blame", |command| {
+    let commits = history();
+    let expected_output = format!("\
+{} (Jane 2022-01-01 12:00:00 +0000 1) a
+{} (John 2022-01-02 12:00:00 +0000 2) B
+{} (Jane 2022-01-01 12:00:00 +0000 3) c
+{} (Jane 2022-01-03 12:00:00 +0000 4) d
+", &commits[0].id()[..SHORT_ID_LENGTH], &commits[1].id()[..SHORT_ID_LENGTH], &commits[0].id()[..SHORT_ID_LENGTH], &commits[2].id()[..SHORT_ID_LENGTH]);
+
+    assert_eq!(output(command.arg("notes")), expected_output);
+  });
+}
+
+#[test]
+#[serial]
+fn blame_given_missing_parent_exits_with_an_error() {
+  run_acceptance("blame", |command| {
+    let commits = history();
+    fs::remove_file(object_location(commits[1].id()).unwrap()).unwrap();
+
+    let stderr = String::from_utf8(command.arg("notes").assert().code(USER_ERROR_CODE).get_output().stderr.clone()).unwrap();
+
+    assert!(stderr.contains(&format!("unable to read parent of commit '{}'", commits[2].id())));
+  });
+}
+
+#[test]
+#[serial]
+fn blame_given_revision_annotates_the_file_at_that_commit() {
+  run_acceptance("blame", |command| {
+    let commits = history();
+
+    assert_eq!(output(command.args(["notes", "HEAD~2", "-L", "2,+1"])), format!("{} (Jane 2022-01-01 12:00:00 +0000 2) b\n", &commits[0].id()[..SHORT_ID_LENGTH]));
+  });
+}
+
+#[test]
+#[serial]
+fn blame_with_porcelain_option_describes_each_commit_once() {
+  run_acceptance("blame", |command| {
+    let commits = history();
+    let expected_output = format!("\
+{first} 1 1 1
+author Jane
+author-mail <jane@mail.com>
+author-time 1641038400
+author-tz +0000
+committer Jane
+committer-mail <jane@mail.com>
+committer-time 1641038400
+committer-tz +0000
+summary version 1
+filename notes
+\ta
+{second} 2 2 1
+author John
+author-mail <john@mail.com>
+author-time 1641124800
+author-tz +0000
+committer John
+committer-mail <john@mail.com>
+committer-time 1641124800
+committer-tz +0000
+summary version 2
+filename notes
+\tB
+{first} 3 3 1
+\tc
+", first = commits[0].id(), second = commits[1].id());
+
+    assert_eq!(output(command.args(["notes", "-L", "1,3", "--porcelain"])), expected_output);
+  });
+}
+
+#[test]
+#[serial]
+fn blame_given_invalid_range_exits_with_user_error_code() {
+  run_acceptance("blame", |command| {
+    history();
+
+    command.args(["notes", "-L", "3,9"]).assert().code(USER_ERROR_CODE);
+  });
+}
+
+#[test]
+#[serial]
+fn blame_given_overflowing_range_exits_with_user_error_code() {
+  run_acceptance("blame", |command| {
+    history();
+
+    command.args(["notes", "-L", &format!("2,+{}", usize::MAX)]).assert().code(USER_ERROR_CODE);
+  });
+}
@@ -0,0 +1,70 @@
+use serial_test::serial;
+use crate::{tests::{run_acceptance, factory::commit}, core::{branch::Branch, commit::Commit, head::{Head, Reference}}, lib::errors::{USER_ERROR_CODE, REPOSITORY_ERROR_CODE}};
+
+// Points master at a child of `commit()` and returns both.
+fn history() -> (Commit, Commit) {
+  let parent = commit();
+  let child = Commit::new(Some(parent.id()), parent.tree_id(), "child\n\nbody").unwrap();
+  Branch::set("master", child.id()).unwrap();
+
+  (parent, child)
+}
+
+#[test]
+#[serial]
+fn branch_given_name_and_start_point_creates_the_branch_there() {
+  run_acceptance("branch", |command| {
+    let (parent, _) = history();
+
+    command.args(["feature", "HEAD~1"]).assert().success();
+    assert_eq!(Branch::get("feature").unwrap().commit_id().as_ref(), Some(parent.id()));
+  });
+}
+
+#[test]
+#[serial]
+fn branch_with_verbose_option_lists_the_tip_commits() {
+  run_acceptance("branch", |command| {
+    let (parent, child) = history();
+    Branch::new("feature", Some(parent.id())).unwrap();
+
+    command.arg("-v").assert().success().stdout(format!("   feature {} {}\n-> master  {} child\n", &parent.id()[..7], parent.message(), &child.id()[..7]));
+  });
+}
+
+#[test]
+#[serial]
+fn branch_with_delete_option_refuses_unmerged_branches() {
+  run_acceptance("branch", |command| {
+    let (_, child) = history();
+    let unmerged = Commit::new(Some(child.id()), child.tree_id(), "unmerged").unwrap();
+    Branch::new("feature", Some(unmerged.id())).unwrap();
+
+    command.args(["-d", "feature"]).assert().code(REPOSITORY_ERROR_CODE);
+    assert!(Branch::get("feature").is_ok());
+  });
+}
+
+#[test]
+#[serial]
+fn branch_with_force_delete_option_deletes_unmerged_branches() {
+  run_acceptance("branch", |command| {
+    let (_, child) = history();
+    let unmerged = Commit::new(Some(child.id()), child.tree_id(), "unmerged").unwrap();
+    Branch::new("feature", Some(unmerged.id())).unwrap();
+
+    command.args(["-D", "feature"]).assert().success().stdout("Deleted branch feature\n");
+    assert!(Branch::get("feature").is_err());
+  });
+}
+
+#[test]
+#[serial]
+fn branch_with_rename_option_and_detached_head_requires_two_names() {
+  run_acceptance("branch", |command| {
+    let (parent, _) = history();
+    Head::set(&Reference::Commit(parent)).unwrap();
+
+    command.args(["-m", "main"]).assert().code(USER_ERROR_CODE);
+  });
+}
@@ -0,0 +1,68 @@
+use serial_test::serial;
+use crate::{tests::{run_acceptance, factory::commit}, core::branch::Branch, lib::{read_object_bytes, errors::USER_ERROR_CODE}};
+
+fn output(command: &mut assert_cmd::Command) -> String {
+  String::from_utf8(command.output().unwrap().stdout).unwrap()
+}
+
+#[test]
+#[serial]
+fn cat_file_requires_a_query_option() {
+  run_acceptance("cat-file", |command| {
+    command.arg("HEAD").assert().failure();
+  });
+}
+
+#[test]
+#[serial]
+fn cat_file_with_type_option_prints_the_object_type() {
+  run_acceptance("cat-file", |command| {
+    let commit = commit();
+
+    assert_eq!(output(command.args(["-t", commit.id()])), "commit\n");
+  });
+}
+
+#[test]
+#[serial]
+fn cat_file_with_size_option_prints_the_content_length() {
+  run_acceptance("cat-file", |command| {
+    let commit = commit();
+    let blob = commit.tree().unwrap().get("a/b/c").unwrap().into_blob().unwrap().clone();
+
+    assert_eq!(output(command.args(["-s", blob.id()])), format!("{}\n", read_object_bytes(blob.id()).unwrap().len()));
+  });
+}
+
+#[test]
+#[serial]
+fn cat_file_with_pretty_option_prints_the_file_content() {
+  run_acceptance("cat-file", |command| {
+    let commit = commit();
+    Branch::set("master", commit.id()).unwrap();
+    let blob = commit.tree().unwrap().get("a/b/c").unwrap().into_blob().unwrap().clone();
+
+    assert_eq!(output(command.args(["-p", "HEAD:a/b/c"])).into_bytes(), read_object_bytes(blob.id()).unwrap());
+  });
+}
+
+#[test]
+#[serial]
+fn cat_file_with_pretty_option_lists_folder_entries() {
+  run_acceptance("cat-file", |command| {
+    let commit = commit();
+    let tree = commit.tree().unwrap();
+    let folder = |name: &str| tree.get(name).unwrap().into_tree().unwrap().id().clone();
+    let expected_output = format!("040000 tree {}\ta\n040000 tree {}\tb\n040000 tree {}\tc\n", folder("a"), folder("b"), folder("c"));
+
+    assert_eq!(output(command.args(["-p", commit.tree_id()])), expected_output);
+  });
+}
+
+#[test]
+#[serial]
+fn cat_file_given_unknown_object_exits_with_user_error_code() {
+  run_acceptance("cat-file", |command| {
+    command.args(["-t", "abcdef"]).assert().code(USER_ERROR_CODE);
+  });
+}
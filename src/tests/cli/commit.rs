@@ -1,6 +1,7 @@
-use std::fs::File;
+use std::fs::{File, self};
+use camino::Utf8PathBuf;
 use serial_test::serial;
-use crate::{tests::run_acceptance, core::{index::Index, head::Head}, lib::locale};
+use crate::{tests::{run_acceptance, factory::{hook, commit}}, core::{index::Index, head::Head, commit::Commit, branch::Branch, blob::Blob}, lib::{locale, identity::Identity, layout::Layout, errors::USER_ERROR_CODE}};
 
 #[test]
 #[serial]
@@ -60,10 +61,163 @@ fn commit_creates_a_new_commit() {
     File::create(locale().join("file")).unwrap();
     Index::add(&vec![locale().join("file")]).unwrap();
 
-    let old_commit = Head::get().unwrap().commit();
+    let old_commit = Head::get().unwrap().commit().unwrap();
     command.args(["-m", "commit-message"]).ok().unwrap();
-    let new_commit = Head::get().unwrap().commit();
+    let new_commit = Head::get().unwrap().commit().unwrap();
 
     assert!(old_commit != new_commit);
   });
 }
+
+#[test]
+#[serial]
+fn commit_with_failing_pre_commit_hook_is_aborted() {
+  run_acceptance("commit", |command| {
+    File::create(locale().join("file")).unwrap();
+    Index::add(&[locale().join("file")]).unwrap();
+    hook("pre-commit", "exit 1");
+
+    command.args(["-m", "commit-message"]).assert().code(USER_ERROR_CODE);
+    assert!(Head::get().unwrap().commit().unwrap().is_none());
+  });
+}
+
+#[test]
+#[serial]
+fn commit_uses_the_message_rewritten_by_the_commit_msg_hook() {
+  run_acceptance("commit", |command| {
+    File::create(locale().join("file")).unwrap();
+    Index::add(&[locale().join("file")]).unwrap();
+    hook("commit-msg", "printf '\\nTicket: 42\\n' >> \"$1\"");
+
+    command.args(["-m", "commit-message"]).ok().unwrap();
+
+    assert_eq!(Head::get().unwrap().commit().unwrap().unwrap().message(), "commit-message\n\nTicket: 42");
+  });
+}
+
+#[test]
+#[serial]
+fn commit_with_no_verify_option_skips_the_hooks() {
+  run_acceptance("commit", |command| {
+    File::create(locale().join("file")).unwrap();
+    Index::add(&[locale().join("file")]).unwrap();
+    hook("pre-commit", "exit 1");
+    hook("commit-msg", "exit 1");
+    hook("post-commit", "echo done");
+
+    command.args(["--no-verify", "-m", "commit-message"]).assert().success().stdout("done\n");
+    assert_eq!(Head::get().unwrap().commit().unwrap().unwrap().message(), "commit-message");
+  });
+}
+
+#[test]
+#[serial]
+fn commit_with_amend_option_replaces_the_last_commit() {
+  run_acceptance("commit", |command| {
+    let parent = commit();
+    let last = Commit::new(Some(parent.id()), parent.tree_id(), "typo").unwrap();
+    Branch::set("master", last.id()).unwrap();
+    File::create(locale().join("file")).unwrap();
+    Index::add(&[locale().join("file")]).unwrap();
+
+    command.args(["--amend", "-m", "fixed"]).assert().success();
+    let amended = Head::get().unwrap().commit().unwrap().unwrap();
+
+    assert_eq!(amended.parent_id().as_ref().unwrap(), parent.id());
+    assert_eq!(amended.message(), "fixed");
+    assert!(amended.tree().unwrap().get("file").is_some());
+    assert!(amended.tree().unwrap().get("a/a/a").is_some());
+  });
+}
+
+#[test]
+#[serial]
+fn commit_with_amend_option_and_no_message_keeps_the_message() {
+  run_acceptance("commit", |command| {
+    let last = commit();
+    Branch::set("master", last.id()).unwrap();
+
+    command.arg("--amend").assert().success();
+    let amended = Head::get().unwrap().commit().unwrap().unwrap();
+
+    assert_eq!(amended.message(), last.message());
+    assert!(amended.parent_id().is_none());
+  });
+}
+
+#[test]
+#[serial]
+fn commit_with_amend_option_and_no_commits_fails() {
+  run_acceptance("commit", |command| {
+    command.arg("--amend").assert().code(USER_ERROR_CODE);
+  });
+}
+
+#[test]
+#[serial]
+fn commit_with_all_option_commits_changes_to_tracked_files() {
+  run_acceptance("commit", |command| {
+    let last = commit();
+    Branch::set("master", last.id()).unwrap();
+    last.tree().unwrap().unpack(locale()).unwrap();
+    fs::write(locale().join("a/a/a"), "changed").unwrap();
+    fs::remove_file(locale().join("a/a/b")).unwrap();
+    File::create(locale().join("untracked")).unwrap();
+
+    command.args(["-a", "-m", "commit-message"]).assert().success();
+    let tree = Head::get().unwrap().commit().unwrap().unwrap().tree().unwrap();
+
+    assert_eq!(tree.get("a/a/a").unwrap().into_blob().unwrap(), &Blob::from_path(locale().join("a/a/a")).unwrap());
+    assert!(tree.get("a/a/b").is_none());
+    assert!(tree.get("untracked").is_none());
+  });
+}
+
+#[test]
+#[serial]
+fn commit_given_paths_leaves_other_staged_changes_in_the_index() {
+  run_acceptance("commit", |command| {
+    File::create(locale().join("staged")).unwrap();
+    File::create(locale().join("committed")).unwrap();
+    Index::add(&[locale().join("staged")]).unwrap();
+
+    command.args(["-m", "commit-message", "committed"]).assert().success();
+    let tree = Head::get().unwrap().commit().unwrap().unwrap().tree().unwrap();
+
+    assert!(tree.get("committed").is_some());
+    assert!(tree.get("staged").is_none());
+    assert_eq!(Index::get().unwrap().staged_paths().keys().collect::<Vec<_>>(), [&Utf8PathBuf::from("staged")]);
+  });
+}
+
+#[test]
+#[serial]
+fn commit_with_author_and_date_options_overrides_the_author() {
+  run_acceptance("commit", |command| {
+    File::create(locale().join("file")).unwrap();
+    Index::add(&[locale().join("file")]).unwrap();
+
+    command.args(["-m", "commit-message", "--author", "Jane Doe <jane@mail.com>", "--date", "1641031200 +0200"]).assert().success();
+    let commit = Head::get().unwrap().commit().unwrap().unwrap();
+
+    assert_eq!(commit.author().format(Layout::Git), "Jane Doe <jane@mail.com> 1641031200 +0200");
+    assert_eq!(commit.committer().username(), "admin");
+  });
+}
+
+#[test]
+#[serial]
+fn commit_with_amend_option_keeps_the_original_author() {
+  run_acceptance("commit", |command| {
+    let author = Identity::parse("Jane <jane@mail.com> 1641031200 +0200").unwrap();
+    let last = Commit::with_author(None, commit().tree_id(), "typo", author.clone()).unwrap();
+    Branch::set("master", last.id()).unwrap();
+
+    command.args(["--amend", "-m", "fixed"]).assert().success();
+    let amended = Head::get().unwrap().commit().unwrap().unwrap();
+
+    assert_eq!(amended.author(), &author);
+    assert_ne!(amended.committer(), &author);
+  });
+}
@@ -1,5 +1,6 @@
 use serial_test::serial;
-use crate::{tests::run_acceptance, lib::user::User};
+use assert_cmd::Command;
+use crate::{tests::run_acceptance, lib::{user::User, config::{Config, Scope}, errors::USER_ERROR_CODE, locale}};
 
 #[test]
 #[serial]
@@ -53,3 +54,45 @@ fn config_updates_the_user_credentials() {
     assert_eq!(user.email(), "email");
   });
 }
+
+#[test]
+#[serial]
+fn config_sets_and_gets_values() {
+  run_acceptance("config", |command| {
+    command.args(["set", "user.name", "Jane Doe"]).ok().unwrap();
+
+    let mut get = Command::cargo_bin("rgit").unwrap();
+    get.current_dir(locale()).args(["config", "get", "user.name"]).assert().success().stdout("Jane Doe\n");
+    assert_eq!(User::get().unwrap().username(), "Jane Doe");
+  });
+}
+
+#[test]
+#[serial]
+fn config_with_global_option_writes_the_global_config() {
+  run_acceptance("config", |command| {
+    command.args(["--global", "set", "core.editor", "vim"]).ok().unwrap();
+
+    assert_eq!(Config::read(Scope::Global).unwrap().get("core.editor").unwrap(), "vim");
+    assert!(Config::read(Scope::Repository).unwrap().entries().is_empty());
+  });
+}
+
+#[test]
+#[serial]
+fn config_with_list_option_prints_every_scope() {
+  run_acceptance("config", |command| {
+    Config::set(Scope::Global, "user.name", "Jane").unwrap();
+    Config::set(Scope::Repository, "user.name", "John").unwrap();
+
+    command.arg("--list").assert().success().stdout("user.name=Jane\nuser.name=John\n");
+  });
+}
+
+#[test]
+#[serial]
+fn config_given_missing_key_exits_with_user_error_code() {
+  run_acceptance("config", |command| {
+    command.args(["get", "user.name"]).assert().code(USER_ERROR_CODE);
+  });
+}
@@ -0,0 +1,127 @@
+use std::fs;
+use serial_test::serial;
+use crate::{tests::{run_acceptance, factory::{commit, tree}}, core::{branch::Branch, commit::Commit, tree::Node}, lib::{locale, initialize_git, read_object_bytes, write_object_bytes, folder_files, object::{Object, FromId}, constants::{OBJECTS_PATH, REPOSITORY_PATH, GIT_TAGS_PATH, GIT_PACKED_REFS_PATH}, format::ObjectFormat, errors::USER_ERROR_CODE, config::{Config, Scope}, identity::Identity, signature::{SigningKey, SIGNING_KEY_KEY}}};
+
+#[test]
+#[serial]
+fn convert_objects_does_not_handle_unknown_formats() {
+  run_acceptance("convert-objects", |command| {
+    command.arg("md5").assert().failure();
+  });
+}
+
+#[test]
+#[serial]
+fn convert_objects_rewrites_the_history_with_the_new_format() {
+  run_acceptance("convert-objects", |command| {
+    let commit = commit();
+    Branch::set("master", commit.id()).unwrap();
+    let old_blob = commit.tree().unwrap().get("a/b/c").unwrap().into_blob().unwrap().clone();
+
+    command.arg("sha256").ok().unwrap();
+
+    let new_commit = Branch::get("master").unwrap().commit().unwrap().unwrap();
+    let new_blob = new_commit.tree().unwrap().get("a/b/c").unwrap().into_blob().unwrap().clone();
+
+    assert_eq!(ObjectFormat::get().unwrap(), ObjectFormat::Sha256);
+    assert_eq!(new_commit.id().len(), 64);
+    assert_eq!(new_commit.message(), commit.message());
+    assert_eq!(new_blob.id().len(), 64);
+    assert!(read_object_bytes(old_blob.id()).is_err());
+    assert!(folder_files(locale().join(OBJECTS_PATH)).is_ok());
+  });
+}
+
+#[test]
+#[serial]
+fn convert_objects_given_signed_commit_keeps_the_repository_unchanged() {
+  run_acceptance("convert-objects", |command| {
+    Config::set(Scope::Repository, SIGNING_KEY_KEY, "key").unwrap();
+    let key = SigningKey::generate().unwrap();
+    let commit = Commit::signed(None, commit().tree_id(), "signed", Identity::author().unwrap(), Identity::committer().unwrap(), &key).unwrap();
+    Branch::set("master", commit.id()).unwrap();
+
+    command.arg("sha256").assert().code(USER_ERROR_CODE);
+
+    assert_eq!(ObjectFormat::get().unwrap(), ObjectFormat::Sha1);
+    assert_eq!(Branch::get("master").unwrap().commit_id().as_ref().unwrap(), commit.id());
+  });
+}
+
+#[test]
+#[serial]
+fn convert_objects_given_gitlink_keeps_the_repository_unchanged() {
+  run_acceptance("convert-objects", |command| {
+    let mut tree = tree();
+    tree.insert("modules/library", Node::Gitlink(commit().id().clone())).unwrap();
+    tree.pack().unwrap();
+    let commit = Commit::new(None, tree.id(), "submodule").unwrap();
+    Branch::set("master", commit.id()).unwrap();
+
+    let stderr = String::from_utf8(command.arg("sha256").assert().code(USER_ERROR_CODE).get_output().stderr.clone()).unwrap();
+
+    assert!(stderr.contains("'modules/library'"));
+    assert_eq!(ObjectFormat::get().unwrap(), ObjectFormat::Sha1);
+    assert_eq!(Branch::get("master").unwrap().commit_id().as_ref().unwrap(), commit.id());
+  });
+}
+
+#[test]
+#[serial]
+fn convert_objects_in_git_layout_rewrites_loose_and_packed_tags() {
+  run_acceptance("convert-objects", |command| {
+    fs::remove_dir_all(locale().join(REPOSITORY_PATH)).unwrap();
+    initialize_git().unwrap();
+    let commit = commit();
+    let tagged_commit = Commit::new(None, commit.tree_id(), "tagged").unwrap();
+    Branch::set("master", commit.id()).unwrap();
+    fs::write(locale().join(GIT_TAGS_PATH).join("v1"), format!("{}\n", commit.id())).unwrap();
+    fs::write(locale().join(GIT_PACKED_REFS_PATH), format!("# pack-refs with: peeled fully-peeled sorted\n{} refs/tags/v0\n", tagged_commit.id())).unwrap();
+
+    command.arg("sha256").ok().unwrap();
+
+    let new_commit_id = Branch::get("master").unwrap().commit_id().clone().unwrap();
+    let packed_refs = fs::read_to_string(locale().join(GIT_PACKED_REFS_PATH)).unwrap();
+    let (new_tagged_commit_id, _) = packed_refs.lines().nth(1).unwrap().split_once(' ').unwrap();
+
+    assert_eq!(ObjectFormat::get().unwrap(), ObjectFormat::Sha256);
+    assert_eq!(fs::read_to_string(locale().join(GIT_TAGS_PATH).join("v1")).unwrap(), format!("{}\n", new_commit_id));
+    assert_eq!(Commit::from_id(new_tagged_commit_id).unwrap().message(), "tagged");
+    assert_eq!(new_tagged_commit_id.len(), 64);
+  });
+}
+
+#[test]
+#[serial]
+fn convert_objects_given_merge_commit_keeps_the_repository_unchanged() {
+  run_acceptance("convert-objects", |command| {
+    let (commit, other_commit) = (commit(), commit());
+    let text = String::from_utf8(read_object_bytes(commit.id()).unwrap()).unwrap()
+      .replacen("\nauthor ", &format!("\nparent {}\nparent {}\nauthor ", commit.id(), other_commit.id()), 1);
+    let merge_id = write_object_bytes(Object::Commit, text).unwrap();
+    Branch::set("master", &merge_id).unwrap();
+
+    let stderr = String::from_utf8(command.arg("sha256").assert().code(USER_ERROR_CODE).get_output().stderr.clone()).unwrap();
+
+    assert!(stderr.contains("merge commit"));
+    assert_eq!(ObjectFormat::get().unwrap(), ObjectFormat::Sha1);
+    assert_eq!(Branch::get("master").unwrap().commit_id().as_ref().unwrap(), &merge_id);
+  });
+}
+
+#[test]
+#[serial]
+fn convert_objects_given_commit_with_unknown_header_keeps_the_repository_unchanged() {
+  run_acceptance("convert-objects", |command| {
+    let commit = commit();
+    let text = String::from_utf8(read_object_bytes(commit.id()).unwrap()).unwrap().replacen("\n\n", "\nencoding ISO-8859-1\n\n", 1);
+    let encoded_id = write_object_bytes(Object::Commit, text).unwrap();
+    Branch::set("master", &encoded_id).unwrap();
+
+    let stderr = String::from_utf8(command.arg("sha256").assert().code(USER_ERROR_CODE).get_output().stderr.clone()).unwrap();
+
+    assert!(stderr.contains("'encoding'"));
+    assert_eq!(ObjectFormat::get().unwrap(), ObjectFormat::Sha1);
+    assert_eq!(Branch::get("master").unwrap().commit_id().as_ref().unwrap(), &encoded_id);
+  });
+}
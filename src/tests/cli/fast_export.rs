@@ -0,0 +1,48 @@
+use std::collections::HashSet;
+use serial_test::serial;
+use crate::{tests::{run_acceptance, factory::{commit, tree}}, core::{branch::Branch, commit::Commit, tree::Node}, lib::{read_object_bytes, layout::Layout}};
+
+#[test]
+#[serial]
+fn fast_export_does_not_handle_any_arguments() {
+  run_acceptance("fast-export", |command| {
+    command.arg("argument").assert().failure();
+  });
+}
+
+#[test]
+#[serial]
+fn fast_export_outputs_blobs_commits_and_branches() {
+  run_acceptance("fast-export", |command| {
+    let commit = commit();
+    Branch::set("master", commit.id()).unwrap();
+
+    let output = String::from_utf8(command.output().unwrap().stdout).unwrap();
+    let blob = commit.tree().unwrap().get("a/a/a").unwrap().into_blob().unwrap().clone();
+    let text = String::from_utf8(read_object_bytes(blob.id()).unwrap()).unwrap();
+    let commit_mark = commit.tree().unwrap().blobs().values().map(|blob| blob.id().clone()).collect::<HashSet<String>>().len() + 1;
+
+    assert!(output.starts_with("blob\nmark :1\n"));
+    assert!(output.contains(&format!("data {}\n{}\n", text.len(), text)));
+    assert!(output.contains(&format!("commit refs/heads/master\nmark :{}\nauthor {}\n", commit_mark, commit.author().format(Layout::Git))));
+    assert!(output.contains(&format!("data {}\n{}\ndeleteall\n", commit.message().len(), commit.message())));
+    assert!(output.contains("M 100644 :"));
+    assert!(output.ends_with(&format!("reset refs/heads/master\nfrom :{}\n\ndone\n", commit_mark)));
+  });
+}
+
+#[test]
+#[serial]
+fn fast_export_given_gitlink_outputs_the_submodule_commit() {
+  run_acceptance("fast-export", |command| {
+    let submodule_id = commit().id().clone();
+    let mut tree = tree();
+    tree.insert("modules/library", Node::Gitlink(submodule_id.clone())).unwrap();
+    tree.pack().unwrap();
+    Branch::set("master", Commit::new(None, tree.id(), "submodule").unwrap().id()).unwrap();
+
+    let output = String::from_utf8(command.output().unwrap().stdout).unwrap();
+
+    assert!(output.contains(&format!("M 160000 {} modules/library\n", submodule_id)));
+  });
+}
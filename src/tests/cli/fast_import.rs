@@ -0,0 +1,168 @@
+use serial_test::serial;
+use crate::{tests::run_acceptance, core::{branch::Branch, tree::Node}, lib::{read_object_bytes, object::Mode, errors::{CORRUPTION_ERROR_CODE, USER_ERROR_CODE}}};
+
+const STREAM: &str = "\
+blob
+mark :1
+data 6
+hello
+
+reset refs/heads/feature
+commit refs/heads/feature
+mark :2
+author Jane Doe <jane@mail.com> 1700000000 +0200
+committer Jane Doe <jane@mail.com> 1700000000 +0200
+data 6
+first
+M 100644 :1 my notes.txt
+M 100755 inline bin/run
+data 10
+#!/bin/sh
+
+commit refs/heads/feature
+mark :3
+committer Jane Doe <jane@mail.com> 1700000100 +0200
+data <<END
+second
+END
+from :2
+R \"my notes.txt\" notes.txt
+
+done
+";
+
+#[test]
+#[serial]
+fn fast_import_does_not_handle_any_arguments() {
+  run_acceptance("fast-import", |command| {
+    command.arg("argument").assert().failure();
+  });
+}
+
+#[test]
+#[serial]
+fn fast_import_creates_commits_and_branches_from_the_stream() {
+  run_acceptance("fast-import", |command| {
+    command.write_stdin(STREAM).ok().unwrap();
+
+    let commit = Branch::get("feature").unwrap().commit().unwrap().unwrap();
+    let parent = commit.parent().unwrap();
+    let tree = commit.tree().unwrap();
+
+    assert_eq!(commit.message(), "second");
+    assert_eq!(parent.message(), "first");
+    assert_eq!(parent.author().username(), "Jane Doe");
+    assert_eq!(parent.author().date().timestamp(), 1700000000);
+    assert_eq!(parent.parent(), None);
+    assert_eq!(tree.get("my notes.txt"), None);
+
+    if let Some(Node::Blob(blob)) = tree.get("notes.txt") {
+      assert_eq!(read_object_bytes(blob.id()).unwrap(), b"hello\n");
+    } else {
+      unreachable!();
+    }
+
+    assert_eq!(tree.get("bin/run").unwrap().into_blob().unwrap().mode(), &Mode::Executable);
+  });
+}
+
+#[test]
+#[serial]
+fn fast_import_given_path_leaving_the_work_tree_exits_with_corruption_error_code() {
+  run_acceptance("fast-import", |command| {
+    command.write_stdin(stream_with_path("a/../../outside.txt")).assert().code(CORRUPTION_ERROR_CODE);
+
+    assert!(Branch::get("feature").is_err());
+  });
+}
+
+#[test]
+#[serial]
+fn fast_import_given_path_inside_the_repository_folder_exits_with_corruption_error_code() {
+  run_acceptance("fast-import", |command| {
+    command.write_stdin(stream_with_path(".rgit/hooks/post-switch")).assert().code(CORRUPTION_ERROR_CODE);
+
+    assert!(Branch::get("feature").is_err());
+  });
+}
+
+#[test]
+#[serial]
+fn fast_import_given_empty_path_exits_with_corruption_error_code() {
+  run_acceptance("fast-import", |command| {
+    command.write_stdin(stream_with_path("\"\"")).assert().code(CORRUPTION_ERROR_CODE);
+  });
+}
+
+#[test]
+#[serial]
+#[cfg(unix)]
+fn fast_import_given_raw_and_quoted_non_utf8_paths_keeps_their_bytes() {
+  use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+
+  run_acceptance("fast-import", |command| {
+    let stream = b"commit refs/heads/feature\ncommitter Jane Doe <jane@mail.com> 1700000000 +0200\ndata 5\nfirst\nM 100644 inline caf\xe9\ndata 3\nrun\nM 100644 inline \"na\\303\\257ve\"\ndata 3\nrun\n\ndone\n";
+
+    command.write_stdin(&stream[..]).ok().unwrap();
+
+    let tree = Branch::get("feature").unwrap().commit().unwrap().unwrap().tree().unwrap();
+
+    assert!(tree.get(OsStr::from_bytes(b"caf\xe9")).is_some());
+    assert!(tree.get("na\u{ef}ve").is_some());
+  });
+}
+
+#[test]
+#[serial]
+fn fast_import_given_merge_fails_without_updating_branches() {
+  run_acceptance("fast-import", |command| {
+    let stream = STREAM.replace("from :2\n", "from :2\nmerge :2\n");
+
+    command.write_stdin(stream).assert().code(USER_ERROR_CODE);
+
+    assert!(Branch::get("feature").is_err());
+  });
+}
+
+#[test]
+#[serial]
+fn fast_import_given_tag_fails_without_updating_branches() {
+  run_acceptance("fast-import", |command| {
+    let stream = STREAM.replace("\ndone\n", "\ntag v1\nfrom :3\ntagger Jane Doe <jane@mail.com> 1700000200 +0200\ndata 4\nv1.0\n\ndone\n");
+
+    command.write_stdin(stream).assert().code(USER_ERROR_CODE);
+
+    assert!(Branch::get("feature").is_err());
+  });
+}
+
+#[test]
+#[serial]
+fn fast_import_given_lightweight_tag_fails_without_updating_branches() {
+  run_acceptance("fast-import", |command| {
+    let stream = STREAM.replace("\ndone\n", "\nreset refs/tags/v1\nfrom :3\n\ndone\n");
+    let stderr = String::from_utf8(command.write_stdin(stream).assert().code(USER_ERROR_CODE).get_output().stderr.clone()).unwrap();
+
+    assert!(stderr.contains("'refs/tags/v1'"));
+    assert!(Branch::get("feature").is_err());
+  });
+}
+
+#[test]
+#[serial]
+fn fast_import_given_gitlink_inserts_the_submodule_commit() {
+  run_acceptance("fast-import", |command| {
+    let id = "a".repeat(40);
+    let stream = STREAM.replace("M 100755 inline bin/run", &format!("M 160000 {} modules/library\nM 100755 inline bin/run", id));
+
+    command.write_stdin(stream).ok().unwrap();
+
+    let tree = Branch::get("feature").unwrap().commit().unwrap().unwrap().tree().unwrap();
+
+    assert_eq!(tree.get("modules/library"), Some(&Node::Gitlink(id)));
+  });
+}
+
+fn stream_with_path(path: &str) -> String {
+  format!("commit refs/heads/feature\ncommitter Jane Doe <jane@mail.com> 1700000000 +0200\ndata 5\nfirst\nM 100755 inline {}\ndata 3\nrun\n\ndone\n", path)
+}
@@ -0,0 +1,76 @@
+use std::fs;
+use serial_test::serial;
+use crate::{tests::run_acceptance, core::{blob::Blob, tree::{Tree, Node}, commit::Commit, branch::Branch}, lib::{locale, errors::USER_ERROR_CODE}};
+
+// Commits `src/main.rs` and `notes.txt`, then changes the notes in the working tree only.
+fn project() {
+  let mut tree = Tree::new();
+  tree.insert("src/main.rs", Node::Blob(Blob::new("fn main() {\n  println!(\"Hello\");\n}\n").unwrap())).unwrap();
+  tree.insert("notes.txt", Node::Blob(Blob::new("hello world\nbye\n").unwrap())).unwrap();
+  let tree_id = tree.pack().unwrap().clone();
+
+  let commit = Commit::new(None, &tree_id, "initial").unwrap();
+  Branch::set("master", commit.id()).unwrap();
+  tree.unpack(locale()).unwrap();
+
+  fs::write(locale().join("notes.txt"), "goodbye\n").unwrap();
+  fs::write(locale().join("untracked.txt"), "hello\n").unwrap();
+}
+
+#[test]
+#[serial]
+fn grep_searches_tracked_files_in_the_working_tree() {
+  run_acceptance("grep", |command| {
+    project();
+
+    command.args(["-n", "-i", "hello"]).assert().success().stdout("src/main.rs:2:  println!(\"Hello\");\n");
+  });
+}
+
+#[test]
+#[serial]
+fn grep_given_revision_searches_its_blobs() {
+  run_acceptance("grep", |command| {
+    project();
+
+    command.args(["hello", "HEAD"]).assert().success().stdout("HEAD:notes.txt:hello world\n");
+  });
+}
+
+#[test]
+#[serial]
+fn grep_with_count_option_given_paths_counts_matches_in_them() {
+  run_acceptance("grep", |command| {
+    project();
+
+    command.args(["-c", "e", "HEAD", "--", "notes.txt"]).assert().success().stdout("HEAD:notes.txt:2\n");
+  });
+}
+
+#[test]
+#[serial]
+fn grep_with_files_with_matches_option_prints_file_names() {
+  run_acceptance("grep", |command| {
+    project();
+
+    command.args(["-l", "o"]).assert().success().stdout("notes.txt\nsrc/main.rs\n");
+  });
+}
+
+#[test]
+#[serial]
+fn grep_without_matches_fails() {
+  run_acceptance("grep", |command| {
+    project();
+
+    command.arg("missing").assert().code(USER_ERROR_CODE).stdout("").stderr("");
+  });
+}
+
+#[test]
+#[serial]
+fn grep_given_invalid_pattern_returns_user_error() {
+  run_acceptance("grep", |command| {
+    command.arg("(").assert().code(USER_ERROR_CODE);
+  });
+}
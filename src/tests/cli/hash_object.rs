@@ -0,0 +1,34 @@
+use std::fs;
+use serial_test::serial;
+use crate::{tests::run_acceptance, lib::{locale, read_object_bytes, object::Object, hash_object_bytes}};
+
+#[test]
+#[serial]
+fn hash_object_requires_paths_or_stdin() {
+  run_acceptance("hash-object", |command| {
+    command.assert().failure();
+  });
+}
+
+#[test]
+#[serial]
+fn hash_object_prints_the_id_without_writing() {
+  run_acceptance("hash-object", |command| {
+    fs::write(locale().join("file"), "content").unwrap();
+    let id = hash_object_bytes(Object::Blob, "content").unwrap();
+
+    command.arg("file").assert().success().stdout(format!("{}\n", id));
+    assert!(read_object_bytes(&id).is_err());
+  });
+}
+
+#[test]
+#[serial]
+fn hash_object_with_write_option_stores_the_blob() {
+  run_acceptance("hash-object", |command| {
+    let id = hash_object_bytes(Object::Blob, "content").unwrap();
+
+    command.args(["-w", "--stdin"]).write_stdin("content").assert().success().stdout(format!("{}\n", id));
+    assert_eq!(read_object_bytes(&id).unwrap(), b"content");
+  });
+}
@@ -1,6 +1,6 @@
 use std::fs;
 use serial_test::serial;
-use crate::{tests::run_acceptance, lib::{constants::*, locale}};
+use crate::{tests::run_acceptance, lib::{constants::*, errors::REPOSITORY_ERROR_CODE, locale}};
 
 #[test]
 #[serial]
@@ -31,7 +31,7 @@ fn init_given_no_arguments_and_options_succeeds() {
 #[serial]
 fn init_if_repository_exists_fails_with_error() {
   run_acceptance("init", |command| {
-    let output = command.output().unwrap().stdout;
+    let output = command.output().unwrap().stderr;
     let expected_output = "error: rgit repository already instantiated in current working environment\n";
 
     assert_eq!(output, expected_output.as_bytes());
@@ -54,3 +54,37 @@ fn init_if_no_repository_exists_succeeds_creating_repository() {
     assert!(locale.join(CONFIG_PATH).exists());
   });
 }
+
+#[test]
+#[serial]
+fn init_given_git_option_creates_git_repository() {
+  run_acceptance("init", |command| {
+    let locale = locale();
+    command.arg("--git").ok().unwrap();
+
+    assert!(locale.join(GIT_OBJECTS_PATH).exists());
+    assert!(locale.join(GIT_BRANCHES_PATH).exists());
+    assert!(locale.join(GIT_HEAD_PATH).exists());
+    assert!(locale.join(GIT_STATE_CONFIG_PATH).exists());
+  });
+}
+
+#[test]
+#[serial]
+fn init_given_object_format_option_records_the_format() {
+  run_acceptance("init", |command| {
+    let locale = locale();
+    fs::remove_dir_all(locale.join(REPOSITORY_PATH)).unwrap();
+    command.args(["--object-format", "sha256"]).ok().unwrap();
+
+    assert_eq!(fs::read_to_string(locale.join(OBJECT_FORMAT_PATH)).unwrap(), "sha256");
+  });
+}
+
+#[test]
+#[serial]
+fn init_if_repository_exists_exits_with_repository_error_code() {
+  run_acceptance("init", |command| {
+    command.assert().code(REPOSITORY_ERROR_CODE);
+  });
+}
@@ -0,0 +1,241 @@
+use std::fs;
+use chrono::{TimeZone, Utc};
+use serial_test::serial;
+use crate::{tests::{run_acceptance, factory::tree}, core::{commit::Commit, branch::Branch, blob::Blob, tree::{Tree, Node}}, lib::{user::User, identity::Identity, errors::USER_ERROR_CODE, object_location}};
+
+// Creates `first` (Jane, 2022-01-01), `second fix` (John, 2022-02-01) and `third` (Jane, 2022-03-01) on master.
+fn history() -> Vec<Commit> {
+  let tree_id = tree().id().clone();
+  let jane = User::new("Jane", "jane@mail.com").unwrap();
+  let john = User::new("John", "john@mail.com").unwrap();
+
+  let first = Commit::with_author(None, &tree_id, "first", Identity::new(&jane, Utc.ymd(2022, 1, 1).and_hms(12, 0, 0).into())).unwrap();
+  let second = Commit::with_author(Some(first.id()), &tree_id, "second fix", Identity::new(&john, Utc.ymd(2022, 2, 1).and_hms(12, 0, 0).into())).unwrap();
+  let third = Commit::with_author(Some(second.id()), &tree_id, "third", Identity::new(&jane, Utc.ymd(2022, 3, 1).and_hms(12, 0, 0).into())).unwrap();
+
+  Branch::set("master", third.id()).unwrap();
+  vec![first, second, third]
+}
+
+fn output(command: &mut assert_cmd::Command) -> String {
+  String::from_utf8(command.output().unwrap().stdout).unwrap()
+}
+
+#[test]
+#[serial]
+fn log_does_not_handle_any_arguments() {
+  run_acceptance("log", |command| {
+    command.arg("argument").assert().failure();
+  });
+}
+
+#[test]
+#[serial]
+fn log_prints_every_commit_from_newest() {
+  run_acceptance("log", |command| {
+    let commits = history();
+    let output = output(command);
+
+    assert!(output.starts_with(&format!("commit {}\nAuthor: Jane <jane@mail.com>\n", commits[2].id())));
+    assert_eq!(output.matches("commit ").count(), 3);
+  });
+}
+
+#[test]
+#[serial]
+fn log_with_oneline_option_prints_short_ids_and_subjects() {
+  run_acceptance("log", |command| {
+    let commits = history();
+    let expected_output = commits.iter().rev()
+      .map(|commit| format!("{} {}\n", &commit.id()[..7], commit.message()))
+      .collect::<String>();
+
+    assert_eq!(output(command.arg("--oneline")), expected_output);
+  });
+}
+
+#[test]
+#[serial]
+fn log_with_max_count_and_reverse_limits_before_reversing() {
+  run_acceptance("log", |command| {
+    history();
+
+    assert_eq!(output(command.args(["-n", "2", "--reverse", "--format", "%s"])), "second fix\nthird\n");
+  });
+}
+
+#[test]
+#[serial]
+fn log_with_author_option_filters_by_author() {
+  run_acceptance("log", |command| {
+    history();
+
+    assert_eq!(output(command.args(["--author", "^Jane", "--format", "%s by %an <%ae>"])), "third by Jane <jane@mail.com>\nfirst by Jane <jane@mail.com>\n");
+  });
+}
+
+#[test]
+#[serial]
+fn log_with_date_options_filters_by_date() {
+  run_acceptance("log", |command| {
+    history();
+
+    assert_eq!(output(command.args(["--since", "2022-01-15", "--until", "2022-02-15T00:00:00Z", "--format", "%s"])), "second fix\n");
+  });
+}
+
+#[test]
+#[serial]
+fn log_with_grep_option_filters_by_message() {
+  run_acceptance("log", |command| {
+    let commits = history();
+
+    assert_eq!(output(command.args(["--grep", "fi[x]", "--format", "%H%%%n%s"])), format!("{}%\nsecond fix\n", commits[1].id()));
+  });
+}
+
+#[test]
+#[serial]
+fn log_given_invalid_pattern_exits_with_user_error_code() {
+  run_acceptance("log", |command| {
+    command.args(["--grep", "("]).assert().code(USER_ERROR_CODE);
+  });
+}
+
+#[test]
+#[serial]
+fn log_given_missing_parent_exits_with_an_error() {
+  run_acceptance("log", |command| {
+    let commits = history();
+    fs::remove_file(object_location(commits[0].id()).unwrap()).unwrap();
+
+    let stderr = String::from_utf8(command.assert().code(USER_ERROR_CODE).get_output().stderr.clone()).unwrap();
+
+    assert!(stderr.contains(&format!("unable to read parent of commit '{}'", commits[1].id())));
+  });
+}
+
+#[test]
+#[serial]
+fn log_with_max_count_option_stops_reading_the_history_after_the_shown_commits() {
+  run_acceptance("log", |command| {
+    let commits = history();
+    fs::remove_file(object_location(commits[0].id()).unwrap()).unwrap();
+
+    assert_eq!(output(command.args(["-n", "2", "--format", "%s"])), "third\nsecond fix\n");
+  });
+}
+
+#[test]
+#[serial]
+fn log_with_graph_and_all_options_draws_every_branch() {
+  run_acceptance("log", |command| {
+    let commits = history();
+    let jane = User::new("Jane", "jane@mail.com").unwrap();
+    let feature = Commit::with_author(Some(commits[0].id()), commits[0].tree_id(), "feature", Identity::new(&jane, Utc.ymd(2022, 2, 15).and_hms(12, 0, 0).into())).unwrap();
+    Branch::new("feature", Some(feature.id())).unwrap();
+
+    let short_id = |commit: &Commit| String::from(&commit.id()[..7]);
+    let expected_output = format!("\
+* {} (HEAD -> master) third
+| * {} (feature) feature
+* | {} second fix
+|/
+* {} first
+", short_id(&commits[2]), short_id(&feature), short_id(&commits[1]), short_id(&commits[0]));
+
+    assert_eq!(output(command.args(["--graph", "--all", "--oneline"])), expected_output);
+  });
+}
+
+#[test]
+#[serial]
+fn log_with_graph_option_only_draws_the_current_history() {
+  run_acceptance("log", |command| {
+    let commits = history();
+    Branch::new("feature", Some(commits[0].id())).unwrap();
+
+    assert_eq!(output(command.args(["--graph", "--format", "%s%d"])), "* third (HEAD -> master)\n* second fix\n* first (feature)\n");
+  });
+}
+
+// Creates `add notes` (notes.txt, src/main), `edit main`, `rename notes` (notes.txt -> docs/notes.md)
+// and `edit notes` on master.
+fn file_history() {
+  let notes = Node::Blob(Blob::new("notes").unwrap());
+  let mut tree = Tree::new();
+  let mut parent_id: Option<String> = None;
+
+  let mut commit = |tree: &mut Tree, message: &str| {
+    let jane = User::new("Jane", "jane@mail.com").unwrap();
+    let commit = Commit::with_author(parent_id.as_deref(), tree.pack().unwrap(), message, Identity::new(&jane, Utc::now().into())).unwrap();
+    parent_id = Some(commit.id().clone());
+  };
+
+  tree.insert("notes.txt", notes.clone()).unwrap();
+  tree.insert("src/main", Node::Blob(Blob::new("main").unwrap())).unwrap();
+  commit(&mut tree, "add notes");
+
+  tree.insert("src/main", Node::Blob(Blob::new("main 2").unwrap())).unwrap();
+  commit(&mut tree, "edit main");
+
+  tree.remove("notes.txt");
+  tree.insert("docs/notes.md", notes).unwrap();
+  commit(&mut tree, "rename notes");
+
+  tree.insert("docs/notes.md", Node::Blob(Blob::new("notes 2").unwrap())).unwrap();
+  commit(&mut tree, "edit notes");
+
+  Branch::set("master", &parent_id.unwrap()).unwrap();
+}
+
+#[test]
+#[serial]
+fn log_given_paths_shows_only_commits_changing_them() {
+  run_acceptance("log", |command| {
+    file_history();
+
+    assert_eq!(output(command.args(["--format", "%s", "--", "src"])), "edit main\nadd notes\n");
+  });
+}
+
+#[test]
+#[serial]
+fn log_given_paths_reads_only_the_trees_along_them() {
+  run_acceptance("log", |command| {
+    file_history();
+    let commit = Branch::get("master").unwrap().commit().unwrap().unwrap();
+    let notes = commit.tree().unwrap().get("docs/notes.md").unwrap().into_blob().unwrap().clone();
+    fs::remove_file(object_location(notes.id()).unwrap()).unwrap();
+
+    assert_eq!(output(command.args(["--format", "%s", "--", "src"])), "edit main\nadd notes\n");
+  });
+}
+
+#[test]
+#[serial]
+fn log_given_renamed_path_stops_at_the_rename() {
+  run_acceptance("log", |command| {
+    file_history();
+
+    assert_eq!(output(command.args(["--format", "%s", "--", "docs/notes.md"])), "edit notes\nrename notes\n");
+  });
+}
+
+#[test]
+#[serial]
+fn log_with_follow_option_tracks_the_file_across_renames() {
+  run_acceptance("log", |command| {
+    file_history();
+
+    assert_eq!(output(command.args(["--follow", "--format", "%s", "--", "docs/notes.md"])), "edit notes\nrename notes\nadd notes\n");
+  });
+}
+
+#[test]
+#[serial]
+fn log_with_follow_option_does_not_handle_multiple_paths() {
+  run_acceptance("log", |command| {
+    command.args(["--follow", "--", "a", "b"]).assert().code(USER_ERROR_CODE);
+  });
+}
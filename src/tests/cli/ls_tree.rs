@@ -0,0 +1,45 @@
+use serial_test::serial;
+use crate::{tests::{run_acceptance, factory::{commit, TREE_PATHS}}, core::branch::Branch, lib::errors::USER_ERROR_CODE};
+
+fn output(command: &mut assert_cmd::Command) -> String {
+  String::from_utf8(command.output().unwrap().stdout).unwrap()
+}
+
+#[test]
+#[serial]
+fn ls_tree_lists_the_folder_entries() {
+  run_acceptance("ls-tree", |command| {
+    let commit = commit();
+    Branch::set("master", commit.id()).unwrap();
+    let tree = commit.tree().unwrap();
+    let blob = |path: &str| tree.get(path).unwrap().into_blob().unwrap().id().clone();
+    let expected_output = format!("100644 blob {}\ta\n100644 blob {}\tb\n100644 blob {}\tc\n", blob("a/b/a"), blob("a/b/b"), blob("a/b/c"));
+
+    assert_eq!(output(command.arg("HEAD:a/b")), expected_output);
+  });
+}
+
+#[test]
+#[serial]
+fn ls_tree_with_recursive_option_lists_every_file() {
+  run_acceptance("ls-tree", |command| {
+    let commit = commit();
+    let output = output(command.args(["-r", commit.id()]));
+    let paths = output.lines()
+      .map(|line| line.split_once('\t').unwrap().1)
+      .collect::<Vec<&str>>();
+
+    assert_eq!(paths, TREE_PATHS);
+  });
+}
+
+#[test]
+#[serial]
+fn ls_tree_given_file_exits_with_user_error_code() {
+  run_acceptance("ls-tree", |command| {
+    let commit = commit();
+    Branch::set("master", commit.id()).unwrap();
+
+    command.arg("HEAD:a/b/c").assert().code(USER_ERROR_CODE);
+  });
+}
@@ -5,3 +5,19 @@ mod config;
 mod commit;
 mod switch;
 mod status;
+mod fast_export;
+mod fast_import;
+mod convert_objects;
+mod log;
+mod blame;
+mod show;
+mod cat_file;
+mod hash_object;
+mod ls_tree;
+mod rev_parse;
+mod update_ref;
+mod alias;
+mod branch;
+mod verify_commit;
+mod bisect;
+mod grep;
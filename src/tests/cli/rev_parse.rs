@@ -0,0 +1,23 @@
+use serial_test::serial;
+use crate::{tests::{run_acceptance, factory::commit}, core::{commit::Commit, branch::Branch}, lib::errors::USER_ERROR_CODE};
+
+#[test]
+#[serial]
+fn rev_parse_prints_the_id_of_every_name() {
+  run_acceptance("rev-parse", |command| {
+    let first = commit();
+    let second = Commit::new(Some(first.id()), first.tree_id(), "second").unwrap();
+    Branch::set("master", second.id()).unwrap();
+    let expected_output = format!("{}\n{}\n{}\n", second.id(), first.id(), first.tree_id());
+
+    command.args(["master", &first.id()[..8], "HEAD~1:"]).assert().success().stdout(expected_output);
+  });
+}
+
+#[test]
+#[serial]
+fn rev_parse_given_unknown_name_exits_with_user_error_code() {
+  run_acceptance("rev-parse", |command| {
+    command.arg("HEAD").assert().code(USER_ERROR_CODE);
+  });
+}
@@ -0,0 +1,90 @@
+use serial_test::serial;
+use crate::{tests::run_acceptance, core::{commit::Commit, branch::Branch, blob::Blob, tree::{Tree, Node}}, lib::{object::Mode, errors::USER_ERROR_CODE}};
+
+// Commits `notes` and `old` first, then edits `notes`, removes `old` and adds the executable `bin/run`.
+fn history() -> Vec<Commit> {
+  let mut tree = Tree::new();
+  tree.insert("notes", Node::Blob(Blob::new("a\nb\nc\n").unwrap())).unwrap();
+  tree.insert("old", Node::Blob(Blob::new("old\n").unwrap())).unwrap();
+  let first = Commit::new(None, tree.pack().unwrap(), "first").unwrap();
+
+  tree.insert("notes", Node::Blob(Blob::new("a\nB\nc\n").unwrap())).unwrap();
+  tree.remove("old");
+  tree.insert("bin/run", Node::Blob(Blob::new("run\n").unwrap().with_mode(Mode::Executable))).unwrap();
+  let second = Commit::new(Some(first.id()), tree.pack().unwrap(), "second").unwrap();
+
+  Branch::set("master", second.id()).unwrap();
+  vec![first, second]
+}
+
+fn output(command: &mut assert_cmd::Command) -> String {
+  String::from_utf8(command.output().unwrap().stdout).unwrap()
+}
+
+#[test]
+#[serial]
+fn show_prints_the_commit_and_its_changes() {
+  run_acceptance("show", |command| {
+    let commits = history();
+    let expected_output = format!("\
+commit {}
+Author: admin <admin>
+Date:   {}
+
+    second
+
+diff --git a/bin/run b/bin/run
+new file mode 100755
+--- /dev/null
++++ b/bin/run
+@@ -0,0 +1 @@
++run
+diff --git a/notes b/notes
+--- a/notes
++++ b/notes
+@@ -1,3 +1,3 @@
+ a
+-b
++B
+ c
+diff --git a/old b/old
+deleted file mode 100644
+--- a/old
++++ /dev/null
+@@ -1 +0,0 @@
+-old
+", commits[1].id(), commits[1].author().date());
+
+    assert_eq!(output(command), expected_output);
+  });
+}
+
+#[test]
+#[serial]
+fn show_given_revision_and_path_prints_the_file() {
+  run_acceptance("show", |command| {
+    history();
+
+    assert_eq!(output(command.arg("HEAD~1:notes")), "a\nb\nc\n");
+  });
+}
+
+#[test]
+#[serial]
+fn show_given_folder_lists_its_entries() {
+  run_acceptance("show", |command| {
+    history();
+
+    assert_eq!(output(command.arg("master:")), "tree master:\n\nbin/\nnotes\n");
+  });
+}
+
+#[test]
+#[serial]
+fn show_given_unknown_path_exits_with_user_error_code() {
+  run_acceptance("show", |command| {
+    history();
+
+    command.arg("HEAD:missing").assert().code(USER_ERROR_CODE);
+  });
+}
@@ -1,4 +1,4 @@
-use std::fs;
+use std::{fs, os::unix::fs::PermissionsExt};
 use serial_test::serial;
 use crate::{tests::{run_acceptance, factory::{TREE_PATHS, tree, commit}}, core::{index::Index, head::{Head, Reference}}, lib::locale};
 
@@ -67,3 +67,34 @@ Changes not staged for commit:
     assert_eq!(output, expected_output.as_bytes());
   });
 }
+
+#[test]
+#[serial]
+fn status_prints_mode_only_changes() {
+  run_acceptance("status", |command| {
+    let commit = commit();
+    commit.tree().unwrap().unpack(locale()).unwrap();
+    Head::set(&Reference::Commit(commit.clone())).unwrap();
+
+    fs::set_permissions(locale().join("a/a/a"), fs::Permissions::from_mode(0o755)).unwrap();
+
+    let output = String::from_utf8(command.output().unwrap().stdout).unwrap();
+
+    assert!(output.contains("      modified:  a/a/a (mode 100644 -> 100755)\n"));
+  });
+}
+
+#[test]
+#[serial]
+fn status_prints_changes_made_after_staging_as_not_staged() {
+  run_acceptance("status", |command| {
+    fs::write(locale().join("file"), "staged").unwrap();
+    Index::add(&[locale().join("file")]).unwrap();
+    fs::write(locale().join("file"), "changed").unwrap();
+
+    let output = String::from_utf8(command.output().unwrap().stdout).unwrap();
+
+    assert!(output.contains("Changes to be committed:\n  (use \"rgit restore <PATHS>...\" to unstage)\n      modified:  file\n"));
+    assert!(output.contains("Changes not staged for commit:\n  (use \"rgit add <PATHS>...\" to update what will be committed)\n      modified:  file\n"));
+  });
+}
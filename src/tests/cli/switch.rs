@@ -1,5 +1,5 @@
 use serial_test::serial;
-use crate::{tests::{run_acceptance, factory::{commit, branch}}, core::head::{Head, Reference}};
+use crate::{tests::{run_acceptance, factory::{commit, branch, hook}}, core::head::{Head, Reference}, lib::errors::USER_ERROR_CODE};
 
 #[test]
 #[serial]
@@ -37,7 +37,7 @@ fn switch_handles_new_option() {
 #[serial]
 fn switch_handles_commit_option() {
   run_acceptance("switch", |command| {
-    command.args(["-c", "commit-id"]).assert().success();
+    command.args(["-c", "commit-id"]).assert().code(USER_ERROR_CODE);
   });
 }
 
@@ -53,7 +53,7 @@ fn switch_does_not_handle_commit_and_new_options_at_the_same_time() {
 #[serial]
 fn switch_handles_single_argument() {
   run_acceptance("switch", |command| {
-    command.arg("branch-name").assert().success();
+    command.arg("branch-name").assert().code(USER_ERROR_CODE);
   });
 }
 
@@ -61,7 +61,7 @@ fn switch_handles_single_argument() {
 #[serial]
 fn switch_if_branch_already_exists_on_create_outputs_unrecognised_branch_error() {
   run_acceptance("switch", |command| {
-    let output = command.args(["-n", "master"]).output().unwrap().stdout;
+    let output = command.args(["-n", "master"]).output().unwrap().stderr;
     let expected_output = format!("error: a branch named 'master' already exists\n");
 
     assert_eq!(output, expected_output.as_bytes());
@@ -72,7 +72,7 @@ fn switch_if_branch_already_exists_on_create_outputs_unrecognised_branch_error()
 #[serial]
 fn switch_if_branch_does_not_exists_on_change_outputs_unrecognised_branch_error() {
   run_acceptance("switch", |command| {
-    let output = command.arg("test-branch").output().unwrap().stdout;
+    let output = command.arg("test-branch").output().unwrap().stderr;
     let expected_output = format!("error: branch name 'test-branch' does not exist\n");
 
     assert_eq!(output, expected_output.as_bytes());
@@ -83,7 +83,7 @@ fn switch_if_branch_does_not_exists_on_change_outputs_unrecognised_branch_error(
 #[serial]
 fn switch_if_commit_does_not_exists_on_change_outputs_unrecognised_object_error() {
   run_acceptance("switch", |command| {
-    let output = command.args(["-c", "test-commit-id"]).output().unwrap().stdout;
+    let output = command.args(["-c", "test-commit-id"]).output().unwrap().stderr;
     let expected_output = format!("error: object identificator 'test-commit-id' did not match any object\n");
 
     assert_eq!(output, expected_output.as_bytes());
@@ -102,7 +102,7 @@ fn switch_creates_a_new_branch() {
     match Head::get().unwrap().reference() {
       Reference::Branch(branch) => {
         assert_eq!(branch.name(), "test-branch");
-        assert_eq!(branch.commit(), Some(commit));
+        assert_eq!(branch.commit().unwrap(), Some(commit));
       },
       _ => unreachable!()
     };
@@ -142,3 +142,23 @@ fn switch_changes_to_a_branch() {
     };
   });
 }
+
+#[test]
+#[serial]
+fn switch_if_branch_does_not_exists_exits_with_user_error_code() {
+  run_acceptance("switch", |command| {
+    command.arg("test-branch").assert().code(USER_ERROR_CODE).stdout("");
+  })
+}
+
+#[test]
+#[serial]
+fn switch_runs_the_post_switch_hook_with_both_commits() {
+  run_acceptance("switch", |command| {
+    let branch = branch();
+    hook("post-switch", "echo \"$1 $2\"");
+    let expected_output = format!("{} {}\n", "0".repeat(40), branch.commit_id().as_ref().unwrap());
+
+    command.arg(branch.name()).assert().success().stdout(expected_output);
+  });
+}
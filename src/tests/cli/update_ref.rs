@@ -0,0 +1,102 @@
+use std::fs;
+use serial_test::serial;
+use crate::{tests::{run_acceptance, factory::commit}, core::{branch::Branch, head::{Head, Reference}}, lib::{errors::{REPOSITORY_ERROR_CODE, USER_ERROR_CODE}, locale, initialize_git, layout::Layout, constants::{REPOSITORY_PATH, GIT_PACKED_REFS_PATH, LOCK_EXTENSION}}};
+
+#[test]
+#[serial]
+fn update_ref_points_the_current_branch_at_the_commit() {
+  run_acceptance("update-ref", |command| {
+    let commit = commit();
+
+    command.args(["HEAD", commit.id()]).assert().success();
+    assert_eq!(Head::get().unwrap().commit().unwrap().unwrap().id(), commit.id());
+  });
+}
+
+#[test]
+#[serial]
+fn update_ref_given_new_branch_creates_it() {
+  run_acceptance("update-ref", |command| {
+    let commit = commit();
+
+    command.args(["refs/heads/feature", &commit.id()[..8]]).assert().success();
+    assert_eq!(Branch::get("feature").unwrap().commit_id().as_ref().unwrap(), commit.id());
+  });
+}
+
+#[test]
+#[serial]
+fn update_ref_in_git_layout_given_packed_branch_moves_it() {
+  run_acceptance("update-ref", |command| {
+    fs::remove_dir_all(locale().join(REPOSITORY_PATH)).unwrap();
+    initialize_git().unwrap();
+    let (first, second) = (commit(), commit());
+    fs::write(locale().join(GIT_PACKED_REFS_PATH), format!("{} refs/heads/feature\n", first.id())).unwrap();
+
+    command.args(["refs/heads/feature", second.id()]).assert().success();
+    assert_eq!(Branch::get("feature").unwrap().commit_id().as_ref().unwrap(), second.id());
+  });
+}
+
+#[test]
+#[serial]
+fn update_ref_given_outdated_old_value_keeps_the_branch() {
+  run_acceptance("update-ref", |command| {
+    let (first, second) = (commit(), commit());
+    Branch::set("master", first.id()).unwrap();
+
+    command.args(["master", second.id(), second.id()]).assert().code(REPOSITORY_ERROR_CODE);
+    assert_eq!(Branch::get("master").unwrap().commit_id().as_ref().unwrap(), first.id());
+  });
+}
+
+#[test]
+#[serial]
+fn update_ref_given_reference_outside_branches_exits_with_user_error_code() {
+  run_acceptance("update-ref", |command| {
+    let commit = commit();
+
+    command.args(["refs/tags/v1", commit.id()]).assert().code(USER_ERROR_CODE);
+    assert!(Branch::get("refs/tags/v1").is_err());
+    assert!(!locale().join(Layout::get().branches_path()).join("refs").exists());
+  });
+}
+
+#[test]
+#[serial]
+fn update_ref_given_detached_head_and_outdated_old_value_keeps_head() {
+  run_acceptance("update-ref", |command| {
+    let (first, second) = (commit(), commit());
+    Head::set(&Reference::Commit(first.clone())).unwrap();
+
+    command.args(["HEAD", second.id(), second.id()]).assert().code(REPOSITORY_ERROR_CODE);
+    assert_eq!(Head::get().unwrap().commit().unwrap().unwrap().id(), first.id());
+  });
+}
+
+#[test]
+#[serial]
+fn update_ref_given_detached_head_and_old_value_compares_under_the_head_lock() {
+  run_acceptance("update-ref", |command| {
+    let (first, second) = (commit(), commit());
+    Head::set(&Reference::Commit(first.clone())).unwrap();
+    let lock_path = format!("{}.{}", locale().join(Layout::get().head_path()), LOCK_EXTENSION);
+    fs::write(&lock_path, "").unwrap();
+
+    command.args(["HEAD", second.id(), first.id()]).assert().failure();
+    fs::remove_file(&lock_path).unwrap();
+    assert_eq!(Head::get().unwrap().commit().unwrap().unwrap().id(), first.id());
+  });
+}
+
+#[test]
+#[serial]
+fn update_ref_given_detached_head_and_current_old_value_moves_head() {
+  run_acceptance("update-ref", |command| {
+    let (first, second) = (commit(), commit());
+    Head::set(&Reference::Commit(first.clone())).unwrap();
+
+    command.args(["HEAD", second.id(), first.id()]).assert().success();
+    assert_eq!(Head::get().unwrap().commit().unwrap().unwrap().id(), second.id());
+  });
+}
@@ -0,0 +1,37 @@
+use std::fs;
+use serial_test::serial;
+use crate::{tests::{run_acceptance, factory::commit}, core::{commit::Commit, branch::Branch}, lib::{identity::Identity, signature::{SigningKey, SIGNING_KEY_KEY, TRUSTED_KEYS_KEY}, config::{Config, Scope}, user::EMAIL_KEY, errors::USER_ERROR_CODE, locale}};
+
+fn signed_commit() -> Commit {
+  Config::set(Scope::Repository, EMAIL_KEY, "jane@example.com").unwrap();
+  Config::set(Scope::Repository, SIGNING_KEY_KEY, "key").unwrap();
+  Config::set(Scope::Repository, TRUSTED_KEYS_KEY, "trusted").unwrap();
+
+  let key = SigningKey::generate().unwrap();
+  fs::write(locale().join("trusted"), format!("jane@example.com {}\n", key.public_key())).unwrap();
+
+  let commit = Commit::signed(None, commit().tree_id(), "signed", Identity::author().unwrap(), Identity::committer().unwrap(), &key).unwrap();
+  Branch::set("master", commit.id()).unwrap();
+  commit
+}
+
+#[test]
+#[serial]
+fn verify_commit_given_signed_commit_prints_the_signer() {
+  run_acceptance("verify-commit", |command| {
+    let commit = signed_commit();
+
+    command.arg("HEAD").assert().success().stdout(format!("Good signature from jane@example.com on commit {}\n", commit.id()));
+  });
+}
+
+#[test]
+#[serial]
+fn verify_commit_given_unsigned_commit_fails() {
+  run_acceptance("verify-commit", |command| {
+    signed_commit();
+    let unsigned = commit();
+
+    command.args(["HEAD", unsigned.id()]).assert().code(USER_ERROR_CODE);
+  });
+}
@@ -0,0 +1,106 @@
+use serial_test::serial;
+use crate::{tests::{run_unit, factory::commit}, core::{bisect::{Bisect, Term, Step}, branch::Branch, commit::Commit, head::Reference}, lib::errors::Errors};
+
+// Returns a line of commits, oldest first.
+fn history(length: usize) -> Vec<Commit> {
+  let mut commits = vec![commit()];
+
+  for index in 1..length {
+    let parent = commits.last().unwrap();
+    commits.push(Commit::new(Some(parent.id()), parent.tree_id(), &format!("commit {}", index)).unwrap());
+  }
+
+  commits
+}
+
+#[test]
+#[serial]
+fn next_given_good_and_bad_commits_returns_the_middle_one() {
+  run_unit(|| {
+    let commits = history(10);
+    let mut bisect = Bisect::start().unwrap();
+
+    assert_eq!(bisect.next().unwrap(), Step::Waiting { bad: false, good: false });
+
+    bisect.mark(Term::Bad, &commits[9]).unwrap();
+    bisect.mark(Term::Good, &commits[0]).unwrap();
+
+    assert_eq!(bisect.next().unwrap(), Step::Test { commit: commits[4].clone(), remaining: 4 });
+  });
+}
+
+#[test]
+#[serial]
+fn next_given_skipped_middle_returns_the_closest_commit() {
+  run_unit(|| {
+    let commits = history(6);
+    let mut bisect = Bisect::start().unwrap();
+    bisect.mark(Term::Bad, &commits[5]).unwrap();
+    bisect.mark(Term::Good, &commits[0]).unwrap();
+    bisect.mark(Term::Skip, &commits[2]).unwrap();
+
+    assert!(matches!(bisect.next().unwrap(), Step::Test { commit, .. } if commit == commits[3]));
+  });
+}
+
+#[test]
+#[serial]
+fn next_given_adjacent_good_and_bad_commits_returns_the_bad_one() {
+  run_unit(|| {
+    let commits = history(3);
+    let mut bisect = Bisect::start().unwrap();
+    bisect.mark(Term::Good, &commits[1]).unwrap();
+    bisect.mark(Term::Bad, &commits[2]).unwrap();
+
+    assert_eq!(bisect.next().unwrap(), Step::Found(commits[2].clone()));
+  });
+}
+
+#[test]
+#[serial]
+fn next_given_only_skipped_commits_returns_them() {
+  run_unit(|| {
+    let commits = history(3);
+    let mut bisect = Bisect::start().unwrap();
+    bisect.mark(Term::Good, &commits[0]).unwrap();
+    bisect.mark(Term::Bad, &commits[2]).unwrap();
+    bisect.mark(Term::Skip, &commits[1]).unwrap();
+
+    assert_eq!(bisect.next().unwrap(), Step::OnlySkipped(vec![commits[2].clone(), commits[1].clone()]));
+  });
+}
+
+#[test]
+#[serial]
+fn get_reads_the_marked_commits_back() {
+  run_unit(|| {
+    let commits = history(3);
+    let mut bisect = Bisect::start().unwrap();
+    bisect.mark(Term::Good, &commits[0]).unwrap();
+    bisect.mark(Term::Bad, &commits[2]).unwrap();
+    bisect.mark(Term::Skip, &commits[1]).unwrap();
+
+    assert_eq!(Bisect::get().unwrap(), bisect);
+  });
+}
+
+#[test]
+#[serial]
+fn start_while_bisecting_returns_existing_bisect_error() {
+  run_unit(|| {
+    Bisect::start().unwrap();
+
+    assert!(matches!(Bisect::start(), Err(Errors::ExistingBisect)));
+  });
+}
+
+#[test]
+#[serial]
+fn reset_returns_the_starting_branch_and_ends_bisecting() {
+  run_unit(|| {
+    Bisect::start().unwrap();
+
+    assert_eq!(Bisect::get().unwrap().reset().unwrap(), Reference::Branch(Branch::get("master").unwrap()));
+    assert!(matches!(Bisect::get(), Err(Errors::MissingBisect)));
+  });
+}
@@ -1,6 +1,6 @@
-use std::{fs::File, io::{Read, Write}};
+use std::{fs::{self, File}, io::{Read, Write}, os::unix::fs::{PermissionsExt, symlink}};
 use serial_test::serial;
-use crate::{core::blob::Blob, lib::{decompress, constants::{OBJECTS_PATH, BLOB_TYPE}, locale, errors::Errors, object::FromId}, tests::run_unit};
+use crate::{core::blob::Blob, lib::{decompress, constants::{OBJECTS_PATH, BLOB_TYPE}, locale, errors::Errors, object::{FromId, Mode}}, tests::run_unit};
 
 const DUMMY_TEXT: &str = "Lorem ipsum dolor sit amet, consectetur adipiscing elit.";
 const BLOB_HASH: &str = "52837ea8d4dbe8c130c2d829727ef511bcc262a1";
@@ -58,3 +58,30 @@ fn from_id_given_existing_id_returns_blob() {
     assert_eq!(blob, from_blob);
   });
 }
+
+#[test]
+#[serial]
+fn from_path_given_executable_file_creates_executable_blob() {
+  run_unit(|| {
+    let path = locale().join("script");
+    File::create(&path).unwrap().write_all(DUMMY_TEXT.as_bytes()).unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+
+    let blob = Blob::from_path(&path).unwrap();
+
+    assert_eq!(blob.id(), BLOB_HASH);
+    assert_eq!(blob.mode(), &Mode::Executable);
+  });
+}
+
+#[test]
+#[serial]
+fn from_path_given_symlink_creates_blob_of_the_link_target() {
+  run_unit(|| {
+    symlink("missing-target", locale().join("link")).unwrap();
+
+    let blob = Blob::from_path(locale().join("link")).unwrap();
+
+    assert_eq!(blob, Blob::new("missing-target").unwrap().with_mode(Mode::Symlink));
+  });
+}
@@ -1,6 +1,6 @@
-use std::{fs::File, io::Read};
+use std::{fs::{self, File}, io::Read};
 use serial_test::serial;
-use crate::{tests::{run_unit, factory::commit}, core::branch::Branch, lib::{constants::BRANCHES_PATH, locale, errors::Errors}};
+use crate::{tests::{run_unit, run_unit_git, factory::commit}, core::{branch::Branch, head::{Head, Reference}, tree::Tree, commit::Commit}, lib::{constants::{BRANCHES_PATH, HEAD_PATH, LOCK_EXTENSION, GIT_BRANCHES_PATH, GIT_PACKED_REFS_PATH}, locale, errors::Errors}};
 
 const TEST_BRANCH_NAME: &str = "test-branch-name";
 
@@ -99,6 +99,31 @@ fn set_given_non_existing_name_returns_missing_branch_error() {
   });
 }
 
+#[test]
+#[serial]
+fn replace_given_expected_commit_updates_branch_commit_id() {
+  run_unit(|| {
+    let (first, second) = (commit(), commit());
+    Branch::replace(TEST_BRANCH_NAME, first.id(), None).unwrap();
+    Branch::replace(TEST_BRANCH_NAME, second.id(), Some(first.id())).unwrap();
+
+    assert_eq!(Branch::get(TEST_BRANCH_NAME).unwrap().commit_id().as_ref().unwrap(), second.id());
+  });
+}
+
+#[test]
+#[serial]
+fn replace_given_outdated_commit_returns_stale_reference_error() {
+  run_unit(|| {
+    let (first, second) = (commit(), commit());
+    Branch::new(TEST_BRANCH_NAME, Some(first.id())).unwrap();
+
+    assert!(matches!(Branch::replace(TEST_BRANCH_NAME, second.id(), None), Err(Errors::StaleReference(_))));
+    assert!(matches!(Branch::replace(TEST_BRANCH_NAME, second.id(), Some(second.id())), Err(Errors::StaleReference(_))));
+    assert_eq!(Branch::get(TEST_BRANCH_NAME).unwrap().commit_id().as_ref().unwrap(), first.id());
+  });
+}
+
 #[test]
 #[serial]
 fn remove_given_name_deletes_branch() {
@@ -130,7 +155,7 @@ fn commit_if_present_returns_commit() {
     let commit = commit();
     let branch = Branch::new(TEST_BRANCH_NAME, Some(commit.id())).unwrap();
 
-    assert_eq!(branch.commit(), Some(commit));
+    assert_eq!(branch.commit().unwrap(), Some(commit));
   });
 }
 
@@ -140,7 +165,7 @@ fn commit_if_not_present_returns_none() {
   run_unit(|| {
     let branch = Branch::new(TEST_BRANCH_NAME, None).unwrap();
 
-    assert_eq!(branch.commit(), None);
+    assert_eq!(branch.commit().unwrap(), None);
   });
 }
 
@@ -156,3 +181,184 @@ fn initial_branch_file() {
     assert!(commit_id.is_empty());
   });
 }
+
+#[test]
+#[serial]
+fn remove_given_current_branch_returns_current_branch_error() {
+  run_unit(|| {
+    assert!(matches!(Branch::remove("master"), Err(Errors::CurrentBranch(name)) if name == "master"));
+    assert!(locale().join(BRANCHES_PATH).join("master").exists());
+  });
+}
+
+#[test]
+#[serial]
+fn rename_given_current_branch_moves_head() {
+  run_unit(|| {
+    let commit = commit();
+    Branch::set("master", commit.id()).unwrap();
+
+    Branch::rename("master", TEST_BRANCH_NAME).unwrap();
+
+    assert!(!locale().join(BRANCHES_PATH).join("master").exists());
+    assert_eq!(Head::get().unwrap().reference(), &Reference::Branch(Branch::get(TEST_BRANCH_NAME).unwrap()));
+    assert_eq!(Head::get().unwrap().commit().unwrap(), Some(commit));
+  });
+}
+
+#[test]
+#[serial]
+fn rename_given_name_nested_under_the_branch_moves_it_into_a_folder() {
+  run_unit(|| {
+    let commit = commit();
+    Branch::set("master", commit.id()).unwrap();
+
+    let branch = Branch::rename("master", "master/old").unwrap();
+
+    assert_eq!(branch, Branch::get("master/old").unwrap());
+    assert_eq!(branch.commit_id().as_ref(), Some(commit.id()));
+    assert!(locale().join(BRANCHES_PATH).join("master").is_dir());
+    assert_eq!(Head::get().unwrap().reference(), &Reference::Branch(branch));
+  });
+}
+
+#[test]
+#[serial]
+fn rename_given_folder_of_the_branch_moves_it_out_of_the_folder() {
+  run_unit(|| {
+    let commit = commit();
+    Branch::new("a/b", Some(commit.id())).unwrap();
+
+    Branch::rename("a/b", "a").unwrap();
+
+    assert!(Branch::get("a/b").is_err());
+    assert_eq!(Branch::get("a").unwrap().commit_id().as_ref(), Some(commit.id()));
+  });
+}
+
+#[test]
+#[serial]
+fn rename_given_folder_of_other_branches_returns_existing_branch_error() {
+  run_unit(|| {
+    let commit = commit();
+    Branch::new("a/b", Some(commit.id())).unwrap();
+    Branch::new("a/c", Some(commit.id())).unwrap();
+
+    assert!(matches!(Branch::rename("a/b", "a"), Err(Errors::ExistingBranch(name)) if name == "a/"));
+    assert!(Branch::get("a/b").is_ok());
+  });
+}
+
+#[test]
+#[serial]
+fn rename_given_locked_head_restores_the_branch() {
+  run_unit(|| {
+    let commit = commit();
+    Branch::set("master", commit.id()).unwrap();
+    File::create(locale().join(format!("{}.{}", HEAD_PATH, LOCK_EXTENSION))).unwrap();
+
+    assert!(matches!(Branch::rename("master", "master/old"), Err(Errors::LockedFile(_))));
+    assert!(Branch::get("master/old").is_err());
+    assert_eq!(Branch::get("master").unwrap().commit_id().as_ref(), Some(commit.id()));
+  });
+}
+
+#[test]
+#[serial]
+fn copy_given_existing_name_returns_existing_branch_error() {
+  run_unit(|| {
+    let commit = commit();
+    Branch::set("master", commit.id()).unwrap();
+
+    let copy = Branch::copy("master", TEST_BRANCH_NAME).unwrap();
+
+    assert_eq!(copy.commit_id().as_ref(), Some(commit.id()));
+    assert!(matches!(Branch::copy("master", TEST_BRANCH_NAME), Err(Errors::ExistingBranch(_))));
+  });
+}
+
+#[test]
+#[serial]
+fn new_given_slashed_name_nests_the_branch() {
+  run_unit(|| {
+    let commit = commit();
+    Branch::new("feature/login", Some(commit.id())).unwrap();
+
+    assert!(locale().join(BRANCHES_PATH).join("feature").join("login").is_file());
+    assert!(Branch::list().unwrap().iter().any(|branch| branch.name() == "feature/login"));
+    assert!(matches!(Branch::new("feature", Some(commit.id())), Err(Errors::ExistingBranch(_))));
+    assert!(matches!(Branch::new("feature/login/form", Some(commit.id())), Err(Errors::ExistingBranch(name)) if name == "feature/login"));
+  });
+}
+
+#[test]
+#[serial]
+fn new_given_invalid_name_returns_bad_branch_name_error() {
+  run_unit(|| {
+    for name in ["a..b", "topic.lock", "feature/.hidden", "bad\tname", "trailing/", "-flag", "a@{1}"] {
+      assert!(matches!(Branch::new(name, None), Err(Errors::BadBranchName(_))), "{}", name);
+    }
+  });
+}
+
+#[test]
+#[serial]
+fn remove_given_last_nested_branch_removes_empty_folders() {
+  run_unit(|| {
+    let commit = commit();
+    Branch::new("feature/auth/login", Some(commit.id())).unwrap();
+
+    Branch::remove("feature/auth/login").unwrap();
+
+    assert!(!locale().join(BRANCHES_PATH).join("feature").exists());
+    assert!(locale().join(BRANCHES_PATH).exists());
+  });
+}
+
+#[test]
+#[serial]
+fn get_in_git_layout_given_packed_branch_returns_it() {
+  run_unit_git(|| {
+    let mut root = Tree::new();
+    root.pack().unwrap();
+    let commit = Commit::new(None, root.id(), "first").unwrap();
+    let packed_refs = format!("# pack-refs with: peeled fully-peeled sorted\n{} refs/heads/master\n{} refs/heads/topic\n", commit.id(), commit.id());
+    fs::write(locale().join(GIT_PACKED_REFS_PATH), packed_refs).unwrap();
+
+    assert!(!locale().join(GIT_BRANCHES_PATH).join("master").exists());
+    assert_eq!(Head::get().unwrap().commit().unwrap(), Some(commit.clone()));
+    assert_eq!(Branch::list().unwrap().iter().map(|branch| branch.name().as_str()).collect::<Vec<&str>>(), ["master", "topic"]);
+
+    Branch::remove("topic").unwrap();
+
+    assert!(matches!(Branch::get("topic"), Err(Errors::UnrecognisedBranch(_))));
+    assert_eq!(fs::read_to_string(locale().join(GIT_PACKED_REFS_PATH)).unwrap(), format!("# pack-refs with: peeled fully-peeled sorted\n{} refs/heads/master\n", commit.id()));
+  });
+}
+
+#[test]
+#[serial]
+fn commit_given_missing_object_returns_error() {
+  run_unit(|| {
+    Branch::set("master", &"0".repeat(40)).unwrap();
+
+    assert!(matches!(Branch::get("master").unwrap().commit(), Err(Errors::UnrecognisedObject(_))));
+  });
+}
+
+#[test]
+#[serial]
+fn rename_in_git_layout_given_packed_current_branch_writes_it_loose() {
+  run_unit_git(|| {
+    let mut root = Tree::new();
+    root.pack().unwrap();
+    let commit = Commit::new(None, root.id(), "first").unwrap();
+    fs::write(locale().join(GIT_PACKED_REFS_PATH), format!("{} refs/heads/master\n", commit.id())).unwrap();
+
+    Branch::rename("master", "master/old").unwrap();
+
+    assert_eq!(fs::read_to_string(locale().join(GIT_BRANCHES_PATH).join("master/old")).unwrap(), format!("{}\n", commit.id()));
+    assert_eq!(fs::read_to_string(locale().join(GIT_PACKED_REFS_PATH)).unwrap(), "");
+    assert_eq!(Head::get().unwrap().reference(), &Reference::Branch(Branch::get("master/old").unwrap()));
+  });
+}
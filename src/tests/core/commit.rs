@@ -1,6 +1,6 @@
-use std::{fs::File, io::Read};
+use std::{env, fs::File, io::Read};
 use serial_test::serial;
-use crate::{tests::{run_unit, factory::tree}, core::commit::Commit, lib::{locale, constants::OBJECTS_PATH, decompress, errors::Errors, object::FromId}};
+use crate::{tests::{run_unit, factory::tree}, core::commit::Commit, lib::{locale, identity::Identity, constants::{OBJECTS_PATH, AUTHOR_DATE_ENV, COMMITTER_DATE_ENV}, decompress, errors::Errors, object::{FromId, Object}, write_object_bytes}};
 
 const COMMIT_MESSAGE: &str = "commit-message";
 
@@ -15,13 +15,10 @@ fn new_given_parent_commit_and_tree_creates_commit() {
     let location = locale().join(OBJECTS_PATH).join(&commit.id()[..2]).join(&commit.id()[2..]);
     File::open(location).unwrap().read_to_end(&mut compressed).unwrap();
 
-    let expected = format!("cmit{}\n{}\n{}\n{}\n{}\n{}",
-      "0",
+    let expected = format!("cmittree {}\nauthor admin <admin> {}\ncommitter admin <admin> {}\n\ncommit-message\n",
       tree.id(),
-      "commit-message",
-      commit.date().to_rfc3339(),
-      "admin",
-      "admin",
+      commit.author().date().to_rfc3339(),
+      commit.committer().date().to_rfc3339(),
     );
 
     assert!(!commit.id().is_empty());
@@ -83,3 +80,77 @@ fn from_id_given_existing_id_returns_commit() {
     assert_eq!(commit, from_commit);
   });
 }
+
+#[test]
+#[serial]
+fn from_id_given_multiline_message_returns_commit() {
+  run_unit(|| {
+    let parent = Commit::new(None, tree().id(), COMMIT_MESSAGE).unwrap();
+    let commit = Commit::new(Some(parent.id()), parent.tree_id(), "subject\n\nbody\n  indented").unwrap();
+
+    assert_eq!(Commit::from_id(commit.id()).unwrap(), commit);
+  });
+}
+
+#[test]
+#[serial]
+fn from_id_given_legacy_commit_returns_commit() {
+  run_unit(|| {
+    let tree = tree();
+    let bytes = format!("0\n{}\n{}\n2022-01-01T12:00:00+00:00\njane\njane@mail.com", tree.id(), COMMIT_MESSAGE);
+    let id = write_object_bytes(Object::Commit, bytes).unwrap();
+    let commit = Commit::from_id(&id).unwrap();
+
+    assert_eq!(commit.parent_id(), &None);
+    assert_eq!(commit.tree_id(), tree.id());
+    assert_eq!(commit.message(), COMMIT_MESSAGE);
+    assert_eq!(commit.author().username(), "jane");
+    assert_eq!(commit.author().date().to_rfc3339(), "2022-01-01T12:00:00+00:00");
+  });
+}
+
+#[test]
+#[serial]
+fn with_identities_keeps_separate_author_and_committer_with_their_offsets() {
+  run_unit(|| {
+    let author = Identity::parse("Jane <jane@mail.com> 2022-01-01T12:00:00+02:00").unwrap();
+    let committer = Identity::parse("John <john@mail.com> 2022-01-02T12:00:00-05:00").unwrap();
+    let commit = Commit::with_identities(None, tree().id(), COMMIT_MESSAGE, author.clone(), committer.clone()).unwrap();
+
+    assert_eq!(commit.author(), &author);
+    assert_eq!(commit.committer(), &committer);
+    assert_eq!(Commit::from_id(commit.id()).unwrap(), commit);
+  });
+}
+
+#[test]
+#[serial]
+fn new_given_date_overrides_creates_reproducible_commits() {
+  run_unit(|| {
+    env::set_var(AUTHOR_DATE_ENV, "1641031200 +0200");
+    env::set_var(COMMITTER_DATE_ENV, "1641031200 +0200");
+
+    let tree = tree();
+    let first = Commit::new(None, tree.id(), COMMIT_MESSAGE).unwrap();
+    let second = Commit::new(None, tree.id(), COMMIT_MESSAGE).unwrap();
+
+    assert_eq!(first.id(), second.id());
+
+    env::remove_var(AUTHOR_DATE_ENV);
+    env::remove_var(COMMITTER_DATE_ENV);
+  });
+}
+
+#[test]
+#[serial]
+fn parent_given_legacy_parent_returns_it() {
+  run_unit(|| {
+    let tree = tree();
+    let bytes = format!("0\n{}\n{}\n2022-01-01T12:00:00+00:00\njane\njane@mail.com", tree.id(), COMMIT_MESSAGE);
+    let legacy_id = write_object_bytes(Object::Commit, bytes).unwrap();
+    let commit = Commit::new(Some(&legacy_id), tree.id(), COMMIT_MESSAGE).unwrap();
+
+    assert_eq!(commit.parent(), Some(Commit::from_id(&legacy_id).unwrap()));
+    assert_eq!(Commit::from_id(commit.id()).unwrap().parent_id().as_deref(), Some(legacy_id.as_str()));
+  });
+}
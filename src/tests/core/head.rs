@@ -57,7 +57,7 @@ fn commit_returns_reference_branch_commit() {
     Head::set(&Reference::Branch(branch.clone())).unwrap();
     let head = Head::get().unwrap();
 
-    assert_eq!(head.commit(), branch.commit());
+    assert_eq!(head.commit().unwrap(), branch.commit().unwrap());
   });
 }
 
@@ -69,7 +69,7 @@ fn commit_returns_reference_commit_commit() {
     Head::set(&Reference::Commit(commit.clone())).unwrap();
     let head = Head::get().unwrap();
 
-    assert_eq!(head.commit(), Some(commit));
+    assert_eq!(head.commit().unwrap(), Some(commit));
   });
 }
 
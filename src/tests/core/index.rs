@@ -1,7 +1,7 @@
-use std::{fs::{File, self}, io::{Read, Write}};
+use std::{fs::{File, self}, io::{Read, Write}, path::Path};
 use lipsum::lipsum;
 use serial_test::serial;
-use crate::{tests::{run_unit, factory::{tree, commit}}, lib::{locale, constants::INDEX_PATH, decompress}, core::{index::Index, head::{Head, Reference}, blob::Blob}};
+use crate::{tests::{run_unit, factory::{tree, commit}}, lib::{locale, constants::INDEX_PATH, decompress, compress}, core::{index::{Index, Stage}, head::{Head, Reference}, blob::Blob}};
 
 #[test]
 #[serial]
@@ -21,8 +21,8 @@ fn add_given_untracked_file_paths_inserts_to_index() {
 
     let text = String::from_utf8(decompress(compressed_data).unwrap()).unwrap();
 
-    assert!(text.contains(&format!("a/a/a {}", blob_1.id())));
-    assert!(text.contains(&format!("a/a/b {}", blob_2.id())));
+    assert!(text.contains(&format!("a/a/a\0{}\n", blob_1.id())));
+    assert!(text.contains(&format!("a/a/b\0{}\n", blob_2.id())));
   });
 }
 
@@ -48,8 +48,8 @@ fn add_given_modified_file_paths_inserts_to_index() {
 
     let text = String::from_utf8(decompress(compressed_data).unwrap()).unwrap();
 
-    assert!(text.contains(&format!("a/a/a {}", blob_1.id())));
-    assert!(text.contains(&format!("a/a/b {}", blob_2.id())));
+    assert!(text.contains(&format!("a/a/a\0{}\n", blob_1.id())));
+    assert!(text.contains(&format!("a/a/b\0{}\n", blob_2.id())));
   });
 }
 
@@ -71,8 +71,8 @@ fn add_given_deleted_file_paths_inserts_to_index() {
 
     let text = String::from_utf8(decompress(compressed_data).unwrap()).unwrap();
 
-    assert!(text.contains(&format!("a/a/a DELETED")));
-    assert!(text.contains(&format!("a/a/b DELETED")));
+    assert!(text.contains(&format!("a/a/a\0DELETED\n")));
+    assert!(text.contains(&format!("a/a/b\0DELETED\n")));
   });
 }
 
@@ -93,3 +93,90 @@ fn remove_given_file_paths_removes_from_index() {
     assert!(data.is_empty());
   });
 }
+
+#[test]
+#[serial]
+fn add_given_file_path_with_spaces_inserts_to_index() {
+  run_unit(|| {
+    let locale = locale();
+    File::create(locale.join("my notes.txt")).unwrap().write_all(lipsum(20).as_bytes()).unwrap();
+    let blob = Blob::from_path(locale.join("my notes.txt")).unwrap();
+
+    Index::add(&[locale.join("my notes.txt")]).unwrap();
+    let index = Index::get().unwrap();
+
+    assert_eq!(index.staged_paths().get(Path::new("my notes.txt")), Some(&Stage::Blob(blob)));
+  });
+}
+
+#[test]
+#[serial]
+#[cfg(unix)]
+fn add_given_folder_with_non_utf8_file_name_inserts_to_index() {
+  use std::{ffi::OsStr, os::unix::ffi::OsStrExt};
+
+  run_unit(|| {
+    let locale = locale();
+    let path = Path::new("folder").join(OsStr::from_bytes(b"caf\xe9"));
+    fs::create_dir(locale.join("folder")).unwrap();
+    File::create(locale.as_std_path().join(&path)).unwrap().write_all(lipsum(20).as_bytes()).unwrap();
+    let blob = Blob::from_path(locale.as_std_path().join(&path)).unwrap();
+
+    Index::add(&[locale.join("folder")]).unwrap();
+    let index = Index::get().unwrap();
+
+    assert_eq!(index.staged_paths().get(&path), Some(&Stage::Blob(blob)));
+  });
+}
+
+#[test]
+#[serial]
+fn get_given_legacy_index_returns_staged_paths() {
+  run_unit(|| {
+    let locale = locale();
+    let blob = Blob::new(lipsum(20)).unwrap();
+    let legacy = format!("a/a/a {}\na/a/b DELETED", blob.id());
+    File::create(locale.join(INDEX_PATH)).unwrap().write_all(&compress(legacy).unwrap()).unwrap();
+
+    let index = Index::get().unwrap();
+
+    assert_eq!(index.staged_paths().get(Path::new("a/a/a")), Some(&Stage::Blob(blob)));
+    assert_eq!(index.staged_paths().get(Path::new("a/a/b")), Some(&Stage::Deleted));
+  });
+}
+
+#[test]
+#[serial]
+fn add_tracked_stages_modified_and_deleted_files_but_not_untracked_ones() {
+  run_unit(|| {
+    let locale = locale();
+    let commit = commit();
+    commit.tree().unwrap().unpack(&locale).unwrap();
+
+    Head::set(&Reference::Commit(commit)).unwrap();
+    File::create(locale.join("a/a/a")).unwrap().write_all(lipsum(20).as_bytes()).unwrap();
+    fs::remove_file(locale.join("a/a/b")).unwrap();
+    File::create(locale.join("untracked")).unwrap();
+
+    Index::add_tracked().unwrap();
+    let staged_paths = Index::get().unwrap().staged_paths().clone();
+
+    assert_eq!(staged_paths.len(), 2);
+    assert_eq!(staged_paths.get(Path::new("a/a/a")), Some(&Stage::Blob(Blob::from_path(locale.join("a/a/a")).unwrap())));
+    assert_eq!(staged_paths.get(Path::new("a/a/b")), Some(&Stage::Deleted));
+  });
+}
+
+#[test]
+#[serial]
+fn from_paths_stages_the_paths_without_saving_the_index() {
+  run_unit(|| {
+    let locale = locale();
+    tree().unpack(&locale).unwrap();
+
+    let index = Index::from_paths(&[locale.join("a/a/a")]).unwrap();
+
+    assert_eq!(index.staged_paths().keys().collect::<Vec<_>>(), [Path::new("a/a/a")]);
+    assert!(Index::get().unwrap().staged_paths().is_empty());
+  });
+}
@@ -4,3 +4,5 @@ mod commit;
 mod branch;
 mod head;
 mod index;
+mod revision;
+mod bisect;
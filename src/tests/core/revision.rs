@@ -0,0 +1,87 @@
+use serial_test::serial;
+use crate::{tests::{run_unit, factory::{commit, tree}}, core::{revision::Revision, commit::Commit, branch::Branch, head::{Head, Reference}}, lib::errors::Errors};
+
+fn history() -> Vec<Commit> {
+  let tree = tree();
+  let first = Commit::new(None, tree.id(), "first").unwrap();
+  let second = Commit::new(Some(first.id()), tree.id(), "second").unwrap();
+  let third = Commit::new(Some(second.id()), tree.id(), "third").unwrap();
+
+  Branch::set("master", third.id()).unwrap();
+  vec![first, second, third]
+}
+
+#[test]
+#[serial]
+fn resolve_given_head_or_branch_returns_its_commit() {
+  run_unit(|| {
+    let commits = history();
+
+    assert_eq!(Revision::resolve("HEAD").unwrap(), commits[2]);
+    assert_eq!(Revision::resolve("@").unwrap(), commits[2]);
+    assert_eq!(Revision::resolve("master").unwrap(), commits[2]);
+  });
+}
+
+#[test]
+#[serial]
+fn resolve_given_full_or_short_id_returns_the_commit() {
+  run_unit(|| {
+    let commits = history();
+
+    assert_eq!(Revision::resolve(commits[1].id()).unwrap(), commits[1]);
+    assert_eq!(Revision::resolve(&commits[1].id()[..10]).unwrap(), commits[1]);
+  });
+}
+
+#[test]
+#[serial]
+fn resolve_given_ancestor_steps_walks_to_the_parents() {
+  run_unit(|| {
+    let commits = history();
+
+    assert_eq!(Revision::resolve("HEAD~").unwrap(), commits[1]);
+    assert_eq!(Revision::resolve("master~2").unwrap(), commits[0]);
+    assert_eq!(Revision::resolve("HEAD^^").unwrap(), commits[0]);
+    assert_eq!(Revision::resolve("HEAD~1^0").unwrap(), commits[1]);
+  });
+}
+
+#[test]
+#[serial]
+fn resolve_given_unknown_revision_returns_unrecognised_revision_error() {
+  run_unit(|| {
+    history();
+
+    assert!(matches!(Revision::resolve("HEAD~3"), Err(Errors::UnrecognisedRevision(_))));
+    assert!(matches!(Revision::resolve("HEAD^2"), Err(Errors::UnrecognisedRevision(_))));
+    assert!(matches!(Revision::resolve("feature"), Err(Errors::UnrecognisedRevision(_))));
+    assert!(matches!(Revision::resolve("abc"), Err(Errors::UnrecognisedRevision(_))));
+  });
+}
+
+#[test]
+#[serial]
+fn resolve_given_detached_head_returns_the_head_commit() {
+  run_unit(|| {
+    let commit = commit();
+    Head::set(&Reference::Commit(commit.clone())).unwrap();
+
+    assert_eq!(Revision::resolve("HEAD").unwrap(), commit);
+  });
+}
+
+#[test]
+#[serial]
+fn resolve_object_given_revision_and_path_returns_the_node_id() {
+  run_unit(|| {
+    let commits = history();
+    let tree = commits[0].tree().unwrap();
+
+    assert_eq!(&Revision::resolve_object("HEAD~2:").unwrap(), commits[0].tree_id());
+    assert_eq!(&Revision::resolve_object("HEAD:a/b").unwrap(), tree.get("a/b").unwrap().into_tree().unwrap().id());
+    assert_eq!(&Revision::resolve_object("master:a/b/c").unwrap(), tree.get("a/b/c").unwrap().into_blob().unwrap().id());
+    assert_eq!(&Revision::resolve_object("HEAD").unwrap(), commits[2].id());
+    assert!(matches!(Revision::resolve_object("HEAD:d"), Err(Errors::UnrecognisedPath(_))));
+  });
+}
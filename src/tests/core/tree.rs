@@ -1,6 +1,6 @@
-use std::{fs::File, io::Read};
+use std::{fs::{self, File}, io::Read, ffi::OsStr, path::Path};
 use serial_test::serial;
-use crate::{core::tree::{Tree, Node}, tests::{run_unit, factory::{blob, blob_and_text}}, lib::{decompress, locale, constants::OBJECTS_PATH, object::FromId}};
+use crate::{core::{tree::{Tree, Node}, blob::Blob}, tests::{run_unit, factory::{blob, blob_and_text}}, lib::{decompress, locale, constants::OBJECTS_PATH, object::{FromId, Mode, Entry, pack_entries}, write_object_bytes, object_location, object::Object}};
 
 #[test]
 #[serial]
@@ -21,11 +21,11 @@ fn insert_given_single_component_path_and_node_creates_a_direct_child() {
     let blob = blob();
     let tree = Tree::new();
 
-    root.insert("blob", Node::Blob(blob.clone()));
-    root.insert("tree", Node::Tree(tree.clone()));
+    root.insert("blob", Node::Blob(blob.clone())).unwrap();
+    root.insert("tree", Node::Tree(tree.clone())).unwrap();
 
-    assert_eq!(root.children().get("blob").unwrap(), &blob);
-    assert_eq!(root.children().get("tree").unwrap(), &tree);
+    assert_eq!(root.children().get(OsStr::new("blob")).unwrap(), &blob);
+    assert_eq!(root.children().get(OsStr::new("tree")).unwrap(), &tree);
   });
 }
 
@@ -37,14 +37,14 @@ fn insert_given_multi_component_path_and_node_creates_child_with_preceding_trees
     let blob = blob();
     let tree = Tree::new();
 
-    root.insert("tree/tree/blob", Node::Blob(blob.clone()));
-    root.insert("tree/tree/tree", Node::Tree(tree.clone()));
+    root.insert("tree/tree/blob", Node::Blob(blob.clone())).unwrap();
+    root.insert("tree/tree/tree", Node::Tree(tree.clone())).unwrap();
 
-    let inner_tree = root.children().get("tree").unwrap().into_tree().unwrap()
-      .children().get("tree").unwrap().into_tree().unwrap();
+    let inner_tree = root.children().get(OsStr::new("tree")).unwrap().into_tree().unwrap()
+      .children().get(OsStr::new("tree")).unwrap().into_tree().unwrap();
 
-    assert_eq!(inner_tree.children().get("blob").unwrap(), &blob);
-    assert_eq!(inner_tree.children().get("tree").unwrap(), &tree);
+    assert_eq!(inner_tree.children().get(OsStr::new("blob")).unwrap(), &blob);
+    assert_eq!(inner_tree.children().get(OsStr::new("tree")).unwrap(), &tree);
   });
 }
 
@@ -56,15 +56,29 @@ fn insert_given_multi_component_path_and_node_creates_child_overwriting_any_obst
     let blob = blob();
     let tree = Tree::new();
 
-    root.insert("tree/blob", Node::Blob(blob.clone()));
-    root.insert("tree/blob/blob", Node::Blob(blob.clone()));
-    root.insert("tree/blob/tree", Node::Tree(tree.clone()));
+    root.insert("tree/blob", Node::Blob(blob.clone())).unwrap();
+    root.insert("tree/blob/blob", Node::Blob(blob.clone())).unwrap();
+    root.insert("tree/blob/tree", Node::Tree(tree.clone())).unwrap();
 
-    let inner_tree = root.children().get("tree").unwrap().into_tree().unwrap()
-      .children().get("blob").unwrap().into_tree().unwrap();
+    let inner_tree = root.children().get(OsStr::new("tree")).unwrap().into_tree().unwrap()
+      .children().get(OsStr::new("blob")).unwrap().into_tree().unwrap();
 
-    assert_eq!(inner_tree.children().get("blob").unwrap(), &blob);
-    assert_eq!(inner_tree.children().get("tree").unwrap(), &tree);
+    assert_eq!(inner_tree.children().get(OsStr::new("blob")).unwrap(), &blob);
+    assert_eq!(inner_tree.children().get(OsStr::new("tree")).unwrap(), &tree);
+  });
+}
+
+#[test]
+#[serial]
+fn insert_given_path_leaving_the_tree_or_entering_a_repository_folder_returns_error() {
+  run_unit(|| {
+    let mut root = Tree::new();
+
+    for path in ["", "..", "a/../../b", "/a", ".rgit/hooks/post-switch", "a/.git/config"] {
+      assert!(root.insert(path, Node::Blob(blob())).is_err());
+    }
+
+    assert!(root.children().is_empty());
   });
 }
 
@@ -75,12 +89,12 @@ fn remove_given_non_existing_path_has_no_effect() {
     let mut root = Tree::new();
     let blob = blob();
 
-    root.insert("tree/blob", Node::Blob(blob.clone()));
+    root.insert("tree/blob", Node::Blob(blob.clone())).unwrap();
     root.remove("tree/tree");
 
-    let inner_tree = root.children().get("tree").unwrap().into_tree().unwrap();
+    let inner_tree = root.children().get(OsStr::new("tree")).unwrap().into_tree().unwrap();
 
-    assert_eq!(inner_tree.children().get("blob").unwrap(), &blob);
+    assert_eq!(inner_tree.children().get(OsStr::new("blob")).unwrap(), &blob);
   });
 }
 
@@ -92,16 +106,16 @@ fn remove_given_existing_path_removes_the_target_node() {
     let blob = blob();
     let tree = Tree::new();
 
-    root.insert("tree/blob", Node::Blob(blob.clone()));
-    root.insert("tree/tree", Node::Tree(tree.clone()));
+    root.insert("tree/blob", Node::Blob(blob.clone())).unwrap();
+    root.insert("tree/tree", Node::Tree(tree.clone())).unwrap();
 
     root.remove("tree/blob");
     root.remove("tree/tree");
 
-    let inner_tree = root.children().get("tree").unwrap().into_tree().unwrap();
+    let inner_tree = root.children().get(OsStr::new("tree")).unwrap().into_tree().unwrap();
 
-    assert_eq!(inner_tree.children().get("blob"), None);
-    assert_eq!(inner_tree.children().get("tree"), None);
+    assert_eq!(inner_tree.children().get(OsStr::new("blob")), None);
+    assert_eq!(inner_tree.children().get(OsStr::new("tree")), None);
   });
 }
 
@@ -113,8 +127,8 @@ fn get_given_non_existing_path_returns_none() {
     let blob = blob();
     let tree = Tree::new();
 
-    root.insert("tree/blob", Node::Blob(blob.clone()));
-    root.insert("tree/tree", Node::Tree(tree.clone()));
+    root.insert("tree/blob", Node::Blob(blob.clone())).unwrap();
+    root.insert("tree/tree", Node::Tree(tree.clone())).unwrap();
 
     let node_1 = tree.get("tree/tree/non_blob");
     let node_2 = tree.get("tree/non_tree");
@@ -134,8 +148,8 @@ fn get_given_existing_path_returns_the_node() {
     let blob = blob();
     let tree = Tree::new();
 
-    root.insert("tree/blob", Node::Blob(blob.clone()));
-    root.insert("tree/tree", Node::Tree(tree.clone()));
+    root.insert("tree/blob", Node::Blob(blob.clone())).unwrap();
+    root.insert("tree/tree", Node::Tree(tree.clone())).unwrap();
 
     let node_1 = root.get("tree/blob").unwrap().into_blob().unwrap();
     let node_2 = root.get("tree/tree").unwrap().into_tree().unwrap();
@@ -143,8 +157,30 @@ fn get_given_existing_path_returns_the_node() {
 
     assert_eq!(node_1, &blob);
     assert_eq!(node_2, &tree);
-    assert_eq!(node_3.children().get("blob").unwrap(), node_1);
-    assert_eq!(node_3.children().get("tree").unwrap(), node_2);
+    assert_eq!(node_3.children().get(OsStr::new("blob")).unwrap(), node_1);
+    assert_eq!(node_3.children().get(OsStr::new("tree")).unwrap(), node_2);
+  });
+}
+
+#[test]
+#[serial]
+fn entry_given_path_reads_only_the_trees_along_it() {
+  run_unit(|| {
+    let mut root = Tree::new();
+    let (blob_1, blob_2) = (blob(), blob());
+
+    root.insert("tree/blob", Node::Blob(blob_1.clone())).unwrap();
+    root.insert("other/blob", Node::Blob(blob_2.clone())).unwrap();
+    root.pack().unwrap();
+    fs::remove_file(object_location(blob_2.id()).unwrap()).unwrap();
+
+    let entry = Tree::entry(root.id(), "tree/blob").unwrap().unwrap();
+
+    assert_eq!(entry.id, *blob_1.id());
+    assert_eq!(entry.mode, Mode::Regular.as_str());
+    assert_eq!(Tree::entry(root.id(), "tree/missing").unwrap(), None);
+    assert_eq!(Tree::entry(root.id(), "tree/blob/inner").unwrap(), None);
+    assert!(Tree::from_id(root.id()).is_err());
   });
 }
 
@@ -156,8 +192,8 @@ fn pack_compresses_and_saves_the_tree_inside_the_repository_and_modifies_and_ret
     let blob_1 = blob();
     let blob_2 = blob();
 
-    root.insert("blob", Node::Blob(blob_1.clone()));
-    root.insert("tree/blob", Node::Blob(blob_2.clone()));
+    root.insert("blob", Node::Blob(blob_1.clone())).unwrap();
+    root.insert("tree/blob", Node::Blob(blob_2.clone())).unwrap();
 
     root.pack().unwrap();
 
@@ -169,8 +205,8 @@ fn pack_compresses_and_saves_the_tree_inside_the_repository_and_modifies_and_ret
 
     let expected = format!("\
       tree\
-      blob blob {}\n\
-      tree tree {}\
+      100644 blob\0{}\n\
+      040000 tree\0{}\n\
     ", blob_1.id(), inner_tree.id());
 
     assert_eq!(decompress(&compressed).unwrap(), expected.as_bytes());
@@ -185,8 +221,8 @@ fn unpack_given_path_directory_prefix_creates_directories_and_files_based_on_the
     let (blob_1, text_1) = blob_and_text();
     let (blob_2, text_2) = blob_and_text();
 
-    root.insert("blob", Node::Blob(blob_1.clone()));
-    root.insert("tree/blob", Node::Blob(blob_2.clone()));
+    root.insert("blob", Node::Blob(blob_1.clone())).unwrap();
+    root.insert("tree/blob", Node::Blob(blob_2.clone())).unwrap();
 
     let prefix = locale();
     root.unpack(&prefix).unwrap();
@@ -208,13 +244,121 @@ fn from_id_given_existing_id_returns_tree() {
   run_unit(|| {
     let mut root = Tree::new();
 
-    root.insert("blob", Node::Blob(blob()));
-    root.insert("tree/blob", Node::Blob(blob()));
+    root.insert("blob", Node::Blob(blob())).unwrap();
+    root.insert("tree/blob", Node::Blob(blob())).unwrap();
+
+    root.pack().unwrap();
+
+    let tree = Tree::from_id(root.id()).unwrap();
+
+    assert_eq!(root, tree);
+  });
+}
+
+#[test]
+#[serial]
+fn unpack_restores_executable_and_symlink_modes() {
+  run_unit(|| {
+    let mut root = Tree::new();
+    let (blob, text) = blob_and_text();
+
+    root.insert("script", Node::Blob(blob.clone().with_mode(Mode::Executable))).unwrap();
+    root.insert("link", Node::Blob(Blob::new("script").unwrap().with_mode(Mode::Symlink))).unwrap();
+
+    let prefix = locale();
+    root.unpack(&prefix).unwrap();
+
+    let mut file_text = String::new();
+    File::open(prefix.join("link")).unwrap().read_to_string(&mut file_text).unwrap();
+
+    assert_eq!(file_text, text);
+    assert_eq!(fs::read_link(prefix.join("link")).unwrap().to_str().unwrap(), "script");
+    assert_eq!(Blob::from_path(prefix.join("script")).unwrap().mode(), &Mode::Executable);
+    assert_eq!(Blob::from_path(prefix.join("link")).unwrap().mode(), &Mode::Symlink);
+  })
+}
+
+#[test]
+#[serial]
+fn from_id_given_existing_id_preserves_blob_modes() {
+  run_unit(|| {
+    let mut root = Tree::new();
+
+    root.insert("script", Node::Blob(blob().with_mode(Mode::Executable))).unwrap();
+    root.insert("tree/link", Node::Blob(blob().with_mode(Mode::Symlink))).unwrap();
+
+    root.pack().unwrap();
+
+    let tree = Tree::from_id(root.id()).unwrap();
+
+    assert_eq!(root, tree);
+    assert_eq!(tree.get("script").unwrap().into_blob().unwrap().mode(), &Mode::Executable);
+  });
+}
+
+#[test]
+#[serial]
+fn from_id_given_legacy_tree_without_modes_returns_tree() {
+  run_unit(|| {
+    let blob = blob();
+    let id = write_object_bytes(Object::Tree, format!("blob blob {}", blob.id())).unwrap();
+
+    let tree = Tree::from_id(&id).unwrap();
+
+    assert_eq!(tree.get("blob").unwrap(), &blob);
+  });
+}
+
+#[test]
+#[serial]
+fn from_id_given_names_leaving_the_tree_or_entering_a_repository_folder_returns_error() {
+  run_unit(|| {
+    let blob = blob();
+
+    for name in ["..", ".", "a/b", ".rgit", ".GIT"] {
+      let id = write_object_bytes(Object::Tree, pack_entries(&mut [Entry::new(Mode::Regular.as_str(), name, blob.id())])).unwrap();
+
+      assert!(Tree::from_id(&id).is_err());
+    }
+  });
+}
+
+#[test]
+#[serial]
+fn from_id_given_names_with_spaces_and_newlines_returns_tree() {
+  run_unit(|| {
+    let mut root = Tree::new();
+
+    root.insert("my notes.txt", Node::Blob(blob())).unwrap();
+    root.insert("some folder/multi\nline", Node::Blob(blob())).unwrap();
+
+    root.pack().unwrap();
+
+    let tree = Tree::from_id(root.id()).unwrap();
+
+    assert_eq!(root, tree);
+  });
+}
+
+#[test]
+#[serial]
+#[cfg(unix)]
+fn from_id_given_non_utf8_name_returns_tree_that_unpacks_the_name() {
+  use std::os::unix::ffi::OsStrExt;
+
+  run_unit(|| {
+    let mut root = Tree::new();
+    let (blob, text) = blob_and_text();
+    let path = Path::new("folder").join(OsStr::from_bytes(b"caf\xe9"));
 
+    root.insert(&path, Node::Blob(blob.clone())).unwrap();
     root.pack().unwrap();
 
     let tree = Tree::from_id(root.id()).unwrap();
+    tree.unpack(locale()).unwrap();
 
     assert_eq!(root, tree);
+    assert_eq!(tree.get(&path).unwrap(), &blob);
+    assert_eq!(fs::read_to_string(locale().as_std_path().join(&path)).unwrap(), text);
   });
 }
@@ -0,0 +1,100 @@
+use std::fs;
+use serial_test::serial;
+use crate::{lib::{config::{Config, Scope}, errors::Errors}, tests::run_unit};
+
+const TEXT: &str = "\
+# comment
+[user]
+\tname = Jane Doe ; comment
+\tEmail = jane@mail.com
+[branch \"Main\"]
+\tremote = \" origin # \\\"x\\\"\"
+[core]
+\tbare
+";
+
+#[test]
+fn parse_reads_sections_subsections_and_values() {
+  let config = Config::parse(TEXT).unwrap();
+
+  assert_eq!(config.get("user.name").unwrap(), "Jane Doe");
+  assert_eq!(config.get("USER.email").unwrap(), "jane@mail.com");
+  assert_eq!(config.get("branch.Main.remote").unwrap(), " origin # \"x\"");
+  assert_eq!(config.get("core.bare").unwrap(), "true");
+  assert!(config.get("branch.main.remote").is_none());
+}
+
+#[test]
+fn parse_given_entry_outside_a_section_returns_bad_object_structure_error() {
+  assert!(matches!(Config::parse("[user]\nname = \"x\n"), Err(Errors::BadObjectStructure)));
+  assert!(matches!(Config::parse("name = x\n"), Err(Errors::BadObjectStructure)));
+}
+
+#[test]
+fn parse_given_legacy_config_reads_the_user() {
+  let config = Config::parse("jane jane@mail.com").unwrap();
+
+  assert_eq!(config.get("user.name").unwrap(), "jane");
+  assert_eq!(config.get("user.email").unwrap(), "jane@mail.com");
+  assert!(Config::parse("admin admin").unwrap().entries().is_empty());
+}
+
+#[test]
+fn format_can_be_parsed_back() {
+  let config = Config::parse(TEXT).unwrap();
+
+  assert_eq!(Config::parse(&config.format()).unwrap(), config);
+}
+
+#[test]
+#[serial]
+fn set_adds_the_key_to_its_section() {
+  run_unit(|| {
+    Config::set(Scope::Repository, "user.name", "Jane").unwrap();
+    Config::set(Scope::Repository, "core.editor", "vim").unwrap();
+    Config::set(Scope::Repository, "user.email", "jane@mail.com").unwrap();
+    Config::set(Scope::Repository, "user.name", "John").unwrap();
+    let text = fs::read_to_string(Scope::Repository.location().unwrap()).unwrap();
+
+    assert_eq!(text, "[user]\n\tname = John\n\temail = jane@mail.com\n[core]\n\teditor = vim\n");
+  });
+}
+
+#[test]
+#[serial]
+fn unset_given_missing_key_returns_missing_config_key_error() {
+  run_unit(|| {
+    Config::set(Scope::Global, "core.editor", "vim").unwrap();
+    Config::unset(Scope::Global, "core.editor").unwrap();
+
+    assert!(matches!(Config::unset(Scope::Global, "core.editor"), Err(Errors::MissingConfigKey(_))));
+    assert!(Config::read(Scope::Global).unwrap().entries().is_empty());
+  });
+}
+
+#[test]
+#[serial]
+fn load_gives_precedence_to_the_narrower_scope() {
+  run_unit(|| {
+    Config::set(Scope::System, "core.editor", "nano").unwrap();
+    Config::set(Scope::System, "core.pager", "less").unwrap();
+    Config::set(Scope::Global, "core.editor", "vim").unwrap();
+    let config = Config::load().unwrap();
+
+    assert_eq!(config.get("core.editor").unwrap(), "vim");
+    assert_eq!(config.get("core.pager").unwrap(), "less");
+
+    Config::set(Scope::Repository, "core.editor", "emacs").unwrap();
+
+    assert_eq!(Config::load().unwrap().get("core.editor").unwrap(), "emacs");
+  });
+}
+
+#[test]
+#[serial]
+fn set_given_invalid_key_returns_bad_arguments_error() {
+  run_unit(|| {
+    assert!(matches!(Config::set(Scope::Repository, "editor", "vim"), Err(Errors::BadArguments(_))));
+    assert!(matches!(Config::set(Scope::Repository, "core.bad_name", "vim"), Err(Errors::BadArguments(_))));
+  });
+}
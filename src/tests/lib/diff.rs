@@ -0,0 +1,126 @@
+use crate::lib::diff::{diff, unified, hunks, patch, Edit};
+
+fn apply(old: &[&str], new: &[&str], edits: &[Edit]) -> Vec<String> {
+  edits.iter().filter_map(|edit| match edit {
+    Edit::Equal(old_index, new_index) => {
+      assert_eq!(old[*old_index], new[*new_index]);
+      Some(String::from(old[*old_index]))
+    },
+    Edit::Insert(new_index) => Some(String::from(new[*new_index])),
+    Edit::Delete(_) => None,
+  }).collect()
+}
+
+#[test]
+fn diff_given_equal_sequences_returns_only_equal_edits() {
+  let lines = ["a", "b", "c"];
+
+  assert_eq!(diff(&lines, &lines), vec![Edit::Equal(0, 0), Edit::Equal(1, 1), Edit::Equal(2, 2)]);
+}
+
+#[test]
+fn diff_given_empty_sequences_returns_inserts_or_deletes() {
+  assert_eq!(diff(&[], &["a", "b"]), vec![Edit::Insert(0), Edit::Insert(1)]);
+  assert_eq!(diff(&["a", "b"], &[]), vec![Edit::Delete(0), Edit::Delete(1)]);
+  assert_eq!(diff::<&str>(&[], &[]), vec![]);
+}
+
+#[test]
+fn diff_returns_a_shortest_edit_script() {
+  let old = ["a", "b", "c", "a", "b", "b", "a"];
+  let new = ["c", "b", "a", "b", "a", "c"];
+  let edits = diff(&old, &new);
+  let changes = edits.iter().filter(|edit| !matches!(edit, Edit::Equal(_, _))).count();
+
+  assert_eq!(apply(&old, &new, &edits), new);
+  assert_eq!(changes, 5);
+}
+
+#[test]
+fn diff_given_any_short_sequences_returns_a_shortest_edit_script() {
+  let sequences = (0..6).flat_map(|length| (0..1 << length).map(move |bits| {
+    (0..length).map(|index| if bits >> index & 1 == 1 { "a" } else { "b" }).collect::<Vec<&str>>()
+  })).collect::<Vec<Vec<&str>>>();
+
+  for old in &sequences {
+    for new in &sequences {
+      let mut common = vec![vec![0; new.len() + 1]; old.len() + 1];
+
+      for x in (0..old.len()).rev() {
+        for y in (0..new.len()).rev() {
+          common[x][y] = if old[x] == new[y] { common[x + 1][y + 1] + 1 } else { common[x + 1][y].max(common[x][y + 1]) };
+        }
+      }
+
+      let edits = diff(old, new);
+      let changes = edits.iter().filter(|edit| !matches!(edit, Edit::Equal(_, _))).count();
+
+      assert_eq!(apply(old, new, &edits), *new);
+      assert_eq!(changes, old.len() + new.len() - 2 * common[0][0], "{:?} -> {:?}", old, new);
+    }
+  }
+}
+
+#[test]
+fn unified_groups_nearby_changes_into_hunks() {
+  let old = ["a", "b", "c", "d", "e", "f", "g", "h", "i", "j"];
+  let new = ["a", "B", "c", "d", "e", "f", "g", "h", "i", "j", "k"];
+  let expected_output = "\
+@@ -1,5 +1,5 @@
+ a
+-b
++B
+ c
+ d
+ e
+@@ -8,3 +8,4 @@
+ h
+ i
+ j
++k
+";
+
+  assert_eq!(unified(&old, &new, 3), expected_output);
+}
+
+#[test]
+fn unified_given_new_file_counts_from_zero() {
+  assert_eq!(unified(&[], &["a"], 3), "@@ -0,0 +1 @@\n+a\n");
+  assert_eq!(unified(&["a"], &["a"], 3), "");
+}
+
+#[test]
+fn split_given_hunk_with_separate_changes_returns_a_hunk_per_change() {
+  let old = ["a", "b", "c", "d", "e"];
+  let new = ["A", "b", "c", "d", "E"];
+  let hunk = hunks(&old, &new, 3).remove(0);
+  let split = hunk.split();
+
+  assert_eq!(split.len(), 2);
+  assert_eq!(split[0].header(), "@@ -1,3 +1,3 @@");
+  assert_eq!(split[1].header(), "@@ -4,2 +4,2 @@");
+  assert_eq!(split.iter().flat_map(|hunk| hunk.lines().clone()).collect::<Vec<String>>(), *hunk.lines());
+}
+
+#[test]
+fn patch_applies_only_the_given_hunks() {
+  let old = ["a", "b", "c", "d", "e", "f", "g", "h", "i", "j"];
+  let new = ["A", "b", "c", "d", "e", "f", "g", "h", "i", "J"];
+  let hunks = hunks(&old, &new, 3);
+
+  assert_eq!(hunks.len(), 2);
+  assert_eq!(patch(&old, &hunks[1..]), ["a", "b", "c", "d", "e", "f", "g", "h", "i", "J"]);
+  assert_eq!(patch(&old, &hunks), new);
+  assert_eq!(patch(&old, &[]), old);
+}
+
+#[test]
+fn with_lines_given_lines_changing_the_old_side_returns_none() {
+  let hunk = hunks(&["a", "b"], &["a", "B"], 3).remove(0);
+  let lines = |lines: &[&str]| lines.iter().map(|line| String::from(*line)).collect::<Vec<String>>();
+
+  assert!(hunk.with_lines(lines(&[" a", "-b", "+C"])).is_some());
+  assert!(hunk.with_lines(lines(&[" a", " b"])).is_some());
+  assert!(hunk.with_lines(lines(&[" a", "+B"])).is_none());
+  assert!(hunk.with_lines(lines(&["a", "-b"])).is_none());
+}
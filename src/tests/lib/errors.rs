@@ -0,0 +1,34 @@
+use std::{fs::File, io::ErrorKind};
+use serial_test::serial;
+use crate::{tests::run_unit, lib::{locale, read_object_bytes, object::Object, write_object_bytes, errors::*}};
+
+#[test]
+fn display_given_context_describes_the_operation_and_the_cause() {
+  let error: Result<(), Errors> = Err(std::io::Error::from(ErrorKind::NotFound)).context(|| String::from("unable to read 'a.txt'"));
+  let error = error.unwrap_err();
+
+  assert!(error.to_string().starts_with("unable to read 'a.txt': "));
+  assert_eq!(error.exit_code(), IO_ERROR_CODE);
+}
+
+#[test]
+fn exit_code_differs_between_error_categories() {
+  assert_eq!(Errors::UnrecognisedBranch(String::from("feature")).exit_code(), USER_ERROR_CODE);
+  assert_eq!(Errors::MissingRepository.exit_code(), REPOSITORY_ERROR_CODE);
+  assert_eq!(Errors::BadObjectStructure.exit_code(), CORRUPTION_ERROR_CODE);
+}
+
+#[test]
+#[serial]
+fn read_object_bytes_given_corrupt_object_names_the_object() {
+  run_unit(|| {
+    let id = write_object_bytes(Object::Blob, "text").unwrap();
+    let location = locale().join(".rgit/objects").join(&id[..2]).join(&id[2..]);
+    File::create(location).unwrap();
+
+    let error = read_object_bytes(&id).unwrap_err();
+
+    assert!(error.to_string().starts_with(&format!("unable to read object '{}'", id)));
+    assert_eq!(error.exit_code(), CORRUPTION_ERROR_CODE);
+  });
+}
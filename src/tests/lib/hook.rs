@@ -0,0 +1,42 @@
+use std::fs;
+use serial_test::serial;
+use crate::{tests::{run_unit, factory::hook}, lib::{hook::Hook, errors::Errors, locale, layout::Layout}};
+
+#[test]
+#[serial]
+fn run_given_missing_hook_succeeds() {
+  run_unit(|| {
+    assert!(Hook::PreCommit.run(&[]).is_ok());
+  });
+}
+
+#[test]
+#[serial]
+fn run_given_non_executable_hook_skips_it() {
+  run_unit(|| {
+    fs::write(locale().join(Layout::get().hooks_path()).join("pre-commit"), "#!/bin/sh\nexit 1\n").unwrap();
+
+    assert!(Hook::PreCommit.run(&[]).is_ok());
+  });
+}
+
+#[test]
+#[serial]
+fn run_passes_the_arguments_from_the_top_of_the_repository() {
+  run_unit(|| {
+    hook("commit-msg", "echo \"$1\" > output");
+    Hook::CommitMsg.run(&["argument"]).unwrap();
+
+    assert_eq!(fs::read_to_string(locale().join("output")).unwrap(), "argument\n");
+  });
+}
+
+#[test]
+#[serial]
+fn run_given_failing_hook_returns_failed_hook_error() {
+  run_unit(|| {
+    hook("post-switch", "exit 2");
+
+    assert!(matches!(Hook::PostSwitch.run(&[]), Err(Errors::FailedHook(name)) if name == "post-switch"));
+  });
+}
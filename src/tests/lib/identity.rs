@@ -0,0 +1,49 @@
+use std::env;
+use serial_test::serial;
+use crate::{tests::run_unit, lib::{identity::{Identity, parse_date, parse_user}, layout::Layout, constants::{AUTHOR_NAME_ENV, AUTHOR_DATE_ENV, COMMITTER_EMAIL_ENV}, errors::Errors}};
+
+#[test]
+fn parse_date_accepts_rfc_3339_git_and_iso_dates() {
+  let expected = "2022-01-01T12:00:00+02:00";
+
+  assert_eq!(parse_date("2022-01-01T12:00:00+02:00").unwrap().to_rfc3339(), expected);
+  assert_eq!(parse_date("1641031200 +0200").unwrap().to_rfc3339(), expected);
+  assert_eq!(parse_date("2022-01-01 12:00:00 +0200").unwrap().to_rfc3339(), expected);
+  assert_eq!(parse_date("Sat, 1 Jan 2022 12:00:00 +0200").unwrap().to_rfc3339(), expected);
+  assert_eq!(parse_date("@1641031200").unwrap().to_rfc3339(), "2022-01-01T10:00:00+00:00");
+  assert_eq!(parse_date("2022-01-01").unwrap().to_rfc3339(), "2022-01-01T00:00:00+00:00");
+  assert!(matches!(parse_date("yesterday"), Err(Errors::BadArguments(_))));
+}
+
+#[test]
+fn parse_reads_identities_in_both_layouts() {
+  let identity = Identity::parse("Jane Doe <jane@mail.com> 1641031200 +0200").unwrap();
+
+  assert_eq!(identity.username(), "Jane Doe");
+  assert_eq!(identity.email(), "jane@mail.com");
+  assert_eq!(identity.format(Layout::Git), "Jane Doe <jane@mail.com> 1641031200 +0200");
+  assert_eq!(identity.format(Layout::Rgit), "Jane Doe <jane@mail.com> 2022-01-01T12:00:00+02:00");
+  assert_eq!(Identity::parse(&identity.format(Layout::Rgit)).unwrap(), identity);
+  assert!(parse_user("Jane Doe").is_err());
+}
+
+#[test]
+#[serial]
+fn author_and_committer_honor_the_environment_overrides() {
+  run_unit(|| {
+    env::set_var(AUTHOR_NAME_ENV, "Jane Doe");
+    env::set_var(AUTHOR_DATE_ENV, "1641031200 +0200");
+    env::set_var(COMMITTER_EMAIL_ENV, "bot@mail.com");
+
+    let author = Identity::author().unwrap();
+    let committer = Identity::committer().unwrap();
+
+    assert_eq!(author.format(Layout::Git), "Jane Doe <admin> 1641031200 +0200");
+    assert_eq!(committer.username(), "admin");
+    assert_eq!(committer.email(), "bot@mail.com");
+
+    env::remove_var(AUTHOR_NAME_ENV);
+    env::remove_var(AUTHOR_DATE_ENV);
+    env::remove_var(COMMITTER_EMAIL_ENV);
+  });
+}
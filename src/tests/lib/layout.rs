@@ -0,0 +1,214 @@
+use std::{fs::{self, File}, io::Read, path::Path};
+use serial_test::serial;
+use crate::{tests::run_unit_git, lib::{locale, layout::Layout, constants::*, read_object_bytes, write_object_bytes, decompress, errors::Errors, object::{Object, FromId, Mode, Entry, pack_git_entries, unpack_git_index}, format::ObjectFormat}, core::{blob::Blob, tree::{Tree, Node}, commit::Commit, head::{Head, Reference}, branch::Branch, index::{Index, Stage}}};
+
+const GIT_BLOB_HASH: &str = "ce013625030ba8dba906f756967f9e9ca394464a";
+const GIT_TREE_HASH: &str = "6c6becbb833536e8059516a83a223b0020501d3a";
+
+#[test]
+#[serial]
+fn initialize_git_creates_a_git_repository_in_the_current_locale() {
+  run_unit_git(|| {
+    let locale = locale();
+
+    assert_eq!(Layout::get(), Layout::Git);
+    assert!(locale.join(GIT_OBJECTS_PATH).exists());
+    assert!(locale.join(GIT_BRANCHES_PATH).exists());
+    assert!(locale.join(GIT_STATE_PATH).exists());
+    assert!(!locale.join(GIT_INDEX_PATH).exists());
+    assert!(!locale.join(GIT_BRANCHES_PATH).join("master").exists());
+
+    let mut head = String::new();
+    File::open(locale.join(GIT_HEAD_PATH)).unwrap().read_to_string(&mut head).unwrap();
+
+    assert_eq!(head, "ref: refs/heads/master\n");
+  });
+}
+
+#[test]
+#[serial]
+fn write_object_bytes_in_git_layout_writes_git_loose_objects() {
+  run_unit_git(|| {
+    let id = write_object_bytes(Object::Blob, "hello\n").unwrap();
+
+    let mut compressed = Vec::new();
+    File::open(locale().join(GIT_OBJECTS_PATH).join(&id[..2]).join(&id[2..])).unwrap().read_to_end(&mut compressed).unwrap();
+
+    assert_eq!(id, GIT_BLOB_HASH);
+    assert_eq!(decompress(&compressed).unwrap(), b"blob 6\0hello\n");
+    assert_eq!(read_object_bytes(&id).unwrap(), b"hello\n");
+  });
+}
+
+#[test]
+#[serial]
+fn pack_in_git_layout_writes_git_trees() {
+  run_unit_git(|| {
+    let mut root = Tree::new();
+
+    root.insert("hello.txt", Node::Blob(Blob::new("hello\n").unwrap())).unwrap();
+    root.insert("hello", Node::Tree(Tree::new())).unwrap();
+    root.pack().unwrap();
+
+    assert_eq!(root.id(), GIT_TREE_HASH);
+    assert_eq!(Tree::from_id(GIT_TREE_HASH).unwrap(), root);
+  });
+}
+
+#[test]
+#[serial]
+fn from_id_in_git_layout_given_entry_named_git_folder_returns_error() {
+  run_unit_git(|| {
+    let blob = Blob::new("hello\n").unwrap();
+    let bytes = pack_git_entries(&mut [Entry::new(Mode::Executable.as_str(), ".git", blob.id())]).unwrap();
+    let id = write_object_bytes(Object::Tree, bytes).unwrap();
+
+    assert!(Tree::from_id(&id).is_err());
+  });
+}
+
+#[test]
+#[serial]
+fn add_in_git_layout_writes_git_index_of_the_head_tree_and_the_staged_files() {
+  run_unit_git(|| {
+    let mut root = Tree::new();
+    root.insert("old.txt", Node::Blob(Blob::new("old\n").unwrap())).unwrap();
+    root.pack().unwrap();
+    Branch::set("master", Commit::new(None, root.id(), "first").unwrap().id()).unwrap();
+    fs::write(locale().join("hello.txt"), "hello\n").unwrap();
+
+    Index::add(&[locale().join("hello.txt")]).unwrap();
+
+    let bytes = fs::read(locale().join(GIT_INDEX_PATH)).unwrap();
+    let entries = unpack_git_index(&bytes, ObjectFormat::Sha1).unwrap();
+
+    assert_eq!(&bytes[..4], b"DIRC");
+    assert_eq!(entries, [
+      Entry::new("100644", "hello.txt", GIT_BLOB_HASH),
+      Entry::new("100644", "old.txt", root.get("old.txt").unwrap().into_blob().unwrap().id()),
+    ]);
+    assert_eq!(Index::get().unwrap().staged_paths().len(), 1);
+
+    Branch::set("master", Commit::new(None, root.id(), "second").unwrap().id()).unwrap();
+    Index::clear().unwrap();
+
+    assert_eq!(unpack_git_index(&fs::read(locale().join(GIT_INDEX_PATH)).unwrap(), ObjectFormat::Sha1).unwrap().len(), 1);
+    assert!(Index::get().unwrap().staged_paths().is_empty());
+  });
+}
+
+#[test]
+#[serial]
+fn get_in_git_layout_given_version_4_index_returns_staged_paths() {
+  run_unit_git(|| {
+    let blob = Blob::new("hello\n").unwrap();
+    let mut bytes = Vec::from(&b"DIRC\0\0\0\x04\0\0\0\x02"[..]);
+
+    for (dropped, suffix) in [(0, &b"a/b"[..]), (1, &b"c"[..])] {
+      bytes.extend_from_slice(&[0; 24]);
+      bytes.extend_from_slice(&0o100644u32.to_be_bytes());
+      bytes.extend_from_slice(&[0; 12]);
+      bytes.extend_from_slice(&hex::decode(blob.id()).unwrap());
+      bytes.extend_from_slice(&3u16.to_be_bytes());
+      bytes.push(dropped);
+      bytes.extend_from_slice(suffix);
+      bytes.push(0);
+    }
+
+    bytes.extend_from_slice(&[0; 20]);
+    fs::write(locale().join(GIT_INDEX_PATH), bytes).unwrap();
+
+    let index = Index::get().unwrap();
+
+    assert_eq!(index.staged_paths().get(Path::new("a/b")), Some(&Stage::Blob(blob.clone())));
+    assert_eq!(index.staged_paths().get(Path::new("a/c")), Some(&Stage::Blob(blob)));
+  });
+}
+
+#[test]
+#[serial]
+fn commit_in_git_layout_writes_git_commits() {
+  run_unit_git(|| {
+    let mut root = Tree::new();
+    root.insert("hello.txt", Node::Blob(Blob::new("hello\n").unwrap())).unwrap();
+    root.pack().unwrap();
+
+    let parent = Commit::new(None, root.id(), "first").unwrap();
+    let commit = Commit::new(Some(parent.id()), root.id(), "second").unwrap();
+
+    let expected = format!("tree {}\nparent {}\nauthor admin <admin> {} {}\ncommitter admin <admin> {} {}\n\nsecond\n",
+      root.id(),
+      parent.id(),
+      commit.author().date().timestamp(),
+      commit.author().date().format("%z"),
+      commit.committer().date().timestamp(),
+      commit.committer().date().format("%z"),
+    );
+
+    assert_eq!(read_object_bytes(commit.id()).unwrap(), expected.as_bytes());
+    assert_eq!(Commit::from_id(commit.id()).unwrap(), commit);
+    assert_eq!(commit.parent().unwrap(), parent);
+  });
+}
+
+#[test]
+#[serial]
+fn head_in_git_layout_resolves_unborn_and_detached_references() {
+  run_unit_git(|| {
+    let mut root = Tree::new();
+    root.pack().unwrap();
+    let commit = Commit::new(None, root.id(), "first").unwrap();
+
+    assert_eq!(Head::get().unwrap().commit().unwrap(), None);
+
+    Branch::set("master", commit.id()).unwrap();
+
+    assert_eq!(Head::get().unwrap().commit().unwrap(), Some(commit.clone()));
+
+    Head::set(&Reference::Commit(commit.clone())).unwrap();
+
+    let mut head = String::new();
+    File::open(locale().join(GIT_HEAD_PATH)).unwrap().read_to_string(&mut head).unwrap();
+
+    assert_eq!(head, format!("{}\n", commit.id()));
+    assert_eq!(Head::get().unwrap().reference(), &Reference::Commit(commit));
+  });
+}
+
+#[test]
+#[serial]
+fn head_in_git_layout_given_packed_commit_returns_packed_object_error() {
+  run_unit_git(|| {
+    fs::create_dir_all(locale().join(GIT_PACKS_PATH)).unwrap();
+    File::create(locale().join(GIT_PACKS_PATH).join("pack-1.pack")).unwrap();
+    fs::write(locale().join(GIT_PACKED_REFS_PATH), format!("{} refs/heads/master\n", GIT_BLOB_HASH)).unwrap();
+
+    assert!(matches!(Head::get().unwrap().commit(), Err(Errors::PackedObject(id)) if id == GIT_BLOB_HASH));
+  });
+}
+
+#[test]
+#[serial]
+fn from_id_in_git_layout_given_gitlink_and_group_writable_file_keeps_the_gitlink() {
+  run_unit_git(|| {
+    let blob_id = write_object_bytes(Object::Blob, "hello\n").unwrap();
+    let mut bytes = b"100664 hello.txt\0".to_vec();
+    bytes.extend(hex::decode(&blob_id).unwrap());
+    bytes.extend(b"160000 module\0");
+    bytes.extend(hex::decode(GIT_BLOB_HASH).unwrap());
+
+    let id = write_object_bytes(Object::Tree, bytes).unwrap();
+    let mut tree = Tree::from_id(&id).unwrap();
+
+    assert_eq!(tree.get("hello.txt").unwrap().into_blob().unwrap().mode(), &Mode::Regular);
+    assert_eq!(tree.get("module").unwrap(), &Node::Gitlink(String::from(GIT_BLOB_HASH)));
+    assert_eq!(tree.blobs().len(), 1);
+
+    tree.unpack(locale()).unwrap();
+    tree.pack().unwrap();
+
+    assert!(locale().join("hello.txt").exists());
+    assert!(!locale().join("module").exists());
+    assert_eq!(Tree::from_id(tree.id()).unwrap().get("module").unwrap(), &Node::Gitlink(String::from(GIT_BLOB_HASH)));
+  });
+}
@@ -0,0 +1,77 @@
+use std::fs;
+use serial_test::serial;
+use crate::{tests::{run_unit, factory::commit}, core::branch::Branch, lib::{locale, errors::Errors, lock::{Lock, write_atomically}, constants::BRANCHES_PATH}};
+
+const FILE_NAME: &str = "file.txt";
+const DUMMY_TEXT: &str = "Lorem ipsum dolor sit amet";
+
+#[test]
+#[serial]
+fn acquire_given_locked_path_returns_locked_file_error() {
+  run_unit(|| {
+    let location = locale().join(FILE_NAME);
+    let _lock = Lock::acquire(&location).unwrap();
+
+    assert!(locale().join("file.txt.lock").exists());
+    assert!(matches!(Lock::acquire(&location), Err(Errors::LockedFile(_))));
+  });
+}
+
+#[test]
+#[serial]
+fn commit_replaces_the_file_and_releases_the_lock() {
+  run_unit(|| {
+    let location = locale().join(FILE_NAME);
+    fs::write(&location, "old contents").unwrap();
+
+    Lock::acquire(&location).unwrap().commit(DUMMY_TEXT).unwrap();
+
+    assert_eq!(fs::read_to_string(&location).unwrap(), DUMMY_TEXT);
+    assert!(!locale().join("file.txt.lock").exists());
+    assert!(Lock::acquire(&location).is_ok());
+  });
+}
+
+#[test]
+#[serial]
+fn dropping_an_uncommitted_lock_leaves_the_file_untouched() {
+  run_unit(|| {
+    let location = locale().join(FILE_NAME);
+    fs::write(&location, DUMMY_TEXT).unwrap();
+
+    drop(Lock::acquire(&location).unwrap());
+
+    assert_eq!(fs::read_to_string(&location).unwrap(), DUMMY_TEXT);
+    assert!(!locale().join("file.txt.lock").exists());
+  });
+}
+
+#[test]
+#[serial]
+fn write_atomically_writes_the_file_without_leaving_temporary_files() {
+  run_unit(|| {
+    let location = locale().join(FILE_NAME);
+    write_atomically(&location, DUMMY_TEXT).unwrap();
+
+    let file_names = fs::read_dir(locale()).unwrap()
+      .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+      .filter(|name| name.starts_with(FILE_NAME))
+      .collect::<Vec<String>>();
+
+    assert_eq!(fs::read_to_string(&location).unwrap(), DUMMY_TEXT);
+    assert_eq!(file_names, vec![FILE_NAME]);
+  });
+}
+
+#[test]
+#[serial]
+fn branch_set_given_locked_branch_keeps_the_previous_commit() {
+  run_unit(|| {
+    let commit = commit();
+    let _lock = Lock::acquire(locale().join(BRANCHES_PATH).join("master")).unwrap();
+
+    assert!(matches!(Branch::set("master", commit.id()), Err(Errors::LockedFile(_))));
+    assert_eq!(Branch::get("master").unwrap().commit_id(), &None);
+    assert_eq!(Branch::list().unwrap().len(), 1);
+  });
+}
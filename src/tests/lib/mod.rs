@@ -1,9 +1,16 @@
 mod user;
-
-use std::{fs::File, io::Read, collections::HashSet};
-use camino::Utf8PathBuf;
+mod identity;
+mod layout;
+mod lock;
+mod errors;
+mod diff;
+mod config;
+mod hook;
+mod signature;
+
+use std::{fs::File, io::Read, collections::HashSet, path::{Path, PathBuf}};
 use serial_test::serial;
-use crate::{lib::{*, object::Object, constants::OBJECTS_PATH}, tests::factory::tree};
+use crate::{lib::{*, object::Object, constants::{OBJECTS_PATH, BLOB_TYPE}}, tests::factory::tree};
 use super::{run_unit, factory::TREE_PATHS};
 
 const DUMMY_TEXT: &str = "Lorem ipsum dolor sit amet, consectetur adipiscing elit.";
@@ -29,7 +36,7 @@ fn initialize_creates_the_repository_folder_in_the_current_locale() {
     let mut master = String::new();
     File::open(locale.join(".rgit/branches/master")).unwrap().read_to_string(&mut master).unwrap();
 
-    assert_eq!(config, "admin admin");
+    assert!(config.is_empty());
     assert_eq!(head, "master");
     assert!(master.is_empty());
   });
@@ -83,12 +90,12 @@ fn folder_files_given_path_returns_list_of_files_descending_of_path() {
     let files_3 = folder_files(locale.join("c/b/a")).unwrap();
 
     let all_test_file_paths = TREE_PATHS.iter()
-      .map(|path| locale.join(path))
-      .collect::<HashSet<Utf8PathBuf>>();
+      .map(|path| locale.join(path).into_std_path_buf())
+      .collect::<HashSet<PathBuf>>();
 
     assert_eq!(files_1, all_test_file_paths);
-    assert_eq!(files_2, HashSet::from_iter([locale.join("b/a/a"), locale.join("b/a/b"), locale.join("b/a/c")].into_iter()));
-    assert_eq!(files_3, HashSet::from_iter([locale.join("c/b/a")].into_iter()));
+    assert_eq!(files_2, HashSet::from_iter(["b/a/a", "b/a/b", "b/a/c"].map(|path| locale.join(path).into_std_path_buf())));
+    assert_eq!(files_3, HashSet::from_iter([locale.join("c/b/a").into_std_path_buf()]));
   });
 }
 
@@ -98,6 +105,31 @@ fn relative_given_path_returns_project_relative_path() {
   run_unit(|| {
     let path = locale().join("a/a/a");
 
-    assert_eq!(relative(path), "a/a/a");
+    assert_eq!(relative(path), Path::new("a/a/a"));
+  });
+}
+
+#[test]
+#[serial]
+fn write_object_bytes_in_sha256_repository_returns_sha256_ids() {
+  run_unit(|| {
+    format::ObjectFormat::set(format::ObjectFormat::Sha256).unwrap();
+
+    let id = write_object_bytes(Object::Blob, DUMMY_TEXT).unwrap();
+
+    assert_eq!(id, format::ObjectFormat::Sha256.hash([BLOB_TYPE, DUMMY_TEXT].concat()));
+    assert_eq!(id.len(), 64);
+    assert_eq!(read_object_bytes(&id).unwrap(), DUMMY_TEXT.as_bytes());
+  });
+}
+
+#[test]
+#[serial]
+fn object_location_given_malformed_id_returns_unrecognised_object_error() {
+  run_unit(|| {
+    assert!(matches!(object_location("a"), Err(crate::lib::errors::Errors::UnrecognisedObject(_))));
+    assert!(matches!(object_location(&"z".repeat(40)), Err(crate::lib::errors::Errors::UnrecognisedObject(_))));
+    assert!(object_location(&"a".repeat(40)).is_ok());
+    assert!(object_location(&"a".repeat(64)).is_ok());
   });
 }
@@ -0,0 +1,72 @@
+use std::fs;
+use chrono::Utc;
+use serial_test::serial;
+use crate::{tests::{run_unit, factory::commit}, core::commit::Commit, lib::{identity::Identity, signature::{SigningKey, SignatureStatus, SIGNING_KEY_KEY, TRUSTED_KEYS_KEY}, object::FromId, config::{Config, Scope}, user::{User, EMAIL_KEY}, errors::Errors, locale}};
+
+fn trust(key: &SigningKey, email: &str) {
+  Config::set(Scope::Repository, TRUSTED_KEYS_KEY, "trusted").unwrap();
+  fs::write(locale().join("trusted"), format!("# comment\n{} {}\n", email, key.public_key())).unwrap();
+}
+
+fn signing_key() -> SigningKey {
+  Config::set(Scope::Repository, EMAIL_KEY, "jane@example.com").unwrap();
+  Config::set(Scope::Repository, SIGNING_KEY_KEY, "key").unwrap();
+  SigningKey::generate().unwrap()
+}
+
+#[test]
+#[serial]
+fn generate_writes_a_key_that_load_reads_back() {
+  run_unit(|| {
+    let key = signing_key();
+
+    assert_eq!(SigningKey::load().unwrap().public_key(), key.public_key());
+    assert!(matches!(SigningKey::generate(), Err(Errors::BadArguments(_))));
+  });
+}
+
+#[test]
+#[serial]
+fn verify_given_commit_signed_by_trusted_key_returns_good() {
+  run_unit(|| {
+    let key = signing_key();
+    trust(&key, "jane@example.com");
+    let commit = Commit::signed(None, commit().tree_id(), "signed\n\nbody", Identity::author().unwrap(), Identity::committer().unwrap(), &key).unwrap();
+
+    assert_eq!(commit.verify().unwrap(), SignatureStatus::Good(String::from("jane@example.com")));
+    assert_eq!(Commit::from_id(commit.id()).unwrap(), commit);
+  });
+}
+
+#[test]
+#[serial]
+fn verify_given_key_trusted_for_another_email_returns_bad() {
+  run_unit(|| {
+    let key = signing_key();
+    trust(&key, "john@example.com");
+    let commit = Commit::signed(None, commit().tree_id(), "signed", Identity::author().unwrap(), Identity::committer().unwrap(), &key).unwrap();
+
+    assert_eq!(commit.verify().unwrap(), SignatureStatus::Bad(String::from("jane@example.com")));
+  });
+}
+
+#[test]
+#[serial]
+fn verify_given_commit_by_another_author_checks_the_committer() {
+  run_unit(|| {
+    let key = signing_key();
+    trust(&key, "jane@example.com");
+    let author = Identity::new(&User::new("John", "john@example.com").unwrap(), Utc::now().into());
+    let commit = Commit::signed(None, commit().tree_id(), "signed", author, Identity::committer().unwrap(), &key).unwrap();
+
+    assert_eq!(commit.verify().unwrap(), SignatureStatus::Good(String::from("jane@example.com")));
+  });
+}
+
+#[test]
+#[serial]
+fn verify_given_unsigned_commit_returns_unsigned() {
+  run_unit(|| {
+    assert_eq!(commit().verify().unwrap(), SignatureStatus::Unsigned);
+  });
+}
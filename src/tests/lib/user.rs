@@ -1,5 +1,6 @@
+use std::{env, fs};
 use serial_test::serial;
-use crate::{lib::user::User, tests::run_unit};
+use crate::{lib::{locale, user::User, config::{Config, Scope}, constants::{GIT_CONFIG_PATH, GIT_GLOBAL_CONFIG_ENV}}, tests::{run_unit, run_unit_git}};
 
 const USERNAME: &str = "Jeremy";
 const EMAIL: &str = "jeremy@mail.com";
@@ -50,3 +51,65 @@ fn set_email_given_email_changes_the_current_user_email() {
     assert_eq!(user.email(), EMAIL);
   });
 }
+
+#[test]
+#[serial]
+fn get_given_username_with_spaces_returns_it() {
+  run_unit(|| {
+    User::set_username("Jane Doe").unwrap();
+
+    assert_eq!(User::get().unwrap().username(), "Jane Doe");
+  });
+}
+
+#[test]
+#[serial]
+fn get_prefers_the_repository_config_over_the_global_one() {
+  run_unit(|| {
+    Config::set(Scope::Global, "user.name", USERNAME).unwrap();
+    Config::set(Scope::Global, "user.email", EMAIL).unwrap();
+    User::set_email("jane@mail.com").unwrap();
+    let user = User::get().unwrap();
+
+    assert_eq!(user.username(), USERNAME);
+    assert_eq!(user.email(), "jane@mail.com");
+  });
+}
+
+#[test]
+#[serial]
+fn get_in_git_layout_reads_the_identity_from_git_configs() {
+  run_unit_git(|| {
+    fs::write(env::var(GIT_GLOBAL_CONFIG_ENV).unwrap(), format!("[user]\n\tname = {}\n\temail = global@mail.com\n", USERNAME)).unwrap();
+    let mut config = fs::read_to_string(locale().join(GIT_CONFIG_PATH)).unwrap();
+    config += &format!("[user]\n\temail = {}\n", EMAIL);
+    fs::write(locale().join(GIT_CONFIG_PATH), config).unwrap();
+    let user = User::get().unwrap();
+
+    assert_eq!(user.username(), USERNAME);
+    assert_eq!(user.email(), EMAIL);
+  });
+}
+
+#[test]
+#[serial]
+fn get_in_git_layout_prefers_the_rgit_config_over_the_git_one() {
+  run_unit_git(|| {
+    fs::write(env::var(GIT_GLOBAL_CONFIG_ENV).unwrap(), "[user]\n\tname = Git\n\temail = git@mail.com\n").unwrap();
+    User::set_username(USERNAME).unwrap();
+    let user = User::get().unwrap();
+
+    assert_eq!(user.username(), USERNAME);
+    assert_eq!(user.email(), "git@mail.com");
+  });
+}
+
+#[test]
+#[serial]
+fn get_in_rgit_layout_ignores_git_configs() {
+  run_unit(|| {
+    fs::write(env::var(GIT_GLOBAL_CONFIG_ENV).unwrap(), "[user]\n\tname = Git\n").unwrap();
+
+    assert_eq!(User::get().unwrap().username(), ADMIN);
+  });
+}
@@ -5,20 +5,46 @@ mod cli;
 use std::env;
 use assert_cmd::Command;
 use tempfile::TempDir;
-use crate::lib::{constants::PROJECT_ENV, initialize};
+use crate::lib::{constants::*, initialize, initialize_git};
 
 const TEMP_DIR_PREFIX: &str = "rgit-test";
 
+// Points the system and global configs into a fresh folder and clears identity overrides, so the
+// machine's own settings never leak into tests. The folder lives as long as the returned handle.
+fn isolate_config() -> TempDir {
+  let config_dir = TempDir::new().unwrap();
+  env::set_var(SYSTEM_CONFIG_ENV, config_dir.path().join("system"));
+  env::set_var(GLOBAL_CONFIG_ENV, config_dir.path().join("global"));
+  env::set_var(GIT_GLOBAL_CONFIG_ENV, config_dir.path().join("git-global"));
+
+  for variable in [AUTHOR_NAME_ENV, AUTHOR_EMAIL_ENV, AUTHOR_DATE_ENV, COMMITTER_NAME_ENV, COMMITTER_EMAIL_ENV, COMMITTER_DATE_ENV] {
+    env::remove_var(variable);
+  }
+
+  config_dir
+}
+
 pub fn run_unit(body: fn()) {
   let temp_dir = TempDir::new().unwrap();
+  let _config_dir = isolate_config();
   env::set_var(PROJECT_ENV, temp_dir.path());
   initialize().unwrap();
 
   body();
 }
 
+pub fn run_unit_git(body: fn()) {
+  let temp_dir = TempDir::new().unwrap();
+  let _config_dir = isolate_config();
+  env::set_var(PROJECT_ENV, temp_dir.path());
+  initialize_git().unwrap();
+
+  body();
+}
+
 pub fn run_acceptance(name: &str, body: fn(command: &mut Command) -> ()) {
   let temp_dir = TempDir::new().unwrap();
+  let _config_dir = isolate_config();
   env::set_var(PROJECT_ENV, temp_dir.path());
   initialize().unwrap();
 
@@ -29,8 +55,9 @@ pub fn run_acceptance(name: &str, body: fn(command: &mut Command) -> ()) {
 }
 
 mod factory {
+  use std::{fs, os::unix::fs::PermissionsExt};
   use lipsum::lipsum;
-  use crate::core::{blob::Blob, tree::{Tree, Node}, commit::Commit, branch::Branch};
+  use crate::{core::{blob::Blob, tree::{Tree, Node}, commit::Commit, branch::Branch}, lib::{locale, layout::Layout}};
 
   pub const TREE_PATHS: &[&str] = &[
     "a/a/a", "a/a/b", "a/a/c",
@@ -57,7 +84,7 @@ mod factory {
     let mut tree = Tree::new();
 
     for path in TREE_PATHS {
-      tree.insert(path, Node::Blob(blob()));
+      tree.insert(path, Node::Blob(blob())).unwrap();
     }
 
     tree.pack().unwrap();
@@ -69,6 +96,13 @@ mod factory {
   }
 
   pub fn branch() -> Branch {
-    Branch::new(&lipsum(25), Some(commit().id())).unwrap()
+    let name = lipsum(25).split_whitespace().map(|word| word.trim_matches(|character: char| !character.is_alphanumeric())).collect::<Vec<&str>>().join("-");
+    Branch::new(&name, Some(commit().id())).unwrap()
+  }
+
+  pub fn hook(name: &str, script: &str) {
+    let location = locale().join(Layout::get().hooks_path()).join(name);
+    fs::write(&location, format!("#!/bin/sh\n{}\n", script)).unwrap();
+    fs::set_permissions(&location, fs::Permissions::from_mode(0o755)).unwrap();
   }
 }